@@ -0,0 +1,95 @@
+//! Benchmarks of the simulation, run with `cargo bench`.
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use rand::prelude::*;
+
+use genetic_snake_rs::game_engine::*;
+use genetic_snake_rs::heuristic_bot::{compute_stats_from, HeuristicBot, MAX_DEPTH};
+use genetic_snake_rs::random_bot::RandomBot;
+
+const NB_BOTS: u32 = 4;
+
+fn full_game<F>(seed: u64, create_bot: F) -> GameResults
+where
+    F: Fn(u32) -> Box<dyn SnakeBot>,
+{
+    let mut game = Game::with_seed(seed);
+    game.continue_simulation_if_known_winner(false)
+        .max_steps(Game::MAX_UNWATCHED_STEPS);
+    for id in 0..NB_BOTS {
+        game.add_snake(id, create_bot(id));
+    }
+    game.initialize().run_to_end()
+}
+
+fn bench_full_games(c: &mut Criterion) {
+    let mut seed = 0;
+    c.bench_function("full game, 4 random bots", |b| {
+        b.iter(|| {
+            seed += 1;
+            full_game(seed, |id| Box::new(RandomBot::with_seed(seed + id as u64)))
+        })
+    });
+
+    let mut seed = 0;
+    c.bench_function("full game, 4 heuristic bots", |b| {
+        b.iter(|| {
+            seed += 1;
+            full_game(seed, |_| Box::new(HeuristicBot::default()))
+        })
+    });
+}
+
+/// A board in the middle of a game: a few snakes, some obstacles and food.
+fn representative_board() -> GameBoard {
+    let mut rng = StdRng::seed_from_u64(42);
+    let mut board = GameBoard::new(StdRng::seed_from_u64(0));
+
+    for _ in 0..10 {
+        let coord = Coordinate {
+            x: rng.gen_range(0, board.width()),
+            y: rng.gen_range(0, board.height()),
+        };
+        board.set_tile_at_coord(&coord, Cell::Obstacle).unwrap();
+    }
+
+    // Snakes along horizontal lines
+    for id in 0..NB_BOTS {
+        let y = 2 + 4 * id as i32;
+        for x in 4..14 {
+            board
+                .set_tile_at_coord(&Coordinate { x, y }, Cell::SnakeBody(id))
+                .unwrap();
+        }
+        board
+            .set_tile_at_coord(&Coordinate { x: 4, y }, Cell::SnakeTail(id))
+            .unwrap();
+        board
+            .set_tile_at_coord(&Coordinate { x: 14, y }, Cell::SnakeHead(id))
+            .unwrap();
+    }
+
+    for _ in 0..15 {
+        let coord = Coordinate {
+            x: rng.gen_range(0, board.width()),
+            y: rng.gen_range(0, board.height()),
+        };
+        if board.is_coord_free_or_food(&coord) {
+            board.set_tile_at_coord(&coord, Cell::Food).unwrap();
+        }
+    }
+
+    board.nb_alive_snakes = NB_BOTS as usize;
+    board
+}
+
+fn bench_compute_stats(c: &mut Criterion) {
+    let board = representative_board();
+    let from = Some(Coordinate { x: 15, y: 2 });
+    c.bench_function("compute_stats_from", |b| {
+        b.iter(|| compute_stats_from(0, black_box(&from), &board, MAX_DEPTH))
+    });
+}
+
+criterion_group!(benches, bench_full_games, bench_compute_stats);
+criterion_main!(benches);
@@ -0,0 +1,136 @@
+//! A bot going to the nearest food with A*.
+
+use std::{cmp::Reverse, collections::BinaryHeap};
+
+use crate::game_engine::*;
+use crate::heuristic_bot::{compute_stats_from, MAX_DEPTH};
+
+/// Follows a shortest path to the nearest reachable food, found with A*. The
+/// snake bodies, the obstacles and the walls block the paths, and each cell
+/// costs one step, mud included. When no food is reachable, the bot goes
+/// where the accessible area is the largest.
+#[derive(Default)]
+pub struct AStarBot;
+
+impl AStarBot {
+    /// Creates a new `AStarBot` for each game.
+    pub fn factory() -> BotFactory<'static> {
+        Box::new(|| Box::new(AStarBot))
+    }
+}
+
+impl SnakeBot for AStarBot {
+    fn get_next_action(&mut self, myself: &SnakeState, board: &GameBoard) -> Action {
+        let head = match myself.try_get_head_coord(board.size()) {
+            Some(head) => head,
+            None => return Action::Front,
+        };
+        let next_coords: Vec<(Action, Option<Coordinate>)> =
+            [Action::Left, Action::Front, Action::Right]
+                .iter()
+                .map(|action| {
+                    let orientation = next_orientation(&myself.current_orientation, action);
+                    let next =
+                        next_coord_towards(&head, &orientation, board.size(), board.wrap_mode());
+                    (action.clone(), next)
+                })
+                .collect();
+
+        let first_steps: Vec<Coordinate> = next_coords
+            .iter()
+            .filter_map(|(_, next)| next.clone())
+            .filter(|next| board.is_coord_free_or_food(next))
+            .collect();
+        if let Some(first_step) = first_step_to_nearest_food(&first_steps, board) {
+            return next_coords
+                .into_iter()
+                .find(|(_, next)| next.as_ref() == Some(&first_step))
+                .unwrap()
+                .0;
+        }
+
+        // No food is reachable, so survive as long as possible
+        let mut best = (Action::Front, -1.);
+        for (action, next) in next_coords {
+            let area = compute_stats_from(myself.id, &next, board, MAX_DEPTH).accessible_area;
+            if area > best.1 {
+                best = (action, area);
+            }
+        }
+        best.0
+    }
+}
+
+/// Returns which of the `starts` begins a shortest path to the nearest food,
+/// or `None` if no food can be reached from them.
+fn first_step_to_nearest_food(starts: &[Coordinate], board: &GameBoard) -> Option<Coordinate> {
+    let size = board.size();
+    let food: Vec<Coordinate> = board
+        .food_positions()
+        .map(|pos| Coordinate::from_pos(pos, size))
+        .collect();
+    if food.is_empty() {
+        return None;
+    }
+    let heuristic = |coord: &Coordinate| {
+        food.iter()
+            .map(|food| board.distance(coord, food))
+            .min()
+            .unwrap_or(0)
+    };
+
+    // The open set, by estimated total cost, then by cost so far. Each entry
+    // also remembers the start it comes from.
+    let mut open = BinaryHeap::new();
+    let mut closed = vec![false; size.nb_cells()];
+    for (i, start) in starts.iter().enumerate() {
+        open.push(Reverse((1 + heuristic(start), 1, start.to_pos(size), i)));
+    }
+
+    while let Some(Reverse((_, cost, pos, start))) = open.pop() {
+        if closed[pos as usize] {
+            continue;
+        }
+        closed[pos as usize] = true;
+
+        if board.get_tile_at_pos(pos) == Cell::Food {
+            return Some(starts[start].clone());
+        }
+        for (coord, cell) in board.neighbors(&Coordinate::from_pos(pos, size)) {
+            let next_pos = coord.to_pos(size);
+            if !closed[next_pos as usize] && !cell.is_blocking() {
+                let next_cost = cost + 1;
+                open.push(Reverse((
+                    next_cost + heuristic(&coord),
+                    next_cost,
+                    next_pos,
+                    start,
+                )));
+            }
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn goes_around_obstacles_by_the_shortest_route() {
+        // The food is behind a wall, which is shorter to go around by the top
+        let board = GameBoard::from_ascii(
+            ".....\n\
+             .0#o.\n\
+             ..#..\n\
+             ..#..\n\
+             ..#..\n\
+             .....",
+        )
+        .unwrap();
+        let myself =
+            SnakeState::with_head(Coordinate { x: 1, y: 1 }, Orientation::East, board.size());
+
+        assert_eq!(AStarBot.get_next_action(&myself, &board), Action::Left);
+    }
+}
@@ -0,0 +1,100 @@
+//! Elo ratings of bots, computed from the outcomes of their games.
+
+use std::collections::HashMap;
+
+/// The rating of a bot before its first game.
+pub const INITIAL_RATING: f64 = 1500.;
+
+/// A common K-factor: the maximum rating change after one game.
+pub const DEFAULT_K: f64 = 32.;
+
+/// The outcome of a game between two bots, given by their names.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MatchOutcome {
+    pub first: String,
+    pub second: String,
+    /// The name of the winner, `None` for a draw.
+    pub winner: Option<String>,
+}
+
+impl MatchOutcome {
+    /// The score of the first bot: 1 for a win, 0.5 for a draw and 0 for a
+    /// loss.
+    fn first_score(&self) -> f64 {
+        match &self.winner {
+            Some(winner) if *winner == self.first => 1.,
+            Some(_) => 0.,
+            None => 0.5,
+        }
+    }
+}
+
+/// Computes the Elo ratings of the bots from `results`, applied in order,
+/// each bot starting at `INITIAL_RATING`. See `compute_elo_from()`.
+pub fn compute_elo(results: &[MatchOutcome], k: f64) -> HashMap<String, f64> {
+    compute_elo_from(results, k, INITIAL_RATING)
+}
+
+/// Computes the Elo ratings of the bots from `results`, applied in order,
+/// each bot starting at `initial_rating`. After each game, both ratings move
+/// by `k` times the difference between the actual and the expected score, a
+/// draw counting as half a point.
+pub fn compute_elo_from(
+    results: &[MatchOutcome],
+    k: f64,
+    initial_rating: f64,
+) -> HashMap<String, f64> {
+    let mut ratings = HashMap::new();
+    for outcome in results {
+        let first = *ratings.get(&outcome.first).unwrap_or(&initial_rating);
+        let second = *ratings.get(&outcome.second).unwrap_or(&initial_rating);
+
+        let expected = 1. / (1. + 10f64.powf((second - first) / 400.));
+        let delta = k * (outcome.first_score() - expected);
+        ratings.insert(outcome.first.clone(), first + delta);
+        ratings.insert(outcome.second.clone(), second - delta);
+    }
+    ratings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn outcome(first: &str, second: &str, winner: Option<&str>) -> MatchOutcome {
+        MatchOutcome {
+            first: first.to_string(),
+            second: second.to_string(),
+            winner: winner.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn ratings_move_towards_the_results() {
+        let results = vec![
+            outcome("champion", "challenger", Some("challenger")),
+            outcome("challenger", "champion", Some("challenger")),
+            outcome("champion", "challenger", None),
+            outcome("champion", "newcomer", None),
+        ];
+
+        // An even first game moves the ratings by k/2
+        let ratings = compute_elo(&results[..1], 32.);
+        assert_eq!(ratings["challenger"], 1516.);
+        assert_eq!(ratings["champion"], 1484.);
+
+        let ratings = compute_elo(&results, 32.);
+        assert!(ratings["challenger"] > ratings["champion"]);
+        // The draw against the weaker bot moves the challenger down
+        assert!(ratings["challenger"] < compute_elo(&results[..2], 32.)["challenger"]);
+        // Even a newcomer loses points in a draw against a weaker bot
+        assert!(ratings["newcomer"] < INITIAL_RATING);
+        // The points are only exchanged between the bots
+        let total: f64 = ratings.values().sum();
+        assert!((total - 3. * INITIAL_RATING).abs() < 1e-9);
+
+        // A draw between equal bots changes nothing
+        let ratings = compute_elo_from(&results[3..], 32., 1000.);
+        assert_eq!(ratings["newcomer"], 1000.);
+    }
+}
@@ -0,0 +1,98 @@
+//! A bot avoiding the pockets of the board with flood fills.
+
+use crate::game_engine::*;
+
+/// Goes where the region of free cells reachable from the next head position
+/// is the largest, so that it doesn't trap itself in a pocket. The ties are
+/// broken towards the nearest food, by path length, which makes this bot a
+/// good late-game survivor but a slow grower.
+#[derive(Default)]
+pub struct FloodFillBot;
+
+impl FloodFillBot {
+    /// Creates a new `FloodFillBot` for each game.
+    pub fn factory() -> BotFactory<'static> {
+        Box::new(|| Box::new(FloodFillBot))
+    }
+}
+
+impl SnakeBot for FloodFillBot {
+    fn get_next_action(&mut self, myself: &SnakeState, board: &GameBoard) -> Action {
+        let head = match myself.try_get_head_coord(board.size()) {
+            Some(head) => head,
+            None => return Action::Front,
+        };
+        let food: Vec<Coordinate> = board
+            .food_positions()
+            .map(|pos| Coordinate::from_pos(pos, board.size()))
+            .collect();
+
+        [Action::Left, Action::Front, Action::Right]
+            .iter()
+            .map(|action| {
+                let orientation = next_orientation(&myself.current_orientation, action);
+                let next = next_coord_towards(&head, &orientation, board.size(), board.wrap_mode())
+                    .filter(|next| board.is_coord_free_or_food(next));
+                // Unreachable food doesn't count
+                let (area, dist_to_food) = match next {
+                    Some(next) => (
+                        board.accessible_region_size(&next),
+                        food.iter()
+                            .filter_map(|food| board.bfs_distance(&next, food))
+                            .min()
+                            .unwrap_or(i32::MAX),
+                    ),
+                    None => (0, i32::MAX),
+                };
+                (action.clone(), area, -dist_to_food)
+            })
+            // The first of the best actions, preferring Left in a perfect tie
+            .fold(
+                None,
+                |best: Option<(Action, usize, i32)>, candidate| match best {
+                    Some(best) if (best.1, best.2) >= (candidate.1, candidate.2) => Some(best),
+                    _ => Some(candidate),
+                },
+            )
+            .unwrap()
+            .0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn avoids_the_pockets() {
+        // Going left or forward boxes the snake in
+        let board = GameBoard::from_ascii(
+            "#.#.#......\n\
+             #.#.#......\n\
+             #..0.......\n\
+             ###########",
+        )
+        .unwrap();
+        let myself =
+            SnakeState::with_head(Coordinate { x: 3, y: 2 }, Orientation::North, board.size());
+
+        assert_eq!(FloodFillBot.get_next_action(&myself, &board), Action::Right);
+    }
+
+    #[test]
+    fn goes_to_the_food_nearest_by_path() {
+        // The food on the left is as near as the one on the right, but walled
+        // off
+        let board = GameBoard::from_ascii(
+            "o#....o\n\
+             .#.....\n\
+             .#.0...\n\
+             .#.....",
+        )
+        .unwrap();
+        let myself =
+            SnakeState::with_head(Coordinate { x: 3, y: 2 }, Orientation::West, board.size());
+
+        assert_eq!(FloodFillBot.get_next_action(&myself, &board), Action::Right);
+    }
+}
@@ -1,48 +1,145 @@
 use rand::prelude::*;
 
-use std::{collections::VecDeque, fmt};
+use std::{
+    cmp::min,
+    collections::{hash_map::DefaultHasher, BTreeMap, BTreeSet, HashMap, VecDeque},
+    convert::TryFrom,
+    fmt,
+    hash::{Hash, Hasher},
+    time::{Duration, Instant},
+};
 
-use colored::Colorize;
+use colored::{Color, Colorize};
+
+use crate::interrupt::catch_interrupts;
 
 pub type SnakeId = u32;
 
-#[derive(Debug, Copy, Clone, PartialEq)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Cell {
     Empty,
     Food,
     Obstacle,
+    /// Mud can be crossed like an empty cell, but `HeuristicBot` counts it as
+    /// `MUD_COST` steps, so bots tend to avoid it.
+    Mud,
     Wall,
     SnakeHead(SnakeId),
     SnakeBody(SnakeId),
     SnakeTail(SnakeId),
 }
 
+impl Cell {
+    /// The colors of the snakes, by ID. Magenta is kept for the food.
+    const SNAKE_PALETTE: [Color; 10] = [
+        Color::Green,
+        Color::Red,
+        Color::Blue,
+        Color::Cyan,
+        Color::Yellow,
+        Color::BrightGreen,
+        Color::BrightRed,
+        Color::BrightBlue,
+        Color::BrightCyan,
+        Color::BrightYellow,
+    ];
+
+    /// The ID of the snake occupying the cell, if any.
+    #[inline]
+    pub fn snake_id(&self) -> Option<SnakeId> {
+        match self {
+            Cell::SnakeHead(id) | Cell::SnakeBody(id) | Cell::SnakeTail(id) => Some(*id),
+            _ => None,
+        }
+    }
+
+    #[inline]
+    pub fn is_snake(&self) -> bool {
+        self.snake_id().is_some()
+    }
+
+    /// Whether a snake moving into the cell dies. Mud isn't blocking, as
+    /// the snakes can go through it.
+    #[inline]
+    pub fn is_blocking(&self) -> bool {
+        !matches!(self, Cell::Empty | Cell::Food | Cell::Mud)
+    }
+}
+
 impl fmt::Display for Cell {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let colorize_snake = |id, s: String| match id {
-            0 => s.green(),
-            1 => s.red(),
-            2 => s.blue(),
-            3 => s.cyan(),
-            4 => s.yellow(),
-            _ => s.white(),
+        // Cycle through the palette, first in normal, then in bold and then
+        // in dimmed colors
+        let colorize_snake = |id: SnakeId, s: String| {
+            let n = id as usize;
+            let s = s.color(Self::SNAKE_PALETTE[n % Self::SNAKE_PALETTE.len()]);
+            match (n / Self::SNAKE_PALETTE.len()) % 3 {
+                0 => s,
+                1 => s.bold(),
+                _ => s.dimmed(),
+            }
         };
+        // One character per cell, even with more than ten snakes
+        let body_glyph = |id: SnakeId| std::char::from_digit(id % 36, 36).unwrap().to_string();
 
         match self {
             Cell::Empty => write!(f, " "),
             Cell::Food => write!(f, "{}", "o".magenta().bold()),
             Cell::Obstacle => write!(f, "#"),
+            Cell::Mud => write!(f, "{}", "~".yellow().dimmed()),
             Cell::Wall => unreachable!(),
             Cell::SnakeHead(id) => write!(f, "{}", colorize_snake(*id, "H".to_string())),
             Cell::SnakeTail(id) => write!(f, "{}", colorize_snake(*id, "T".to_string())),
-            Cell::SnakeBody(id) => write!(f, "{}", colorize_snake(*id, format!("{}", id))),
+            Cell::SnakeBody(id) => write!(f, "{}", colorize_snake(*id, body_glyph(*id))),
         }
     }
 }
 
 pub type Position = i32;
 
+/// The dimensions of a `GameBoard`, in cells.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BoardSize {
+    pub width: i32,
+    pub height: i32,
+}
+
+impl BoardSize {
+    pub fn new(width: i32, height: i32) -> Self {
+        assert!(width > 0 && height > 0, "The board can't be empty.");
+        BoardSize { width, height }
+    }
+
+    #[inline]
+    pub fn nb_cells(&self) -> usize {
+        (self.width * self.height) as usize
+    }
+}
+
+impl Default for BoardSize {
+    fn default() -> Self {
+        BoardSize {
+            width: 32,
+            height: 16,
+        }
+    }
+}
+
+/// Whether the snakes leaving the board by one edge enter it again by the
+/// opposite edge, see `Game::wrap_mode()`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum WrapMode {
+    /// The edges of the board are walls.
+    #[default]
+    None,
+    Wrap,
+}
+
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Coordinate {
     pub x: i32,
     pub y: i32,
@@ -50,31 +147,68 @@ pub struct Coordinate {
 
 impl Coordinate {
     #[inline]
-    pub fn to_pos(&self) -> i32 {
+    pub fn to_pos(&self, size: BoardSize) -> i32 {
         // The position isn't checked because out-of-bounds means WALL.
         // assert!(...);
 
-        self.x + self.y * BOARD_WIDTH
+        self.x + self.y * size.width
     }
 
     #[inline]
-    pub fn from_pos(position: i32) -> Self {
+    pub fn from_pos(position: i32, size: BoardSize) -> Self {
         // The position isn't checked because out-of-bounds means WALL.
-        // assert!(self >= 0 && self < BOARD_WIDTH * BOARD_HEIGHT);
+        // assert!(self >= 0 && self < size.width * size.height);
 
         Coordinate {
-            x: position % BOARD_WIDTH,
-            y: position / BOARD_WIDTH,
+            x: position % size.width,
+            y: position / size.width,
+        }
+    }
+
+    #[inline]
+    pub fn is_out_of_bounds(&self, size: BoardSize) -> bool {
+        self.x < 0 || self.x >= size.width || self.y < 0 || self.y >= size.height
+    }
+
+    /// The coordinate itself if it's on a board of `size`, the one on the
+    /// opposite side of the board if it's just outside of it in
+    /// `WrapMode::Wrap`, `None` otherwise.
+    #[inline]
+    pub fn on_board(self, size: BoardSize, wrap_mode: WrapMode) -> Option<Coordinate> {
+        if !self.is_out_of_bounds(size) {
+            return Some(self);
         }
+        match wrap_mode {
+            WrapMode::None => None,
+            WrapMode::Wrap => Some(Coordinate {
+                x: self.x.rem_euclid(size.width),
+                y: self.y.rem_euclid(size.height),
+            }),
+        }
+    }
+
+    /// The distance ignoring everything on the board, and the edges of the
+    /// wrapping boards, see `distance()`.
+    #[inline]
+    #[allow(dead_code)]
+    pub fn manhattan_distance(&self, other: &Coordinate) -> i32 {
+        (self.x - other.x).abs() + (self.y - other.y).abs()
     }
 
+    /// The Manhattan distance on a board of `size`, going through the edges
+    /// in `WrapMode::Wrap`. See also `GameBoard::distance()`.
     #[inline]
-    pub fn is_out_of_bounds(&self) -> bool {
-        self.x < 0 || self.x >= BOARD_WIDTH || self.y < 0 || self.y >= BOARD_HEIGHT
+    pub fn distance(&self, other: &Coordinate, size: BoardSize, wrap_mode: WrapMode) -> i32 {
+        let (dx, dy) = ((self.x - other.x).abs(), (self.y - other.y).abs());
+        match wrap_mode {
+            WrapMode::None => dx + dy,
+            WrapMode::Wrap => dx.min(size.width - dx) + dy.min(size.height - dy),
+        }
     }
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Action {
     Left,
     Front,
@@ -82,6 +216,7 @@ pub enum Action {
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Orientation {
     North,
     East,
@@ -89,67 +224,92 @@ pub enum Orientation {
     West,
 }
 
+impl Orientation {
+    /// All the orientations, in clockwise order.
+    pub const ALL: [Orientation; 4] = [
+        Orientation::North,
+        Orientation::East,
+        Orientation::South,
+        Orientation::West,
+    ];
+
+    /// The index of the orientation in `ALL`.
+    fn index(&self) -> usize {
+        match self {
+            Orientation::North => 0,
+            Orientation::East => 1,
+            Orientation::South => 2,
+            Orientation::West => 3,
+        }
+    }
+
+    /// The orientation after a half-turn, which a snake can't do.
+    pub fn opposite(&self) -> Orientation {
+        Self::ALL[(self.index() + 2) % 4].clone()
+    }
+
+    /// The orientation after doing `action`: `Left` and `Right` turn by a
+    /// quarter, relative to the snake, and `Front` keeps the orientation.
+    /// North is towards `y = 0`, so turning right from North gives East.
+    pub fn turn(&self, action: &Action) -> Orientation {
+        let quarters = match action {
+            Action::Left => 3,
+            Action::Front => 0,
+            Action::Right => 1,
+        };
+        Self::ALL[(self.index() + quarters) % 4].clone()
+    }
+
+    /// The `(dx, dy)` of a move towards this orientation, e.g. `(0, -1)` for
+    /// North.
+    pub fn delta(&self) -> (i32, i32) {
+        match self {
+            Orientation::North => (0, -1),
+            Orientation::East => (1, 0),
+            Orientation::South => (0, 1),
+            Orientation::West => (-1, 0),
+        }
+    }
+}
+
+/// Returns the orientation of a snake facing `current_orientation` after
+/// doing `action`, see `Orientation::turn()`.
 pub fn next_orientation(current_orientation: &Orientation, action: &Action) -> Orientation {
-    match *current_orientation {
-        Orientation::North => match *action {
-            Action::Left => Orientation::West,
-            Action::Front => Orientation::North,
-            Action::Right => Orientation::East,
-        },
-        Orientation::East => match *action {
-            Action::Left => Orientation::North,
-            Action::Front => Orientation::East,
-            Action::Right => Orientation::South,
-        },
-        Orientation::South => match *action {
-            Action::Left => Orientation::East,
-            Action::Front => Orientation::South,
-            Action::Right => Orientation::West,
-        },
-        Orientation::West => match *action {
-            Action::Left => Orientation::South,
-            Action::Front => Orientation::West,
-            Action::Right => Orientation::North,
-        },
-    }
-}
-
-/// Returns None if the move leads outside of the board
-pub fn next_coord_towards(from: &Coordinate, orientation: &Orientation) -> Option<Coordinate> {
-    // Check if the move is in-bounds
-    if (*orientation == Orientation::West && from.x == 0)
-        || (*orientation == Orientation::East && from.x == BOARD_WIDTH - 1)
-        || (*orientation == Orientation::North && from.y == 0)
-        || (*orientation == Orientation::South && from.y == BOARD_HEIGHT - 1)
-    {
-        return None;
-    }
-
-    let next_coord = match orientation {
-        Orientation::North => Coordinate {
-            x: from.x,
-            y: from.y - 1,
-        },
-        Orientation::East => Coordinate {
-            x: from.x + 1,
-            y: from.y,
-        },
-        Orientation::South => Coordinate {
-            x: from.x,
-            y: from.y + 1,
-        },
-        Orientation::West => Coordinate {
-            x: from.x - 1,
-            y: from.y,
-        },
-    };
-    Some(next_coord)
+    current_orientation.turn(action)
+}
+
+/// Returns the coordinate adjacent to `from` in the direction of
+/// `orientation`, where North decreases `y` and West decreases `x`.
+///
+/// Returns None if the move leads outside of a board of `size`, i.e. when
+/// going West from `x = 0`, East from `x = width - 1`, North from `y = 0` or
+/// South from `y = height - 1`. With `WrapMode::Wrap`, the move leads to the
+/// opposite side of the board instead.
+///
+/// `from` is expected to be on the board, otherwise the result is unspecified.
+pub fn next_coord_towards(
+    from: &Coordinate,
+    orientation: &Orientation,
+    size: BoardSize,
+    wrap_mode: WrapMode,
+) -> Option<Coordinate> {
+    let (dx, dy) = orientation.delta();
+    Coordinate {
+        x: from.x + dx,
+        y: from.y + dy,
+    }
+    .on_board(size, wrap_mode)
 }
 
 pub trait SnakeBot {
     fn get_next_action(&mut self, myself: &SnakeState, board: &GameBoard) -> Action;
 }
 
+/// Creates a new bot for each game, because `SnakeBot`s are stateful and
+/// consumed by the `Game`, so they can't be reused.
+pub type BotFactory<'a> = Box<dyn Fn() -> Box<dyn SnakeBot + 'a> + 'a>;
+
+#[derive(Clone)]
 pub struct SnakeState {
     pub id: SnakeId,
     /// Contains the positions of the snake body parts.
@@ -157,123 +317,259 @@ pub struct SnakeState {
     pub positions: VecDeque<Position>,
     pub current_orientation: Orientation,
     pub alive: bool,
+    /// The remaining health, which is decreased at each step and refilled
+    /// to `max_health` when eating. The snake starves when it reaches zero.
+    /// `None` if the game doesn't have the hunger rule.
+    pub health: Option<u32>,
+    pub max_health: Option<u32>,
 }
 
 impl SnakeState {
+    /// A live snake #0 reduced to its head, to test the bots.
+    #[cfg(test)]
+    pub fn with_head(head: Coordinate, orientation: Orientation, size: BoardSize) -> Self {
+        SnakeState {
+            id: 0,
+            positions: VecDeque::from(vec![head.to_pos(size)]),
+            current_orientation: orientation,
+            alive: true,
+            health: None,
+            max_health: None,
+        }
+    }
+
+    /// Panics if the snake has no cell, i.e. before the game started. See
+    /// `try_get_head_pos()` for a non-panicking version.
     #[inline]
     pub fn get_head_pos(&self) -> Position {
-        *self
-            .positions
-            .front()
+        self.try_get_head_pos()
             .expect("get_head_pos() called before the game started.")
     }
 
+    /// Panics if the snake has no cell, see `get_head_pos()`.
+    #[inline]
+    pub fn get_head_coord(&self, size: BoardSize) -> Coordinate {
+        Coordinate::from_pos(self.get_head_pos(), size)
+    }
+
+    /// The position of the head, `None` if the snake has no cell.
+    #[inline]
+    pub fn try_get_head_pos(&self) -> Option<Position> {
+        self.positions.front().cloned()
+    }
+
+    /// The coordinate of the head, `None` if the snake has no cell.
+    #[inline]
+    pub fn try_get_head_coord(&self, size: BoardSize) -> Option<Coordinate> {
+        self.try_get_head_pos()
+            .map(|pos| Coordinate::from_pos(pos, size))
+    }
+
+    /// The number of cells of the snake, head and tail included.
     #[inline]
-    pub fn get_head_coord(&self) -> Coordinate {
-        Coordinate::from_pos(self.get_head_pos())
+    pub fn length(&self) -> usize {
+        self.positions.len()
+    }
+
+    /// Whether `pos` is one of the cells of the snake, including its head and
+    /// its tail. Note that the tail moves away at the next step, unless the
+    /// snake grows.
+    pub fn body_contains(&self, pos: Position) -> bool {
+        self.positions.contains(&pos)
     }
 }
 
-pub struct Snake<'a> {
+/// A snake, without the bot which controls it.
+#[derive(Clone)]
+pub struct Snake {
     pub state: SnakeState,
-    bot: Box<dyn SnakeBot + 'a>,
     just_died: bool,
+    death_cause: Option<DeathCause>,
+    /// The step during which the snake died.
+    died_at_step: Option<u32>,
+    food_eaten: u32,
 
     /// The field gets decreased by one at each step. When it reaches zero,
-    /// the snake grows and the field takes the value `GROWTH_RATE`.
+    /// the snake grows and the field takes the value `config.growth_rate`.
     growth_state: i32,
+    config: SnakeConfig,
+}
+
+/// The rules which can be different for each snake.
+#[derive(Debug, Clone)]
+pub struct SnakeConfig {
+    /// The number of cells of the snake at the beginning, head included.
+    /// The body is laid out behind the head.
+    pub initial_length: usize,
+    /// The snake grows every `growth_rate` steps.
+    pub growth_rate: i32,
+}
+
+impl Default for SnakeConfig {
+    fn default() -> Self {
+        SnakeConfig {
+            initial_length: 1,
+            growth_rate: Snake::GROWTH_RATE,
+        }
+    }
 }
 
-impl<'a> Snake<'a> {
+impl Snake {
     pub const GROWTH_RATE: i32 = 3;
     pub const POSITIONS_VEC_INITIAL_CAPACITY: usize = 64;
 
-    fn new(id: u32, bot: Box<dyn SnakeBot + 'a>) -> Self {
+    fn new(id: u32, config: SnakeConfig) -> Self {
         Snake {
             state: SnakeState {
                 id,
                 positions: VecDeque::with_capacity(Self::POSITIONS_VEC_INITIAL_CAPACITY),
-                current_orientation: Orientation::North, // Random value
+                current_orientation: Orientation::North, // Set in Game::initialize()
                 alive: true,
+                health: None,
+                max_health: None,
             },
-            bot,
             just_died: false,
-            growth_state: Self::GROWTH_RATE,
+            death_cause: None,
+            died_at_step: None,
+            food_eaten: 0,
+            growth_state: config.growth_rate,
+            config,
         }
     }
 
-    /// Ask the `SnakeBot` its next action.
-    /// Returns None if the snake is dead.
-    fn get_next_action(&mut self, board: &GameBoard) -> Option<Action> {
-        if !self.state.alive {
-            eprintln!("get_next_action() called on a dead snake!");
-            return None;
+    /// Marks the snake as dead, if it isn't already. The first cause of
+    /// death is kept.
+    fn die(&mut self, cause: DeathCause) {
+        if !self.just_died {
+            self.just_died = true;
+            self.death_cause = Some(cause);
         }
-
-        // Ask the bot for its next action
-        Some(self.bot.get_next_action(&self.state, board))
     }
 
-    /// Returns whether the snake is dead or alive after this move.
+    /// Moves the snake, and sets `just_died` if it died during this move.
+    /// Returns whether the snake ate food.
     /// Heads-up: This doesn't say anything about collisions between snakes.
-    fn execute_action(&mut self, board: &mut GameBoard, action: &Action) {
+    ///
+    /// In `ghost_mode`, a move into another snake is cancelled: the snake
+    /// doesn't move, and nothing else happens to it during this step.
+    ///
+    /// Without `growth_mode`, the snake grows at its `config.growth_rate`.
+    ///
+    /// `contested_food` is the food which other snakes go for during the same
+    /// step: the snake eats it even if another snake moved there first.
+    fn execute_action(
+        &mut self,
+        board: &mut GameBoard,
+        action: &Action,
+        ghost_mode: bool,
+        growth_mode: Option<GrowthMode>,
+        contested_food: &[Position],
+    ) -> bool {
         if !self.state.alive {
             eprintln!("execute_action() called on a dead snake!");
-            return;
+            return false;
         }
 
         let current_orientation: Orientation = self.state.current_orientation.clone();
-        let next_orientation = next_orientation(&current_orientation, &action);
-        let current_head_pos = *self
-            .state
-            .positions
-            .front()
-            .expect("The game hasn't been initialized.");
-        let current_head_coord = Coordinate::from_pos(current_head_pos);
+        let next_orientation = next_orientation(&current_orientation, action);
+        let current_head_pos = match self.state.try_get_head_pos() {
+            Some(pos) => pos,
+            None => {
+                eprintln!("execute_action() called on a snake without head!");
+                return false;
+            }
+        };
+        let current_head_coord = Coordinate::from_pos(current_head_pos, board.size());
 
         // Determine the next head coordinate
-        let next_head_coord = next_coord_towards(&current_head_coord, &next_orientation);
+        let next_head_coord = next_coord_towards(
+            &current_head_coord,
+            &next_orientation,
+            board.size(),
+            board.wrap_mode(),
+        );
 
         // Check if the next position is out of the board => death & return
         if next_head_coord.is_none() {
-            self.just_died = true;
-            board.set_tile_at_pos(current_head_pos, Cell::SnakeBody(self.state.id));
-            return;
+            self.die(DeathCause::Wall);
+            board.set_tile_at_pos_unchecked(current_head_pos, Cell::SnakeBody(self.state.id));
+            return false;
         }
         let next_head_coord = next_head_coord.unwrap();
 
+        // Ghosts don't run into the other snakes, they wait
+        if ghost_mode {
+            if let Some(id) = board.get_tile_at_coord(&next_head_coord).snake_id() {
+                if id != self.state.id {
+                    return false;
+                }
+            }
+        }
+
         // Check if the next position is free => death
         if !board.is_coord_free_or_food(&next_head_coord) {
-            self.just_died = true;
+            self.die(match board.get_tile_at_coord(&next_head_coord) {
+                Cell::SnakeHead(id) => DeathCause::HeadOn(id),
+                Cell::SnakeBody(id) | Cell::SnakeTail(id) if id == self.state.id => {
+                    DeathCause::OwnBody
+                }
+                Cell::SnakeBody(id) | Cell::SnakeTail(id) => DeathCause::SnakeBody(id),
+                _ => DeathCause::Obstacle,
+            });
         }
 
         // Convert the coordinate to a position
-        let next_head_pos = next_head_coord.to_pos();
+        let next_head_pos = next_head_coord.to_pos(board.size());
 
         // Remember if the next position is food
         let next_pos_type = board.get_tile_at_pos(next_head_pos);
-        let food = next_pos_type == Cell::Food;
+        let food = next_pos_type == Cell::Food || contested_food.contains(&next_head_pos);
+        if food {
+            self.food_eaten += 1;
+        }
 
-        // Check the growth rate
-        assert!(self.growth_state > 0);
-        self.growth_state -= 1;
-        let growing = self.growth_state == 0;
-        if growing {
-            self.growth_state = Self::GROWTH_RATE;
+        // Eat or starve
+        if let (Some(health), Some(max_health)) = (&mut self.state.health, self.state.max_health) {
+            if food {
+                *health = max_health;
+            } else {
+                *health = health.saturating_sub(1);
+                if *health == 0 {
+                    self.die(DeathCause::Starvation);
+                }
+            }
         }
 
+        // Check the growth rate
+        let growth_rate = match growth_mode {
+            Some(GrowthMode::Periodic(growth_rate)) => Some(growth_rate),
+            Some(GrowthMode::FoodOnly) => None,
+            None => Some(self.config.growth_rate),
+        };
+        let growing = match growth_rate {
+            Some(growth_rate) => {
+                assert!(self.growth_state > 0);
+                self.growth_state -= 1;
+                let growing = self.growth_state == 0;
+                if growing {
+                    self.growth_state = growth_rate;
+                }
+                growing
+            }
+            None => false,
+        };
+
         // Update the snake
         self.state.positions.push_front(next_head_pos);
         self.state.current_orientation = next_orientation;
 
         // Change the current head to body
-        board.set_tile_at_pos(current_head_pos, Cell::SnakeBody(self.state.id));
+        board.set_tile_at_pos_unchecked(current_head_pos, Cell::SnakeBody(self.state.id));
 
         // Shrink the tail if doesn't grow
-        // FIXME: If >two heads go on the same cell, only the first snake eats the food.
         if !(food || growing) {
             if let Some(tail_pos) = self.state.positions.pop_back() {
-                board.set_tile_at_pos(tail_pos, Cell::Empty);
+                board.clear_tile_at_pos(tail_pos);
             }
         }
 
@@ -283,8 +579,238 @@ impl<'a> Snake<'a> {
             .positions
             .back()
             .expect("0-length Snake in execute_action().");
-        board.set_tile_at_pos(tail_pos, Cell::SnakeTail(self.state.id));
-        board.set_tile_at_pos(next_head_pos, Cell::SnakeHead(self.state.id));
+        board.set_tile_at_pos_unchecked(tail_pos, Cell::SnakeTail(self.state.id));
+        board.set_tile_at_pos_unchecked(next_head_pos, Cell::SnakeHead(self.state.id));
+
+        food
+    }
+
+    /// The coordinate of the head after doing `action`, `None` if the snake
+    /// would leave the board or has no head.
+    fn destination(&self, action: &Action, board: &GameBoard) -> Option<Coordinate> {
+        let head = self.state.try_get_head_coord(board.size())?;
+        let orientation = self.state.current_orientation.turn(action);
+        next_coord_towards(&head, &orientation, board.size(), board.wrap_mode())
+    }
+}
+
+/// The board and the snakes of a game, without the bots. It's cheap to
+/// clone, so that a bot can simulate the game forward with its own moves, see
+/// `GameState::from_board()` and `Game::clone_for_search()`.
+///
+/// `step_with()` only applies the rules of the snakes: moving, eating,
+/// growing, starving and dying. The food appears randomly, and the end of the
+/// game isn't decided.
+#[derive(Clone)]
+pub struct GameState {
+    board: GameBoard,
+    snakes: Vec<Snake>,
+    /// See `Game::with_ghost_mode()`.
+    ghost_mode: bool,
+    /// See `Game::with_growth_mode()`.
+    growth_mode: Option<GrowthMode>,
+}
+
+impl GameState {
+    /// Rebuilds a state from a board, so that the bots can search with
+    /// `step_with()` from what `SnakeBot::get_next_action()` receives.
+    ///
+    /// The board doesn't tell everything, so the state is an approximation:
+    /// the bodies are traced from the heads, which is ambiguous when a snake
+    /// touches itself, the orientations are guessed from the necks (North
+    /// for the snakes without body), the growth countdowns are reset and the
+    /// snakes don't starve. The food appears randomly, like in
+    /// `Game::clone_for_search()`.
+    pub fn from_board(board: &GameBoard) -> GameState {
+        let size = board.size();
+        let wrap_mode = board.wrap_mode();
+        let snakes = board
+            .head_positions()
+            .map(|(id, head)| {
+                let mut snake = Snake::new(id, SnakeConfig::default());
+                snake.state.positions.push_back(head);
+
+                // Follow the body up to the tail
+                let mut current = Coordinate::from_pos(head, size);
+                loop {
+                    let positions = &snake.state.positions;
+                    let next_parts: Vec<(Position, Cell)> = Orientation::ALL
+                        .iter()
+                        .filter_map(|o| next_coord_towards(&current, o, size, wrap_mode))
+                        .map(|coord| (coord.to_pos(size), board.get_tile_at_coord(&coord)))
+                        .filter(|(pos, cell)| {
+                            (*cell == Cell::SnakeBody(id) || *cell == Cell::SnakeTail(id))
+                                && !positions.contains(pos)
+                        })
+                        .collect();
+                    let next = next_parts
+                        .iter()
+                        .find(|(_, cell)| *cell == Cell::SnakeBody(id))
+                        .or_else(|| next_parts.first());
+                    match next {
+                        Some(&(pos, cell)) => {
+                            snake.state.positions.push_back(pos);
+                            current = Coordinate::from_pos(pos, size);
+                            if cell == Cell::SnakeTail(id) {
+                                break;
+                            }
+                        }
+                        None => break,
+                    }
+                }
+
+                // The head is in front of the neck
+                if let Some(&neck) = snake.state.positions.get(1) {
+                    let neck = Coordinate::from_pos(neck, size);
+                    if let Some(orientation) = Orientation::ALL.iter().find(|o| {
+                        next_coord_towards(&neck, o, size, wrap_mode)
+                            .is_some_and(|coord| coord.to_pos(size) == head)
+                    }) {
+                        snake.state.current_orientation = orientation.clone();
+                    }
+                }
+                snake
+            })
+            .collect();
+
+        let mut board = board.clone();
+        board.rng = StdRng::from_entropy();
+        GameState {
+            board,
+            snakes,
+            ghost_mode: false,
+            growth_mode: None,
+        }
+    }
+
+    pub fn board(&self) -> &GameBoard {
+        &self.board
+    }
+
+    /// The snakes, dead or alive, in the order they were added to the game.
+    pub fn snakes(&self) -> impl Iterator<Item = &SnakeState> {
+        self.snakes.iter().map(|snake| &snake.state)
+    }
+
+    /// Plays one step, `actions` containing the action of each alive snake,
+    /// in the order of `snakes()`.
+    pub fn step_with(&mut self, actions: &[Action]) -> &mut Self {
+        self.play_actions(actions);
+        self.board.update();
+        self
+    }
+
+    /// Moves the alive snakes and removes the ones which died from the board.
+    /// Returns whether each snake which moved ate food, and the IDs of the
+    /// snakes which died.
+    fn play_actions(&mut self, actions: &[Action]) -> (Vec<bool>, Vec<SnakeId>) {
+        assert_eq!(
+            actions.len(),
+            self.snakes.iter().filter(|snake| snake.state.alive).count(),
+            "One action per alive snake is needed."
+        );
+
+        // The snakes move one after the other, overwriting the cells, so find
+        // where their heads go beforehand
+        let size = self.board.size();
+        let destinations: Vec<(SnakeId, Position)> = self
+            .snakes
+            .iter()
+            .filter(|snake| snake.state.alive)
+            .zip(actions)
+            .filter_map(|(snake, action)| {
+                let coord = snake.destination(action, &self.board)?;
+                Some((snake.state.id, coord.to_pos(size)))
+            })
+            .collect();
+        let other_entrant = |id: SnakeId, pos: Position| {
+            destinations
+                .iter()
+                .find(|&&(other_id, other_pos)| other_id != id && other_pos == pos)
+                .map(|&(other_id, _)| other_id)
+        };
+
+        // The heads before the moves, to tell the head-on collisions apart
+        let heads: Vec<(SnakeId, Option<Position>)> = self
+            .snakes
+            .iter()
+            .filter(|snake| snake.state.alive)
+            .map(|snake| (snake.state.id, snake.state.try_get_head_pos()))
+            .collect();
+
+        // The food that several snakes go for is eaten by all of them
+        let contested_food: Vec<Position> = destinations
+            .iter()
+            .filter(|&&(id, pos)| {
+                other_entrant(id, pos).is_some() && self.board.get_tile_at_pos(pos) == Cell::Food
+            })
+            .map(|&(_, pos)| pos)
+            .collect();
+
+        // Move the snakes
+        let board = &mut self.board;
+        let ghost_mode = self.ghost_mode;
+        let growth_mode = self.growth_mode;
+        let ate_food = self
+            .snakes
+            .iter_mut()
+            .filter(|snake| snake.state.alive)
+            .zip(actions)
+            .map(|(snake, action)| {
+                snake.execute_action(board, action, ghost_mode, growth_mode, &contested_food)
+            })
+            .collect();
+
+        // A snake running into the head of a snake which moves away runs into
+        // its body, unless they swap their cells or go to the same one
+        for snake in self.snakes.iter_mut().filter(|snake| snake.just_died) {
+            if let Some(DeathCause::HeadOn(other_id)) = snake.death_cause {
+                let id = snake.state.id;
+                let own_head = heads
+                    .iter()
+                    .find(|&&(head_id, _)| head_id == id)
+                    .and_then(|&(_, head)| head);
+                let own_destination = destinations
+                    .iter()
+                    .find(|&&(dest_id, _)| dest_id == id)
+                    .map(|&(_, pos)| pos);
+                let head_on = destinations.iter().any(|&(dest_id, pos)| {
+                    dest_id == other_id && (Some(pos) == own_head || Some(pos) == own_destination)
+                });
+                if !head_on {
+                    snake.death_cause = Some(DeathCause::SnakeBody(other_id));
+                }
+            }
+        }
+
+        // Kill all the snakes whose heads went to the same cell, which can't
+        // happen in ghost mode since the colliding moves are cancelled
+        if !self.ghost_mode {
+            for &(id, pos) in &destinations {
+                if let Some(other_id) = other_entrant(id, pos) {
+                    if let Some(snake) = self.snakes.iter_mut().find(|snake| snake.state.id == id) {
+                        snake.die(DeathCause::HeadOn(other_id));
+                    }
+                }
+            }
+        }
+
+        // Remove the dead snakes from the board
+        let dead_snakes_id = self
+            .snakes
+            .iter_mut()
+            .filter(|snake| snake.just_died)
+            .map(|snake| {
+                snake.just_died = false;
+                snake.state.alive = false;
+                snake.state.id
+            })
+            .collect::<Vec<_>>();
+        self.board
+            .remove_dead_snakes(dead_snakes_id.clone(), &self.snakes);
+        self.board.nb_alive_snakes = self.snakes.iter().filter(|snake| snake.state.alive).count();
+
+        (ate_food, dead_snakes_id)
     }
 }
 
@@ -292,14 +818,89 @@ impl<'a> Snake<'a> {
 pub enum GameResultWinner {
     Winner(SnakeId),
     Draw,
+    /// The game had only one snake, which died: the notion of winner doesn't
+    /// make sense.
+    SoloGameOver,
+}
+
+/// Why a snake died.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DeathCause {
+    /// Went out of the board.
+    Wall,
+    Obstacle,
+    OwnBody,
+    /// Ran into the body or the tail of another snake.
+    SnakeBody(SnakeId),
+    /// Ran into the head of another snake.
+    HeadOn(SnakeId),
+    Starvation,
+}
+
+impl fmt::Display for DeathCause {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DeathCause::Wall => write!(f, "hit a wall"),
+            DeathCause::Obstacle => write!(f, "hit an obstacle"),
+            DeathCause::OwnBody => write!(f, "bit itself"),
+            DeathCause::SnakeBody(id) => write!(f, "ran into snake {}", id),
+            DeathCause::HeadOn(id) => write!(f, "collided head-on with snake {}", id),
+            DeathCause::Starvation => write!(f, "starved"),
+        }
+    }
+}
+
+/// The outcome of a game for one snake.
+#[derive(Debug, Clone)]
+pub struct SnakeResult {
+    pub id: SnakeId,
+    /// `None` if the snake is still alive.
+    pub death_cause: Option<DeathCause>,
+    /// The length of the snake, when it died if it's dead.
+    pub length: usize,
+    pub food_eaten: u32,
+    /// The step during which the snake died, the first one being 0.
+    pub died_at_step: Option<u32>,
+}
+
+/// How the snakes grow, see `Game::with_growth_mode()`. They always grow
+/// when they eat.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GrowthMode {
+    /// The snakes also grow every given number of steps.
+    Periodic(i32),
+    /// The snakes only grow when they eat, like in the classic Snake.
+    FoodOnly,
+}
+
+/// How to decide the winner when the last snakes die during the same step,
+/// or when they are still alive after the `Game::max_steps()`.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum DrawPolicy {
+    /// It's a draw.
+    #[default]
+    TrueDraw,
+    /// The longest snake wins, it's a draw if they have the same length.
+    LongestWins,
+    /// The snake which ate the most wins, it's a draw if they ate as much.
+    MostFoodWins,
 }
 
 #[derive(Debug, Clone)]
 pub struct GameResults {
-    /// `winner` is None if there is only one snake and the notion of winner
-    /// doesn't make sense.
+    /// `winner` is None if the game didn't end, i.e. it was interrupted.
     pub winner: Option<GameResultWinner>,
     pub steps: u32,
+    /// The outcome for each snake, in the order they were added.
+    pub snakes: Vec<SnakeResult>,
+    /// The rank of each snake, from the first to the last: the snakes are
+    /// ranked by elimination order, the snakes dying during the same step
+    /// being ranked with the `DrawPolicy`. Tied snakes share the same rank,
+    /// e.g. 1, 1, 3. Useful with `continue_simulation_if_known_winner()`.
+    pub placements: Vec<(SnakeId, u32)>,
+    /// Whether the game was stopped with Ctrl+C before its end. Then, the
+    /// results are the ones at the moment of the interruption.
+    pub interrupted: bool,
 }
 
 impl fmt::Display for GameResults {
@@ -314,62 +915,539 @@ impl fmt::Display for GameResults {
                 Some(GameResultWinner::Draw) => {
                     format!("It's a draw! ({} moves)", self.steps).yellow()
                 }
-                None => {
+                Some(GameResultWinner::SoloGameOver) => {
                     format!("The snake died after {} moves.", self.steps).blue()
                 }
+                None => {
+                    format!("Game interrupted after {} moves.", self.steps).yellow()
+                }
             }
-        )
+        )?;
+        for snake in &self.snakes {
+            write!(
+                f,
+                "\n  Player {}: length {}, {} food eaten, ",
+                snake.id, snake.length, snake.food_eaten
+            )?;
+            match &snake.death_cause {
+                Some(cause) => write!(f, "{}.", cause)?,
+                None => write!(f, "alive.")?,
+            }
+        }
+        Ok(())
     }
 }
 
-pub struct Game<'a> {
-    board: GameBoard,
-    snakes: Vec<Snake<'a>>,
-    before_each_step: Vec<Box<dyn Fn(&GameBoard)>>,
-    after_each_step: Vec<Box<dyn Fn(&GameBoard)>>,
-
-    initialized: bool,
-    step: u32,
-    results: Option<GameResults>,
-
-    /// If this field is `false` *and* there are more than one snake, then
-    /// the simulation is stopped as soon as there is a winner. In other
-    /// words, we don't continue the simulation with the remaining snake.
-    lazy_simulation: bool,
+/// How long a bot took to choose its moves, see `Game::with_timing()`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BotTiming {
+    pub id: SnakeId,
+    pub nb_moves: u32,
+    pub min: Duration,
+    pub max: Duration,
+    pub total: Duration,
 }
 
-impl<'a> Game<'a> {
-    const MAX_SIZE_OBSTACLE: u32 = 2;
-    const NB_OBSTACLES: u32 = 5;
+impl BotTiming {
+    fn new(id: SnakeId) -> Self {
+        BotTiming {
+            id,
+            nb_moves: 0,
+            min: Duration::MAX,
+            max: Duration::ZERO,
+            total: Duration::ZERO,
+        }
+    }
 
-    pub fn new() -> Self {
-        let mut game = Game {
-            board: GameBoard::new(),
-            snakes: vec![],
-            before_each_step: vec![],
-            after_each_step: vec![],
-            initialized: false,
-            step: 0,
-            results: None,
-            lazy_simulation: true,
-        };
-        game.board
-            .add_random_obstacles(Self::NB_OBSTACLES, Self::MAX_SIZE_OBSTACLE);
-        game
+    fn record(&mut self, duration: Duration) {
+        self.nb_moves += 1;
+        self.min = self.min.min(duration);
+        self.max = self.max.max(duration);
+        self.total += duration;
     }
 
-    pub fn add_snake(&mut self, id: SnakeId, snake_bot: Box<dyn SnakeBot + 'a>) -> &mut Self {
-        if self
-            .snakes
-            .iter()
-            .filter(|snake| snake.state.id == id)
-            .count()
-            > 0
-        {
-            panic!(format!("The ID {} is already used!", id));
+    /// The average duration of a move, zero if the bot didn't move.
+    pub fn average(&self) -> Duration {
+        if self.nb_moves == 0 {
+            return Duration::ZERO;
         }
-        self.snakes.push(Snake::new(id, snake_bot));
-        self
+        self.total / self.nb_moves
+    }
+}
+
+impl fmt::Display for BotTiming {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.nb_moves == 0 {
+            return write!(f, "Bot {} didn't move.", self.id);
+        }
+        write!(
+            f,
+            "Bot {} averages {:?}/move (min {:?}, max {:?}, {} moves).",
+            self.id,
+            self.average(),
+            self.min,
+            self.max,
+            self.nb_moves
+        )
+    }
+}
+
+/// The boards of a game, before the first step and after each step, and the
+/// actions played, see `Game::record()`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Replay {
+    size: BoardSize,
+    frames: Vec<Vec<Cell>>,
+    /// The actions of the alive snakes at each step, in the order they were
+    /// added to the game.
+    actions: Vec<Vec<Action>>,
+}
+
+impl Replay {
+    /// The actions chosen at each step, to replay the game with
+    /// `Game::replay_actions()`.
+    pub fn actions(&self) -> &[Vec<Action>] {
+        &self.actions
+    }
+
+    /// The size of the board of the recorded game.
+    pub fn size(&self) -> BoardSize {
+        self.size
+    }
+
+    /// The number of frames: the initial board and one per step.
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    /// The cells of the board row by row, before the first step for the
+    /// frame 0 and after the step `i` for the frame `i`.
+    pub fn frame(&self, i: usize) -> Option<&[Cell]> {
+        self.frames.get(i).map(Vec::as_slice)
+    }
+
+    /// A hash of all the frames, to check that a game is reproducible.
+    ///
+    /// Two games with the same seed and the same bots have the same
+    /// fingerprint, as long as the bots are deterministic: a bot using its
+    /// own RNG has to be seeded too, e.g. `RandomBot::with_seed()`. The
+    /// fingerprint is only comparable between runs of the same build.
+    pub fn fingerprint(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        self.frames.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    /// The first bytes of the binary format, see `to_bytes()`.
+    const MAGIC: &'static [u8; 4] = b"SNKR";
+    const FORMAT_VERSION: u8 = 1;
+    /// The cells which aren't part of a snake are encoded from 0 to 4, the
+    /// snake cells after, by ID and then head, body and tail.
+    const FIRST_SNAKE_CODE: u8 = 5;
+
+    /// Encodes the replay in a compact binary format, to archive many games:
+    /// a header with the size of the board and the number of frames, one
+    /// byte per cell of each frame, one byte per action, and a checksum to
+    /// detect truncated or corrupted files. See `from_bytes()`.
+    ///
+    /// Only the snakes with an ID up to 82 fit in a byte, and the board
+    /// sizes, numbers of steps and of snakes must fit in the header fields.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, ReplayFormatError> {
+        let width = u16::try_from(self.size.width).map_err(|_| ReplayFormatError::TooLarge)?;
+        let height = u16::try_from(self.size.height).map_err(|_| ReplayFormatError::TooLarge)?;
+        let nb_frames =
+            u32::try_from(self.frames.len()).map_err(|_| ReplayFormatError::TooLarge)?;
+        let nb_steps =
+            u32::try_from(self.actions.len()).map_err(|_| ReplayFormatError::TooLarge)?;
+
+        let nb_cells = self.size.nb_cells();
+        let mut bytes = Vec::with_capacity(20 + self.frames.len() * nb_cells);
+        bytes.extend_from_slice(Self::MAGIC);
+        bytes.push(Self::FORMAT_VERSION);
+        bytes.extend_from_slice(&width.to_le_bytes());
+        bytes.extend_from_slice(&height.to_le_bytes());
+        bytes.extend_from_slice(&nb_frames.to_le_bytes());
+        bytes.extend_from_slice(&nb_steps.to_le_bytes());
+
+        for frame in &self.frames {
+            for &cell in frame {
+                bytes.push(Self::encode_cell(cell)?);
+            }
+        }
+        for actions in &self.actions {
+            bytes.push(u8::try_from(actions.len()).map_err(|_| ReplayFormatError::TooLarge)?);
+            bytes.extend(actions.iter().map(|action| match action {
+                Action::Left => 0,
+                Action::Front => 1,
+                Action::Right => 2,
+            }));
+        }
+
+        let checksum = Self::checksum(&bytes);
+        bytes.extend_from_slice(&checksum.to_le_bytes());
+        Ok(bytes)
+    }
+
+    /// Decodes a replay written by `to_bytes()`.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Replay, ReplayFormatError> {
+        if bytes.len() < 4 {
+            return Err(ReplayFormatError::Truncated);
+        }
+        let (content, checksum) = bytes.split_at(bytes.len() - 4);
+        if Self::checksum(content).to_le_bytes() != checksum {
+            return Err(ReplayFormatError::BadChecksum);
+        }
+
+        let mut reader = content.iter().cloned();
+        let mut next = |n: usize| -> Result<Vec<u8>, ReplayFormatError> {
+            let read: Vec<u8> = reader.by_ref().take(n).collect();
+            if read.len() < n {
+                return Err(ReplayFormatError::Truncated);
+            }
+            Ok(read)
+        };
+        let le_u16 = |b: &[u8]| u16::from_le_bytes([b[0], b[1]]) as usize;
+        let le_u32 = |b: &[u8]| u32::from_le_bytes([b[0], b[1], b[2], b[3]]) as usize;
+
+        let header = next(17)?;
+        if &header[0..4] != Self::MAGIC {
+            return Err(ReplayFormatError::NotAReplay);
+        }
+        if header[4] != Self::FORMAT_VERSION {
+            return Err(ReplayFormatError::UnsupportedVersion(header[4]));
+        }
+        let (width, height) = (le_u16(&header[5..7]), le_u16(&header[7..9]));
+        if width == 0 || height == 0 {
+            return Err(ReplayFormatError::WrongBoardSize { width, height });
+        }
+        let nb_frames = le_u32(&header[9..13]);
+        let nb_steps = le_u32(&header[13..17]);
+
+        let mut replay = Replay {
+            size: BoardSize::new(width as i32, height as i32),
+            ..Replay::default()
+        };
+        for _ in 0..nb_frames {
+            let frame = next(width * height)?
+                .into_iter()
+                .map(Self::decode_cell)
+                .collect::<Result<_, _>>()?;
+            replay.frames.push(frame);
+        }
+        for _ in 0..nb_steps {
+            let nb_actions = next(1)?[0] as usize;
+            let actions = next(nb_actions)?
+                .into_iter()
+                .map(|byte| match byte {
+                    0 => Ok(Action::Left),
+                    1 => Ok(Action::Front),
+                    2 => Ok(Action::Right),
+                    _ => Err(ReplayFormatError::InvalidAction(byte)),
+                })
+                .collect::<Result<_, _>>()?;
+            replay.actions.push(actions);
+        }
+        if next(1).is_ok() {
+            return Err(ReplayFormatError::TrailingBytes);
+        }
+        Ok(replay)
+    }
+
+    fn encode_cell(cell: Cell) -> Result<u8, ReplayFormatError> {
+        let snake_code = |id: SnakeId, part: u32| {
+            let code = Self::FIRST_SNAKE_CODE as u32 + 3 * id + part;
+            if code > u8::MAX as u32 {
+                return Err(ReplayFormatError::SnakeIdTooLarge(id));
+            }
+            Ok(code as u8)
+        };
+        match cell {
+            Cell::Empty => Ok(0),
+            Cell::Food => Ok(1),
+            Cell::Obstacle => Ok(2),
+            Cell::Mud => Ok(3),
+            Cell::Wall => Ok(4),
+            Cell::SnakeHead(id) => snake_code(id, 0),
+            Cell::SnakeBody(id) => snake_code(id, 1),
+            Cell::SnakeTail(id) => snake_code(id, 2),
+        }
+    }
+
+    fn decode_cell(byte: u8) -> Result<Cell, ReplayFormatError> {
+        Ok(match byte {
+            0 => Cell::Empty,
+            1 => Cell::Food,
+            2 => Cell::Obstacle,
+            3 => Cell::Mud,
+            4 => Cell::Wall,
+            _ => {
+                let code = (byte - Self::FIRST_SNAKE_CODE) as SnakeId;
+                match code % 3 {
+                    0 => Cell::SnakeHead(code / 3),
+                    1 => Cell::SnakeBody(code / 3),
+                    _ => Cell::SnakeTail(code / 3),
+                }
+            }
+        })
+    }
+
+    /// The 32-bit FNV-1a hash, which is stable across builds, unlike
+    /// `fingerprint()`.
+    fn checksum(bytes: &[u8]) -> u32 {
+        bytes.iter().fold(0x811c_9dc5, |hash, &byte| {
+            (hash ^ byte as u32).wrapping_mul(0x0100_0193)
+        })
+    }
+}
+
+/// Why a replay can't be written by `Replay::to_bytes()` or read by
+/// `Replay::from_bytes()`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ReplayFormatError {
+    SnakeIdTooLarge(SnakeId),
+    NotAReplay,
+    UnsupportedVersion(u8),
+    WrongBoardSize {
+        width: usize,
+        height: usize,
+    },
+    /// The data ends before the announced frames and actions.
+    Truncated,
+    TrailingBytes,
+    /// The data is corrupted, or truncated.
+    BadChecksum,
+    InvalidAction(u8),
+    /// The board, the game or a step doesn't fit in the format.
+    TooLarge,
+}
+
+impl fmt::Display for ReplayFormatError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ReplayFormatError::SnakeIdTooLarge(id) => {
+                write!(f, "The snake ID {} is too large to be encoded.", id)
+            }
+            ReplayFormatError::NotAReplay => write!(f, "The data isn't a replay."),
+            ReplayFormatError::UnsupportedVersion(version) => {
+                write!(f, "Unsupported replay format version: {}.", version)
+            }
+            ReplayFormatError::WrongBoardSize { width, height } => {
+                write!(f, "The replay has an empty {}x{} board.", width, height)
+            }
+            ReplayFormatError::Truncated => write!(f, "The replay is truncated."),
+            ReplayFormatError::TrailingBytes => write!(f, "The replay has trailing data."),
+            ReplayFormatError::BadChecksum => write!(f, "The replay is corrupted or truncated."),
+            ReplayFormatError::InvalidAction(byte) => {
+                write!(f, "Invalid action code: {}.", byte)
+            }
+            ReplayFormatError::TooLarge => write!(f, "The replay is too large to be encoded."),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum InitializationError {
+    NoRoomForSnake { id: SnakeId, length: usize },
+}
+
+impl fmt::Display for InitializationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            InitializationError::NoRoomForSnake { id, length } => write!(
+                f,
+                "Not able to find an initial position with a safe first move for the snake {} \
+                 of length {}: the board is too cramped.",
+                id, length
+            ),
+        }
+    }
+}
+
+/// A callback executed before or after each step of a `Game`.
+type StepCallback = Box<dyn Fn(&GameBoard)>;
+
+/// What happened during a step, see `Game::on_event`.
+#[derive(Debug, Clone)]
+#[allow(dead_code)]
+pub enum GameEvent {
+    SnakeMoved { id: SnakeId, action: Action },
+    FoodEaten { id: SnakeId, pos: Position },
+    SnakeDied { id: SnakeId, cause: DeathCause },
+    GameEnded { results: GameResults },
+}
+
+type EventCallback = Box<dyn Fn(&GameEvent)>;
+type FoodEatenCallback = Box<dyn Fn(SnakeId, &Coordinate)>;
+type DeathCallback = Box<dyn Fn(SnakeId, &DeathCause)>;
+type BoardGenerator<'a> = Box<dyn FnOnce(&mut GameBoard) + 'a>;
+
+pub struct Game<'a> {
+    state: GameState,
+    /// The bots of the snakes, in the same order as `state.snakes`.
+    bots: Vec<Box<dyn SnakeBot + 'a>>,
+    before_each_step: Vec<StepCallback>,
+    after_each_step: Vec<StepCallback>,
+    on_event: Vec<EventCallback>,
+    on_food_eaten: Vec<FoodEatenCallback>,
+    on_snake_death: Vec<DeathCallback>,
+
+    initialized: bool,
+    step: u32,
+    results: Option<GameResults>,
+    /// The seed of `rng`, to be able to reproduce the game.
+    seed: u64,
+    rng: StdRng,
+
+    /// If this field is `false` *and* there are more than one snake, then
+    /// the simulation is stopped as soon as there is a winner. In other
+    /// words, we don't continue the simulation with the remaining snake.
+    lazy_simulation: bool,
+
+    /// If this field is `false`, all the snakes start toward the North.
+    random_initial_orientation: bool,
+    /// The health of the snakes when they start or eat, if they can starve.
+    max_health: Option<u32>,
+    start_positions: Vec<(SnakeId, Coordinate, Orientation)>,
+    symmetric_starts: bool,
+    /// If this field is `true`, `run_to_end()` stops on Ctrl+C.
+    interruptible: bool,
+    /// If this field is `true`, the game ends as soon as all the alive
+    /// snakes are trapped, see `trapped_regions()`.
+    end_on_entrapment: bool,
+    /// The game ends when this number of steps is reached, see `max_steps()`.
+    max_steps: Option<u32>,
+    /// The food to place at the beginning of the given steps, sorted by step.
+    food_schedule: VecDeque<(u32, Coordinate)>,
+    draw_policy: DrawPolicy,
+    /// The recorded boards, if the game is recorded.
+    replay: Option<Replay>,
+    /// The number of random obstacles, placed after the snakes.
+    nb_obstacles: u32,
+    /// The number of random patches of mud, placed before the snakes.
+    nb_mud_patches: u32,
+    /// The maximum width and height of the obstacles.
+    max_obstacle_size: (u32, u32),
+    /// The number of food placed after the snakes and the obstacles.
+    initial_food: usize,
+    /// The time taken by each bot, in the same order as `state.snakes`, if
+    /// the bots are timed.
+    timings: Option<Vec<BotTiming>>,
+    /// Run on the board when the game is initialized, see
+    /// `with_board_generator()`.
+    board_generators: Vec<BoardGenerator<'a>>,
+    /// Played instead of asking the bots, see `replay_actions()`.
+    scripted_actions: VecDeque<Vec<Action>>,
+}
+
+impl<'a> Default for Game<'a> {
+    fn default() -> Self {
+        let size = BoardSize::default();
+        Self::new(size.width, size.height)
+    }
+}
+
+impl<'a> Game<'a> {
+    const MAX_SIZE_OBSTACLE: u32 = 2;
+    const MAX_SIZE_MUD: u32 = 3;
+    const NB_OBSTACLES: u32 = 5;
+
+    /// A `max_steps()` for the games nobody watches, e.g. to evaluate the
+    /// bots, so that two snakes chasing their tails can't block them.
+    pub const MAX_UNWATCHED_STEPS: u32 = 10_000;
+
+    /// Creates a game on a board of `width` by `height` cells, with a random
+    /// seed. See `with_board_size()` to combine it with a seed.
+    pub fn new(width: i32, height: i32) -> Self {
+        let mut game = Self::with_seed(thread_rng().gen());
+        game.with_board_size(width, height);
+        game
+    }
+
+    /// Creates a game which replays `actions`, e.g. from `Replay::actions()`:
+    /// at each step, the alive snakes play the given actions instead of
+    /// asking their bots. Once the actions run out, the bots play.
+    ///
+    /// The game must be set up like the recorded one, with the same seed,
+    /// options and snakes, but the bots don't matter. Hence, a game can be
+    /// reproduced even if its bots aren't deterministic.
+    pub fn replay_actions(seed: u64, actions: Vec<Vec<Action>>) -> Self {
+        let mut game = Self::with_seed(seed);
+        game.scripted_actions = actions.into();
+        game
+    }
+
+    /// Creates a game whose randomness (obstacles, food, initial positions
+    /// and orientations) only depends on `seed`.
+    pub fn with_seed(seed: u64) -> Self {
+        let mut rng = StdRng::seed_from_u64(seed);
+        Game {
+            state: GameState {
+                board: GameBoard::new(StdRng::seed_from_u64(rng.gen())),
+                snakes: vec![],
+                ghost_mode: false,
+                growth_mode: None,
+            },
+            bots: vec![],
+            before_each_step: vec![],
+            after_each_step: vec![],
+            on_event: vec![],
+            on_food_eaten: vec![],
+            on_snake_death: vec![],
+            initialized: false,
+            step: 0,
+            results: None,
+            seed,
+            rng,
+            lazy_simulation: true,
+            random_initial_orientation: true,
+            max_health: None,
+            start_positions: vec![],
+            symmetric_starts: false,
+            interruptible: false,
+            end_on_entrapment: false,
+            max_steps: None,
+            food_schedule: VecDeque::new(),
+            draw_policy: DrawPolicy::default(),
+            replay: None,
+            nb_obstacles: Self::NB_OBSTACLES,
+            nb_mud_patches: 0,
+            max_obstacle_size: (Self::MAX_SIZE_OBSTACLE, Self::MAX_SIZE_OBSTACLE),
+            initial_food: 0,
+            timings: None,
+            board_generators: vec![],
+            scripted_actions: VecDeque::new(),
+        }
+    }
+
+    pub fn add_snake(&mut self, id: SnakeId, snake_bot: Box<dyn SnakeBot + 'a>) -> &mut Self {
+        self.add_snake_with_config(id, snake_bot, SnakeConfig::default())
+    }
+
+    pub fn add_snake_with_config(
+        &mut self,
+        id: SnakeId,
+        snake_bot: Box<dyn SnakeBot + 'a>,
+        config: SnakeConfig,
+    ) -> &mut Self {
+        assert!(config.initial_length > 0, "A snake needs a head.");
+        assert!(config.growth_rate > 0, "The growth rate must be positive.");
+        if self
+            .state
+            .snakes
+            .iter()
+            .filter(|snake| snake.state.id == id)
+            .count()
+            > 0
+        {
+            panic!("The ID {} is already used!", id);
+        }
+        self.state.snakes.push(Snake::new(id, config));
+        self.bots.push(snake_bot);
+        self
     }
 
     #[allow(dead_code)]
@@ -390,386 +1468,3258 @@ impl<'a> Game<'a> {
         self
     }
 
-    #[allow(dead_code)]
-    pub fn continue_simulation_if_known_winner(&mut self, _continue: bool) -> &mut Self {
-        self.lazy_simulation = !_continue;
-        self
+    /// Registers a callback receiving the events of each step, in the order
+    /// they happened. They are sent after the board is updated, before the
+    /// `after_each_step` callbacks.
+    #[allow(dead_code)]
+    pub fn on_event<F>(&mut self, func: F) -> &mut Self
+    where
+        F: Fn(&GameEvent) + 'static,
+    {
+        self.on_event.push(Box::new(func));
+        self
+    }
+
+    /// Registers a callback called with the ID of a snake and the cell
+    /// where it ate food, each time a snake eats. Like the events, the calls
+    /// happen after the board is updated, after the `on_event` callbacks of
+    /// the same event, and before the `after_each_step` callbacks.
+    pub fn on_food_eaten<F>(&mut self, func: F) -> &mut Self
+    where
+        F: Fn(SnakeId, &Coordinate) + 'static,
+    {
+        self.on_food_eaten.push(Box::new(func));
+        self
+    }
+
+    /// Registers a callback called with the ID of a snake and why it died,
+    /// each time a snake dies. The deaths of a step are reported after the
+    /// food eaten during it, see `on_food_eaten()` for the order of the
+    /// callbacks.
+    pub fn on_snake_death<F>(&mut self, func: F) -> &mut Self
+    where
+        F: Fn(SnakeId, &DeathCause) + 'static,
+    {
+        self.on_snake_death.push(Box::new(func));
+        self
+    }
+
+    #[allow(dead_code)]
+    pub fn continue_simulation_if_known_winner(&mut self, _continue: bool) -> &mut Self {
+        self.lazy_simulation = !_continue;
+        self
+    }
+
+    /// Uneaten food disappears after `steps` steps, so the bots have to
+    /// commit to food quickly. By default, the food is permanent.
+    pub fn with_food_lifetime(&mut self, steps: u32) -> &mut Self {
+        assert!(steps > 0, "The food must last at least one step.");
+        self.state.board.food_lifetime = Some(steps);
+        self
+    }
+
+    /// Tops the board up to at least `n` food at each step, on random free
+    /// cells, on top of the usual random food. Useful to focus the learning
+    /// on growing.
+    pub fn with_min_food(&mut self, n: usize) -> &mut Self {
+        self.state.board.min_food = n;
+        self
+    }
+
+    /// Places `n` food on random free cells when the game is initialized,
+    /// so that the bots have something to pursue from the first step. There
+    /// can be fewer if the board is full or with `with_max_food()`.
+    pub fn with_initial_food(&mut self, n: usize) -> &mut Self {
+        self.initial_food = n;
+        self
+    }
+
+    /// No food is added while there are `n` food or more on the board, even
+    /// to reach the `with_min_food()` minimum.
+    pub fn with_max_food(&mut self, n: usize) -> &mut Self {
+        self.state.board.max_food = Some(n);
+        self
+    }
+
+    /// Places food at exact positions and steps, instead of randomly: each
+    /// `(step, coord)` puts food on `coord` at the beginning of the step
+    /// `step` (the first one is 0), before the bots choose their actions. The
+    /// food is only placed if the cell is free at that moment.
+    pub fn with_food_schedule(&mut self, mut schedule: Vec<(u32, Coordinate)>) -> &mut Self {
+        schedule.sort_by_key(|(step, _)| *step);
+        self.food_schedule = schedule.into();
+        self.without_food()
+    }
+
+    /// Chooses how to decide the winner when the last snakes die during the
+    /// same step. By default, it's a draw.
+    pub fn with_draw_policy(&mut self, draw_policy: DrawPolicy) -> &mut Self {
+        self.draw_policy = draw_policy;
+        self
+    }
+
+    /// Allows to stop the game with Ctrl+C in `run_to_end()`, for instance
+    /// when a human is watching it.
+    pub fn interruptible(&mut self, interruptible: bool) -> &mut Self {
+        self.interruptible = interruptible;
+        self
+    }
+
+    /// Ends the game sooner when its outcome is decided, because all the
+    /// alive snakes are trapped: they can't reach any food, and they don't
+    /// have enough room to survive. Then, the snake with the most room wins.
+    pub fn end_on_entrapment(&mut self, end: bool) -> &mut Self {
+        self.end_on_entrapment = end;
+        self
+    }
+
+    /// Ends the game after `max_steps` steps, if it didn't end before, so
+    /// that it can't last forever, e.g. when the snakes chase their tails.
+    /// The alive snakes are then ranked with the `DrawPolicy`: with the
+    /// default one, it's a draw.
+    pub fn max_steps(&mut self, max_steps: u32) -> &mut Self {
+        self.max_steps = Some(max_steps);
+        self
+    }
+
+    /// Records the board before the first step and after each step, and the
+    /// actions of the snakes, see `replay()`.
+    pub fn record(&mut self, record: bool) -> &mut Self {
+        self.replay = if record {
+            Some(Replay::default())
+        } else {
+            None
+        };
+        self
+    }
+
+    /// If `random` is `false`, all the snakes start toward the North instead
+    /// of a random orientation, which is handy for reproducible tests.
+    #[allow(dead_code)]
+    pub fn random_initial_orientation(&mut self, random: bool) -> &mut Self {
+        self.random_initial_orientation = random;
+        self
+    }
+
+    /// No food ever appears on the board, so the snakes only grow with their
+    /// growth rate. Useful to study the space management alone.
+    pub fn without_food(&mut self) -> &mut Self {
+        self.state.board.food_add_probability = 0.;
+        self
+    }
+
+    /// Enables the hunger rule: the snakes die if they don't eat during
+    /// `max_health` steps.
+    #[allow(dead_code)]
+    pub fn with_hunger(&mut self, max_health: u32) -> &mut Self {
+        assert!(max_health > 0);
+        self.max_health = Some(max_health);
+        self
+    }
+
+    /// Sets the number of random obstacles and their maximum size. The
+    /// obstacles are rectangles, so a maximum width of 1 makes walls and
+    /// corridors. They are placed after the snakes, without boxing them in.
+    pub fn with_obstacles(
+        &mut self,
+        nb_obstacles: u32,
+        max_width: u32,
+        max_height: u32,
+    ) -> &mut Self {
+        self.nb_obstacles = nb_obstacles;
+        self.max_obstacle_size = (max_width, max_height);
+        self
+    }
+
+    /// Plays on `board` instead of a random board, e.g. one made with
+    /// `GameBoard::from_ascii()`. No random obstacles are added.
+    pub fn with_board(&mut self, mut board: GameBoard) -> &mut Self {
+        // Keep the seeded randomness of the game
+        std::mem::swap(&mut board.rng, &mut self.state.board.rng);
+        self.state.board = board;
+        self.nb_obstacles = 0;
+        self
+    }
+
+    /// Plays on an empty board of `width` by `height` cells, instead of the
+    /// default 32x16. The food settings of the board are kept, but not what
+    /// was placed on it, e.g. with `with_board()`.
+    pub fn with_board_size(&mut self, width: i32, height: i32) -> &mut Self {
+        self.state.board.resize(BoardSize::new(width, height));
+        self
+    }
+
+    /// Lets `generate` modify the board when the game is initialized, e.g.
+    /// to stamp a layout with `GameBoard::set_tile_at_coord()`, which fails
+    /// on the cells outside of the board. It runs
+    /// before the snakes are placed, so they start around the layout, and the
+    /// random obstacles are placed afterwards.
+    pub fn with_board_generator<F>(&mut self, generate: F) -> &mut Self
+    where
+        F: FnOnce(&mut GameBoard) + 'a,
+    {
+        self.board_generators.push(Box::new(generate));
+        self
+    }
+
+    /// Measures how long each bot takes to choose its moves, to find the
+    /// slow ones. See `timings()`.
+    pub fn with_timing(&mut self) -> &mut Self {
+        self.timings = Some(vec![]);
+        self
+    }
+
+    /// Debug mode in which the snakes can't run into each other: such a move
+    /// is cancelled and the snake stays where it is, instead of dying. This
+    /// shows where a bot wanted to go, to tell a bad decision from a
+    /// surprising collision.
+    ///
+    /// Heads-up: This changes the rules of the game, so it must not be used
+    /// to evaluate bots, e.g. in the fitness function.
+    pub fn with_ghost_mode(&mut self) -> &mut Self {
+        self.state.ghost_mode = true;
+        self
+    }
+
+    /// With `WrapMode::Wrap`, the snakes leaving the board by one edge enter
+    /// it again by the opposite edge, as in the classic Snake.
+    pub fn wrap_mode(&mut self, wrap_mode: WrapMode) -> &mut Self {
+        self.state.board.set_wrap_mode(wrap_mode);
+        self
+    }
+
+    /// Overrides how all the snakes grow, instead of the `growth_rate` of
+    /// their `SnakeConfig`. With `GrowthMode::FoodOnly`, the snakes don't
+    /// fill the board on their own, which changes the strategy a lot.
+    pub fn with_growth_mode(&mut self, growth_mode: GrowthMode) -> &mut Self {
+        if let GrowthMode::Periodic(growth_rate) = growth_mode {
+            assert!(growth_rate > 0, "The growth rate must be positive.");
+        }
+        self.state.growth_mode = Some(growth_mode);
+        self
+    }
+
+    /// Adds `nb_patches` patches of mud on the board, when the game is
+    /// initialized and before the snakes are placed.
+    #[allow(dead_code)]
+    pub fn with_mud(&mut self, nb_patches: u32) -> &mut Self {
+        self.nb_mud_patches = nb_patches;
+        self
+    }
+
+    /// Requests explicit starting positions and orientations for some
+    /// snakes. A requested cell which isn't free when the game is initialized
+    /// is ignored, and the snake is placed randomly instead.
+    #[allow(dead_code)]
+    pub fn with_start_positions(
+        &mut self,
+        start_positions: Vec<(SnakeId, Coordinate, Orientation)>,
+    ) -> &mut Self {
+        self.start_positions = start_positions;
+        self
+    }
+
+    /// Places every other snake at the mirror image (through the board
+    /// center) of the previous one, facing the opposite direction, so that
+    /// head-to-head matches start on an equal footing.
+    #[allow(dead_code)]
+    pub fn with_symmetric_starts(&mut self) -> &mut Self {
+        self.symmetric_starts = true;
+        self
+    }
+
+    pub fn initialize(&mut self) -> &mut Self {
+        if let Err(e) = self.try_initialize() {
+            panic!("{}", e);
+        }
+        self
+    }
+
+    /// Places the snakes on the board. Fails if there is no room for one of
+    /// them.
+    pub fn try_initialize(&mut self) -> Result<&mut Self, InitializationError> {
+        // The custom layouts first, so that the snakes are placed around them
+        for generate in std::mem::take(&mut self.board_generators) {
+            generate(&mut self.state.board);
+        }
+        self.state
+            .board
+            .add_random_mud(self.nb_mud_patches, Self::MAX_SIZE_MUD);
+
+        // Place the snakes on the board
+        let mut previous_start = None;
+        for i in 0..self.state.snakes.len() {
+            let id = self.state.snakes[i].state.id;
+            let length = self.state.snakes[i].config.initial_length;
+            let requested_body =
+                self.requested_start(id, i, &previous_start)
+                    .and_then(|(coord, orientation)| {
+                        Self::lay_out_body(&self.state.board, &coord, &orientation, length)
+                            .map(|body| (body, orientation))
+                    });
+            let (body, orientation) = match requested_body {
+                Some(start) => start,
+                None => {
+                    if self
+                        .start_positions
+                        .iter()
+                        .any(|(start_id, _, _)| *start_id == id)
+                    {
+                        eprintln!(
+                            "The starting cells of snake {} aren't free, placing it randomly.",
+                            id
+                        );
+                    }
+                    self.random_start(length)
+                        .ok_or(InitializationError::NoRoomForSnake { id, length })?
+                }
+            };
+
+            // Update the board
+            for (j, pos) in body.iter().enumerate() {
+                let cell = if j == 0 {
+                    Cell::SnakeHead(id)
+                } else if j == body.len() - 1 {
+                    Cell::SnakeTail(id)
+                } else {
+                    Cell::SnakeBody(id)
+                };
+                self.state.board.set_tile_at_pos_unchecked(*pos, cell);
+            }
+
+            // Update the snake
+            previous_start = Some((body[0], orientation.clone()));
+            let snake = &mut self.state.snakes[i];
+            snake.state.positions.extend(body);
+            snake.state.current_orientation = orientation;
+            snake.state.health = self.max_health;
+            snake.state.max_health = self.max_health;
+            if let Some(GrowthMode::Periodic(growth_rate)) = self.state.growth_mode {
+                snake.growth_state = growth_rate;
+            }
+        }
+
+        // Place the obstacles around the snakes
+        let (max_width, max_height) = self.max_obstacle_size;
+        self.state.board.add_random_obstacles(
+            self.nb_obstacles,
+            max_width,
+            max_height,
+            &self.state.snakes,
+        );
+        self.state.board.add_random_food(self.initial_food);
+
+        self.record_frame();
+        self.initialized = true;
+        Ok(self)
+    }
+
+    /// Returns the start requested for the `index`-th snake, either
+    /// explicitly or by mirroring the previous snake's start.
+    fn requested_start(
+        &self,
+        id: SnakeId,
+        index: usize,
+        previous_start: &Option<(Position, Orientation)>,
+    ) -> Option<(Coordinate, Orientation)> {
+        if let Some((_, coord, orientation)) = self
+            .start_positions
+            .iter()
+            .find(|(start_id, _, _)| *start_id == id)
+        {
+            return Some((coord.clone(), orientation.clone()));
+        }
+
+        match previous_start {
+            Some((pos, orientation)) if self.symmetric_starts && index % 2 == 1 => {
+                let size = self.state.board.size();
+                let Coordinate { x, y } = Coordinate::from_pos(*pos, size);
+                let mirrored_coord = Coordinate {
+                    x: size.width - 1 - x,
+                    y: size.height - 1 - y,
+                };
+                Some((mirrored_coord, orientation.opposite()))
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the positions of a snake of `length` cells, with its head at
+    /// `head` and its body laid out behind it, or `None` if these cells
+    /// aren't free.
+    fn lay_out_body(
+        board: &GameBoard,
+        head: &Coordinate,
+        orientation: &Orientation,
+        length: usize,
+    ) -> Option<Vec<Position>> {
+        let backward = orientation.opposite();
+        let mut body = Vec::with_capacity(length);
+        let mut coord = head.clone();
+        for i in 0..length {
+            if i > 0 {
+                coord = next_coord_towards(&coord, &backward, board.size(), board.wrap_mode())?;
+            }
+            if !board.is_coord_free_or_food(&coord) {
+                return None;
+            }
+            body.push(coord.to_pos(board.size()));
+        }
+        Some(body)
+    }
+
+    /// Picks random free cells and an orientation for a snake of `length`
+    /// cells, such that it has at least one safe first move. Returns the
+    /// positions of the snake, the head first, or `None` if no such start is
+    /// found.
+    fn random_start(&mut self, length: usize) -> Option<(Vec<Position>, Orientation)> {
+        let size = self.state.board.size();
+        let nb_cells = size.nb_cells() as Position;
+
+        for _ in 0..10_000 {
+            let p = self.rng.gen_range(0, nb_cells);
+
+            // Check that the cell is free, which also prevents two snakes
+            // from starting at the same position
+            if !self.state.board.is_pos_free_or_food(p) {
+                continue; // Retry
+            }
+            let coord = Coordinate::from_pos(p, size);
+
+            // Find the orientations for which the body fits
+            let orientations: &[Orientation] = if self.random_initial_orientation {
+                &Orientation::ALL
+            } else {
+                &[Orientation::North]
+            };
+            let board = &self.state.board;
+            let bodies: Vec<(Vec<Position>, &Orientation)> = orientations
+                .iter()
+                .filter_map(|o| Self::lay_out_body(board, &coord, o, length).map(|b| (b, o)))
+                .collect();
+            if bodies.is_empty() {
+                continue; // Retry
+            }
+
+            // Choose the orientation, giving the snake a fair first move
+            let fair_bodies: Vec<&(Vec<Position>, &Orientation)> = bodies
+                .iter()
+                .filter(|(_, o)| !board.get_non_suicide_moves(&coord, o).is_empty())
+                .collect();
+            let (body, orientation) = match fair_bodies.choose(&mut self.rng) {
+                Some(fair_body) => fair_body,
+                None => continue, // Retry, the snake would die at once
+            };
+
+            return Some((body.clone(), (*orientation).clone()));
+        }
+        None
+    }
+
+    #[allow(dead_code)]
+    pub fn print(&mut self) -> &mut Self {
+        self.state.board.print();
+        self
+    }
+
+    pub fn step(&mut self) -> &mut Self {
+        assert!(self.initialized);
+        //        println!("Running step {}...", self.step);
+
+        // Scheduled food
+        while let Some((step, _)) = self.food_schedule.front() {
+            if *step > self.step {
+                break;
+            }
+            let (step, coord) = self.food_schedule.pop_front().unwrap();
+            if step == self.step
+                && !coord.is_out_of_bounds(self.state.board.size())
+                && matches!(
+                    self.state.board.get_tile_at_coord(&coord),
+                    Cell::Empty | Cell::Mud
+                )
+            {
+                self.state
+                    .board
+                    .set_tile_at_coord_unchecked(&coord, Cell::Food);
+            }
+        }
+
+        // Before-step callbacks
+        for before_each_step in &self.before_each_step {
+            before_each_step(&self.state.board);
+        }
+
+        // Remember which snakes are still alive
+        let prev_nb_alive = self
+            .state
+            .snakes
+            .iter()
+            .filter(|snake| snake.state.alive)
+            .count();
+
+        // Update the board before calling the bots
+        self.state.board.nb_alive_snakes = prev_nb_alive;
+
+        // Take the snakes' next actions
+        if let Some(timings) = &mut self.timings {
+            if timings.is_empty() {
+                *timings = self
+                    .state
+                    .snakes
+                    .iter()
+                    .map(|snake| BotTiming::new(snake.state.id))
+                    .collect();
+            }
+        }
+        let mut scripted_actions = self.scripted_actions.pop_front().map(Vec::into_iter);
+        let mut moves = vec![];
+        for (i, (snake, bot)) in self
+            .state
+            .snakes
+            .iter()
+            .zip(self.bots.iter_mut())
+            .enumerate()
+        {
+            if snake.state.alive {
+                if let Some(actions) = &mut scripted_actions {
+                    let action = actions
+                        .next()
+                        .expect("The replayed actions don't match the alive snakes.");
+                    moves.push((snake.state.id, action));
+                    continue;
+                }
+                let start = self.timings.as_ref().map(|_| Instant::now());
+                let action = bot.get_next_action(&snake.state, &self.state.board);
+                if let (Some(start), Some(timings)) = (start, &mut self.timings) {
+                    timings[i].record(start.elapsed());
+                }
+                moves.push((snake.state.id, action));
+            }
+        }
+
+        // Move the snakes
+        let actions: Vec<Action> = moves.iter().map(|(_, action)| action.clone()).collect();
+        let (ate_food, dead_snakes_id) = self.state.play_actions(&actions);
+        for snake in self.state.snakes.iter_mut() {
+            if dead_snakes_id.contains(&snake.state.id) {
+                snake.died_at_step = Some(self.step);
+            }
+        }
+        if let Some(replay) = &mut self.replay {
+            replay.actions.push(actions);
+        }
+
+        // Only build the events if someone listens to them
+        let emit_events = !self.on_event.is_empty()
+            || !self.on_food_eaten.is_empty()
+            || !self.on_snake_death.is_empty();
+        let mut events = vec![];
+        if emit_events {
+            for ((id, action), ate_food) in moves.into_iter().zip(ate_food) {
+                events.push(GameEvent::SnakeMoved { id, action });
+                if ate_food {
+                    let snake = self.state.snakes.iter().find(|snake| snake.state.id == id);
+                    if let Some(pos) = snake.and_then(|snake| snake.state.try_get_head_pos()) {
+                        events.push(GameEvent::FoodEaten { id, pos });
+                    }
+                }
+            }
+            for snake in self.state.snakes.iter() {
+                if dead_snakes_id.contains(&snake.state.id) {
+                    events.push(GameEvent::SnakeDied {
+                        id: snake.state.id,
+                        cause: snake.death_cause.clone().unwrap(),
+                    });
+                }
+            }
+        }
+
+        // Count the live snakes
+        let nb_alive = self
+            .state
+            .snakes
+            .iter()
+            .filter(|snake| snake.state.alive)
+            .count();
+
+        // Verify if win/loose/draw
+        let results_before = self.results.clone();
+        if self.results.is_none() {
+            // Draw/end: all die
+            if prev_nb_alive > 0 && nb_alive == 0 {
+                let winner = if self.state.snakes.len() > 1 {
+                    self.break_tie(&dead_snakes_id)
+                } else {
+                    GameResultWinner::SoloGameOver
+                };
+                self.results = Some(self.new_results(Some(winner), self.step + 1));
+            }
+            // Winner: last alive, >1 snake total
+            if prev_nb_alive > 0 && nb_alive == 1 && self.state.snakes.len() > 1 {
+                let winner_id: SnakeId = self
+                    .state
+                    .snakes
+                    .iter()
+                    .filter(|snake| snake.state.alive)
+                    .map(|snake| snake.state.id)
+                    .next()
+                    .expect("Logic error: nb_alive == 1 but none found in self.state.snakes.");
+                self.results = Some(
+                    self.new_results(Some(GameResultWinner::Winner(winner_id)), self.step + 1),
+                );
+            }
+        }
+        // Decided: all the alive snakes are trapped
+        if self.results.is_none() && self.end_on_entrapment && nb_alive > 0 {
+            if let Some(regions) = self.trapped_regions() {
+                let max_region = regions.iter().map(|(_, size)| *size).max().unwrap();
+                let mut best = regions.iter().filter(|(_, size)| *size == max_region);
+                let winner = match (best.next(), best.next()) {
+                    _ if self.state.snakes.len() == 1 => Some(GameResultWinner::SoloGameOver),
+                    (Some((id, _)), None) => Some(GameResultWinner::Winner(*id)),
+                    _ => Some(GameResultWinner::Draw),
+                };
+                self.results = Some(self.new_results(winner, self.step + 1));
+            }
+        }
+        // Too long: the alive snakes are tied
+        if self.results.is_none() && nb_alive > 0 && self.is_last_step() {
+            let winner = if self.state.snakes.len() > 1 {
+                let alive_ids: Vec<SnakeId> = self
+                    .state
+                    .snakes
+                    .iter()
+                    .filter(|snake| snake.state.alive)
+                    .map(|snake| snake.state.id)
+                    .collect();
+                self.break_tie(&alive_ids)
+            } else {
+                GameResultWinner::SoloGameOver
+            };
+            self.results = Some(self.new_results(Some(winner), self.step + 1));
+        }
+
+        if emit_events {
+            if let (None, Some(results)) = (&results_before, &self.results) {
+                events.push(GameEvent::GameEnded {
+                    results: results.clone(),
+                });
+            }
+        }
+
+        // Update the board
+        self.state.board.update();
+
+        // Event callbacks
+        for event in &events {
+            for on_event in &self.on_event {
+                on_event(event);
+            }
+            match event {
+                GameEvent::FoodEaten { id, pos } => {
+                    let coord = Coordinate::from_pos(*pos, self.state.board.size());
+                    for on_food_eaten in &self.on_food_eaten {
+                        on_food_eaten(*id, &coord);
+                    }
+                }
+                GameEvent::SnakeDied { id, cause } => {
+                    for on_snake_death in &self.on_snake_death {
+                        on_snake_death(*id, cause);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        self.record_frame();
+
+        // After-step callbacks
+        for after_each_step in &self.after_each_step {
+            after_each_step(&self.state.board);
+        }
+
+        self.step += 1;
+        self
+    }
+
+    /// Steps until the end of the game. If the game is `interruptible()`,
+    /// it stops on Ctrl+C and the returned results are partial.
+    pub fn run_to_end(&mut self) -> GameResults {
+        let interrupts = if self.interruptible {
+            Some(catch_interrupts())
+        } else {
+            None
+        };
+
+        while self.results.is_none()
+            || (!self.lazy_simulation
+                && self
+                    .state
+                    .snakes
+                    .iter()
+                    .filter(|snake| snake.state.alive)
+                    .count()
+                    > 0
+                && !(self.end_on_entrapment && self.trapped_regions().is_some())
+                && self.max_steps.is_none_or(|max| self.step < max))
+        {
+            if interrupts.as_ref().is_some_and(|i| i.take()) {
+                return self.interrupt();
+            }
+            self.step();
+        }
+        self.get_results()
+            .expect("Logic error, no result in run_to_end().")
+    }
+
+    /// Stops the game before its end, e.g. on Ctrl+C, and returns the
+    /// results at this moment, marked as interrupted.
+    pub fn interrupt(&mut self) -> GameResults {
+        if self.results.is_none() {
+            self.results = Some(self.new_results(None, self.step));
+        }
+        if let Some(results) = &mut self.results {
+            results.interrupted = true;
+        }
+        self.get_results()
+            .expect("Logic error, no result in interrupt().")
+    }
+
+    /// Copies the board and the snakes, to simulate the game forward without
+    /// the bots, see `GameState::step_with()`. The food appears randomly in
+    /// the copy, so it doesn't reveal when the food will appear in the game.
+    pub fn clone_for_search(&self) -> GameState {
+        let mut state = self.state.clone();
+        state.board.rng = StdRng::from_entropy();
+        state
+    }
+
+    /// The seed from which the game can be reproduced with `with_seed()`,
+    /// given the same bots and settings.
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// The time taken by each bot so far, in the order the snakes were
+    /// added, if the game is timed with `with_timing()`.
+    pub fn timings(&self) -> Option<&[BotTiming]> {
+        self.timings.as_deref()
+    }
+
+    /// The recorded game, if it's recorded with `record()`.
+    pub fn replay(&self) -> Option<&Replay> {
+        self.replay.as_ref()
+    }
+
+    fn record_frame(&mut self) {
+        if let Some(replay) = &mut self.replay {
+            replay.size = self.state.board.size();
+            replay.frames.push(self.state.board.cells.to_vec());
+        }
+    }
+
+    /// Whether the current step reaches the `max_steps()`.
+    fn is_last_step(&self) -> bool {
+        self.max_steps.is_some_and(|max| self.step + 1 >= max)
+    }
+
+    pub fn is_game_over(&self) -> bool {
+        self.results.is_some()
+    }
+
+    /// The per-snake results are up-to-date, even if some snakes died after
+    /// the end of the game.
+    pub fn get_results(&self) -> Option<GameResults> {
+        self.results.clone().map(|mut results| {
+            results.snakes = self.snake_results();
+            results.placements = self.placements(&results.winner);
+            results
+        })
+    }
+
+    /// Returns the number of cells reachable by each alive snake if all of
+    /// them are trapped, i.e. if none of them can reach food and each of them
+    /// can reach fewer cells than its length.
+    fn trapped_regions(&self) -> Option<Vec<(SnakeId, usize)>> {
+        self.state
+            .snakes
+            .iter()
+            .filter(|snake| snake.state.alive)
+            .map(|snake| {
+                let length = snake.state.length();
+                let head = snake.state.try_get_head_coord(self.state.board.size())?;
+                match self.state.board.flood_fill(&head, length) {
+                    (size, false) if size < length => Some((snake.state.id, size)),
+                    _ => None,
+                }
+            })
+            .collect()
+    }
+
+    /// Decides the winner between snakes which died during the same step,
+    /// according to the `DrawPolicy`.
+    fn break_tie(&self, tied_ids: &[SnakeId]) -> GameResultWinner {
+        if self.draw_policy == DrawPolicy::TrueDraw {
+            return GameResultWinner::Draw;
+        }
+
+        let scores: Vec<(SnakeId, usize)> = self
+            .state
+            .snakes
+            .iter()
+            .filter(|snake| tied_ids.contains(&snake.state.id))
+            .map(|snake| (snake.state.id, self.tie_score(snake)))
+            .collect();
+        let best_score = scores.iter().map(|(_, score)| *score).max();
+        let mut best = scores
+            .iter()
+            .filter(|(_, score)| Some(*score) == best_score);
+        match (best.next(), best.next()) {
+            (Some((id, _)), None) => GameResultWinner::Winner(*id),
+            _ => GameResultWinner::Draw,
+        }
+    }
+
+    /// The higher, the better the snake according to the `DrawPolicy`.
+    fn tie_score(&self, snake: &Snake) -> usize {
+        match self.draw_policy {
+            DrawPolicy::TrueDraw => 0,
+            DrawPolicy::LongestWins => snake.state.length(),
+            DrawPolicy::MostFoodWins => snake.food_eaten as usize,
+        }
+    }
+
+    fn new_results(&self, winner: Option<GameResultWinner>, steps: u32) -> GameResults {
+        GameResults {
+            placements: self.placements(&winner),
+            winner,
+            steps,
+            snakes: self.snake_results(),
+            interrupted: false,
+        }
+    }
+
+    /// See `GameResults::placements`. The winner is always first, even when
+    /// other snakes are still alive.
+    fn placements(&self, winner: &Option<GameResultWinner>) -> Vec<(SnakeId, u32)> {
+        let key = |snake: &Snake| {
+            let is_winner =
+                matches!(winner, Some(GameResultWinner::Winner(id)) if *id == snake.state.id);
+            let survival = snake.died_at_step.unwrap_or(u32::MAX);
+            (is_winner, survival, self.tie_score(snake))
+        };
+        let mut keys: Vec<(SnakeId, _)> = self
+            .state
+            .snakes
+            .iter()
+            .map(|snake| (snake.state.id, key(snake)))
+            .collect();
+        keys.sort_by(|(_, a), (_, b)| b.cmp(a));
+
+        keys.iter()
+            .map(|(id, snake_key)| {
+                let nb_better = keys.iter().filter(|(_, key)| key > snake_key).count();
+                (*id, nb_better as u32 + 1)
+            })
+            .collect()
+    }
+
+    fn snake_results(&self) -> Vec<SnakeResult> {
+        self.state
+            .snakes
+            .iter()
+            .map(|snake| SnakeResult {
+                id: snake.state.id,
+                death_cause: snake.death_cause.clone(),
+                length: snake.state.length(),
+                food_eaten: snake.food_eaten,
+                died_at_step: snake.died_at_step,
+            })
+            .collect()
+    }
+}
+
+/// Represents the game board.
+///
+/// `cells` is a 1D representation of the 2D board, where rows are
+/// "concatenated" on one single row, so `(x, y)` is the `(x + y * width)`-th
+/// value.
+#[derive(Clone)]
+pub struct GameBoard {
+    /// The number of non-OBSTACLE cells.
+    pub nb_free_cells: i32,
+    pub nb_alive_snakes: usize,
+    size: BoardSize,
+    wrap_mode: WrapMode,
+    cells: Vec<Cell>,
+    /// What is under the snakes and the food: either `Cell::Empty` or
+    /// `Cell::Mud`.
+    terrain: Vec<Cell>,
+    /// Index of the `Cell::Food` cells, to avoid scanning the board. It's
+    /// maintained by `set_tile_at_pos_unchecked`.
+    food_positions: BTreeSet<Position>,
+    /// The step at which each food appeared, maintained with
+    /// `food_positions`.
+    food_spawn_steps: HashMap<Position, u32>,
+    /// Index of the `Cell::SnakeHead` cells, by snake, also maintained by
+    /// `set_tile_at_pos_unchecked`.
+    head_positions: BTreeMap<SnakeId, Position>,
+    /// The number of updates of the board, i.e. of steps.
+    step: u32,
+
+    rng: StdRng,
+    food_add_probability: f32,
+    /// The number of steps after which uneaten food disappears, `None` for
+    /// permanent food.
+    food_lifetime: Option<u32>,
+    /// The board is topped up to this much food at each step.
+    min_food: usize,
+    /// No food is added when there is this much food on the board.
+    max_food: Option<usize>,
+}
+
+/// Why a `GameBoard` can't be modified.
+#[derive(Debug, Clone, PartialEq)]
+pub enum BoardError {
+    OutOfBounds {
+        pos: Position,
+        width: i32,
+        height: i32,
+    },
+    OffBoard {
+        coord: Coordinate,
+        width: i32,
+        height: i32,
+    },
+}
+
+impl fmt::Display for BoardError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BoardError::OutOfBounds { pos, width, height } => write!(
+                f,
+                "The position {} is out of the {}x{} board.",
+                pos, width, height
+            ),
+            BoardError::OffBoard {
+                coord,
+                width,
+                height,
+            } => write!(
+                f,
+                "The cell ({}, {}) is out of the {}x{} board.",
+                coord.x, coord.y, width, height
+            ),
+        }
+    }
+}
+
+/// Why a map can't be read by `GameBoard::from_ascii()`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParseError {
+    /// The map has no cell.
+    Empty,
+    /// The row doesn't have the same length as the first one.
+    UnevenRow {
+        row: usize,
+    },
+    UnknownCell {
+        x: usize,
+        y: usize,
+        c: char,
+    },
+    /// There are several heads of this snake.
+    DuplicateSnake(SnakeId),
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseError::Empty => write!(f, "The map is empty."),
+            ParseError::UnevenRow { row } => {
+                write!(
+                    f,
+                    "The row {} doesn't have the length of the first one.",
+                    row
+                )
+            }
+            ParseError::UnknownCell { x, y, c } => {
+                write!(f, "Unknown cell '{}' at ({}, {}).", c, x, y)
+            }
+            ParseError::DuplicateSnake(id) => {
+                write!(f, "The snake {} has several heads.", id)
+            }
+        }
+    }
+}
+
+/// A copy of the content of a `GameBoard`, see `GameBoard::snapshot()`.
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BoardSnapshot {
+    size: BoardSize,
+    wrap_mode: WrapMode,
+    nb_free_cells: i32,
+    nb_alive_snakes: usize,
+    cells: Vec<Cell>,
+    terrain: Vec<Cell>,
+    food_positions: BTreeSet<Position>,
+    food_spawn_steps: HashMap<Position, u32>,
+    head_positions: BTreeMap<SnakeId, Position>,
+    step: u32,
+}
+
+impl BoardSnapshot {
+    pub fn size(&self) -> BoardSize {
+        self.size
+    }
+
+    /// The cells of the board, row by row.
+    pub fn cells(&self) -> &[Cell] {
+        &self.cells
+    }
+
+    pub fn nb_alive_snakes(&self) -> usize {
+        self.nb_alive_snakes
+    }
+}
+
+impl GameBoard {
+    /// Creates an empty board of the default size, without obstacles. The
+    /// snakes are usually added with `Game`, but this is handy to set up a
+    /// board by hand.
+    pub fn new(rng: StdRng) -> Self {
+        Self::with_size(rng, BoardSize::default())
+    }
+
+    /// Creates an empty board of `size`, see `new()`.
+    pub fn with_size(rng: StdRng, size: BoardSize) -> Self {
+        GameBoard {
+            nb_free_cells: size.nb_cells() as i32,
+            nb_alive_snakes: 0,
+            size,
+            wrap_mode: WrapMode::default(),
+            cells: vec![Cell::Empty; size.nb_cells()],
+            terrain: vec![Cell::Empty; size.nb_cells()],
+            food_positions: BTreeSet::new(),
+            food_spawn_steps: HashMap::new(),
+            head_positions: BTreeMap::new(),
+            step: 0,
+
+            rng,
+            food_add_probability: 0.1,
+            food_lifetime: None,
+            min_food: 0,
+            max_food: None,
+        }
+    }
+
+    /// Empties the board and changes its size, keeping its settings.
+    fn resize(&mut self, size: BoardSize) {
+        self.nb_free_cells = size.nb_cells() as i32;
+        self.size = size;
+        self.cells = vec![Cell::Empty; size.nb_cells()];
+        self.terrain = vec![Cell::Empty; size.nb_cells()];
+        self.food_positions.clear();
+        self.food_spawn_steps.clear();
+        self.head_positions.clear();
+    }
+
+    /// Creates a board from a map, one line per row, where `#` is an
+    /// obstacle, `o` is food, `~` is mud, a space or a `.` is an empty cell
+    /// and a digit is the head of the snake with this ID. The board has the
+    /// size of the map.
+    ///
+    /// The snakes are only on the board, e.g. to test a bot in a given
+    /// situation: a `Game` places its own snakes.
+    pub fn from_ascii(ascii: &str) -> Result<GameBoard, ParseError> {
+        let rows: Vec<&str> = ascii.lines().collect();
+        let width = rows.first().map_or(0, |row| row.chars().count());
+        if width == 0 {
+            return Err(ParseError::Empty);
+        }
+        let size = BoardSize::new(width as i32, rows.len() as i32);
+
+        let mut board = GameBoard::with_size(StdRng::from_entropy(), size);
+        for (y, row) in rows.iter().enumerate() {
+            if row.chars().count() != width {
+                return Err(ParseError::UnevenRow { row: y });
+            }
+            for (x, c) in row.chars().enumerate() {
+                let cell = match c {
+                    ' ' | '.' => Cell::Empty,
+                    '#' => Cell::Obstacle,
+                    'o' => Cell::Food,
+                    '~' => Cell::Mud,
+                    _ => match c.to_digit(10) {
+                        Some(id) if board.head_positions.contains_key(&id) => {
+                            return Err(ParseError::DuplicateSnake(id))
+                        }
+                        Some(id) => Cell::SnakeHead(id),
+                        None => return Err(ParseError::UnknownCell { x, y, c }),
+                    },
+                };
+                let pos = Coordinate {
+                    x: x as i32,
+                    y: y as i32,
+                }
+                .to_pos(size);
+                if cell == Cell::Mud {
+                    board.terrain[pos as usize] = Cell::Mud;
+                }
+                board.set_tile_at_pos_unchecked(pos, cell);
+            }
+        }
+        board.nb_free_cells = board.count_cells(|cell| !cell.is_blocking());
+        board.nb_alive_snakes = board.head_positions.len();
+        Ok(board)
+    }
+
+    /// The map of the board, as read by `from_ascii()`: `.` for the empty
+    /// cells and a digit for the heads. The bodies and the tails, which
+    /// `from_ascii()` can't read, are written as `*`, as well as the heads of
+    /// the snakes whose ID isn't a digit.
+    pub fn to_ascii(&self) -> String {
+        let mut ascii = String::with_capacity(self.size.nb_cells() + self.size.height as usize);
+        for (coord, cell) in self.iter_cells() {
+            if coord.x == 0 && coord.y > 0 {
+                ascii.push('\n');
+            }
+            ascii.push(match cell {
+                Cell::Empty => '.',
+                Cell::Obstacle | Cell::Wall => '#',
+                Cell::Food => 'o',
+                Cell::Mud => '~',
+                Cell::SnakeHead(id) => std::char::from_digit(id, 10).unwrap_or('*'),
+                Cell::SnakeBody(_) | Cell::SnakeTail(_) => '*',
+            });
+        }
+        ascii
+    }
+
+    /// Adds rectangular obstacles, of at most `max_width` by `max_height`
+    /// cells, on the free cells of the board. An obstacle which would box in
+    /// one of the `snakes` (see `is_boxed_in`) is drawn again.
+    fn add_random_obstacles(
+        &mut self,
+        nb_obstacles: u32,
+        max_width: u32,
+        max_height: u32,
+        snakes: &[Snake],
+    ) {
+        const MAX_ATTEMPTS_PER_OBSTACLE: u32 = 10;
+
+        let max_width = (max_width as i32).min(self.size.width);
+        let max_height = (max_height as i32).min(self.size.height);
+        if max_width < 1 || max_height < 1 {
+            return;
+        }
+
+        for _ in 0..nb_obstacles {
+            for _ in 0..MAX_ATTEMPTS_PER_OBSTACLE {
+                let w: i32 = self.rng.gen_range(0, max_width) + 1;
+                let h: i32 = self.rng.gen_range(0, max_height) + 1;
+                let x: i32 = self.rng.gen_range(0, self.size.width - w + 1);
+                let y: i32 = self.rng.gen_range(0, self.size.height - h + 1);
+
+                // Only cover the empty cells, not the snakes nor the food
+                let mut stamped = vec![];
+                for i in 0..w {
+                    for j in 0..h {
+                        let pos = Coordinate { x: x + i, y: y + j }.to_pos(self.size);
+                        if matches!(self.cells[pos as usize], Cell::Empty | Cell::Mud) {
+                            self.cells[pos as usize] = Cell::Obstacle;
+                            self.nb_free_cells -= 1;
+                            stamped.push(pos);
+                        }
+                    }
+                }
+
+                if !snakes.iter().any(|snake| self.is_boxed_in(&snake.state)) {
+                    break;
+                }
+                for pos in stamped {
+                    self.cells[pos as usize] = self.terrain[pos as usize];
+                    self.nb_free_cells += 1;
+                }
+            }
+        }
+    }
+
+    /// Returns whether a snake has no safe move, or can reach fewer cells
+    /// than its length.
+    fn is_boxed_in(&self, snake: &SnakeState) -> bool {
+        let head = match snake.try_get_head_coord(self.size) {
+            Some(head) => head,
+            None => return false,
+        };
+        self.get_non_suicide_moves(&head, &snake.current_orientation)
+            .is_empty()
+            || self.accessible_region_size(&head) < snake.length()
+    }
+
+    fn add_random_mud(&mut self, nb_patches: u32, max_size_patch: u32) {
+        let max_size_patch = (max_size_patch as i32)
+            .min(self.size.width)
+            .min(self.size.height);
+        if max_size_patch < 1 {
+            return;
+        }
+
+        for _ in 0..nb_patches {
+            let w: i32 = self.rng.gen_range(0, max_size_patch) + 1;
+            let x: i32 = self.rng.gen_range(0, self.size.width - w + 1);
+            let y: i32 = self.rng.gen_range(0, self.size.height - w + 1);
+
+            for i in 0..w {
+                for j in 0..w {
+                    let pos = Coordinate { x: x + i, y: y + j }.to_pos(self.size);
+                    if self.cells[pos as usize] == Cell::Empty {
+                        self.cells[pos as usize] = Cell::Mud;
+                        self.terrain[pos as usize] = Cell::Mud;
+                    }
+                }
+            }
+        }
+    }
+
+    fn update(&mut self) {
+        self.nb_free_cells = self.count_cells(|cell| !cell.is_blocking());
+
+        debug_assert_eq!(
+            self.food_positions.len() as i32,
+            self.count_cells(|cell| cell == Cell::Food),
+            "The food index is out of sync with the board."
+        );
+
+        self.step += 1;
+        self.expire_food();
+        self.update_food();
+    }
+
+    /// Removes the food which appeared at least `food_lifetime` steps ago.
+    fn expire_food(&mut self) {
+        if let Some(lifetime) = self.food_lifetime {
+            let step = self.step;
+            let expired: Vec<Position> = self
+                .food_spawn_steps
+                .iter()
+                .filter(|(_, &spawn_step)| step - spawn_step >= lifetime)
+                .map(|(&pos, _)| pos)
+                .collect();
+            for pos in expired {
+                self.clear_tile_at_pos(pos);
+            }
+        }
+    }
+
+    /// Adds food randomly with `food_add_probability`, and then tops the
+    /// board up to `min_food`. No food is added beyond `max_food`, which
+    /// takes precedence over `min_food`.
+    fn update_food(&mut self) {
+        let max_food = self.max_food.unwrap_or(usize::MAX);
+        if self.food_positions.len() >= max_food {
+            return;
+        }
+
+        let p = self.rng.gen_range(0., 1.);
+        if p < self.food_add_probability {
+            let x = self.rng.gen_range(0, self.size.width);
+            let y = self.rng.gen_range(0, self.size.height);
+            let coord = Coordinate { x, y };
+            let pos = coord.to_pos(self.size);
+            if self.is_pos_free_or_food(pos) {
+                self.set_tile_at_pos_unchecked(pos, Cell::Food);
+            }
+        }
+
+        let min_food = min(self.min_food, max_food);
+        if self.food_positions.len() < min_food {
+            self.add_random_food(min_food - self.food_positions.len());
+        }
+    }
+
+    /// Places up to `n` food on random free cells, without exceeding
+    /// `max_food`.
+    fn add_random_food(&mut self, n: usize) {
+        let max_food = self.max_food.unwrap_or(usize::MAX);
+        let target = min(self.food_positions.len().saturating_add(n), max_food);
+        if self.food_positions.len() >= target {
+            return;
+        }
+
+        let mut free_positions: Vec<Position> = self
+            .iter_positions()
+            .filter(|&pos| matches!(self.get_tile_at_pos(pos), Cell::Empty | Cell::Mud))
+            .collect();
+        while self.food_positions.len() < target && !free_positions.is_empty() {
+            let i = self.rng.gen_range(0, free_positions.len());
+            let pos = free_positions.swap_remove(i);
+            self.set_tile_at_pos_unchecked(pos, Cell::Food);
+        }
+    }
+
+    fn remove_dead_snakes(&mut self, dead_snake_ids: Vec<SnakeId>, snakes: &[Snake]) {
+        if !dead_snake_ids.is_empty() {
+            // Remove the dead
+            for snake in snakes.iter() {
+                if dead_snake_ids.contains(&snake.state.id) {
+                    for position in snake.state.positions.iter().cloned() {
+                        self.clear_tile_at_pos(position);
+                    }
+                }
+            }
+            // Show the alive
+            for snake in snakes.iter() {
+                let id = snake.state.id;
+                if snake.state.alive {
+                    for position in snake.state.positions.iter().cloned() {
+                        self.set_tile_at_pos_unchecked(position, Cell::SnakeBody(id));
+                    }
+                    // The head last, it's also the tail of 1-cell snakes
+                    if let Some(tail_pos) = snake.state.positions.back() {
+                        self.set_tile_at_pos_unchecked(*tail_pos, Cell::SnakeTail(id));
+                    }
+                    if let Some(head_pos) = snake.state.positions.front() {
+                        self.set_tile_at_pos_unchecked(*head_pos, Cell::SnakeHead(id));
+                    }
+                }
+            }
+        }
+    }
+
+    /// Takes a copy of the content of the board, to be able to simulate some
+    /// moves on it and then go back with `restore()`.
+    pub fn snapshot(&self) -> BoardSnapshot {
+        BoardSnapshot {
+            size: self.size,
+            wrap_mode: self.wrap_mode,
+            nb_free_cells: self.nb_free_cells,
+            nb_alive_snakes: self.nb_alive_snakes,
+            cells: self.cells.clone(),
+            terrain: self.terrain.clone(),
+            food_positions: self.food_positions.clone(),
+            food_spawn_steps: self.food_spawn_steps.clone(),
+            head_positions: self.head_positions.clone(),
+            step: self.step,
+        }
+    }
+
+    /// Creates a board with the content of `snapshot`, e.g. a snapshot saved
+    /// by some tooling with the `serde` feature.
+    pub fn from_snapshot(rng: StdRng, snapshot: &BoardSnapshot) -> Self {
+        let mut board = GameBoard::with_size(rng, snapshot.size);
+        board.restore(snapshot);
+        board
+    }
+
+    /// Puts back the content of the board as it was when `snapshot` was
+    /// taken. Note: The random generator isn't part of the snapshot.
+    pub fn restore(&mut self, snapshot: &BoardSnapshot) {
+        self.size = snapshot.size;
+        self.wrap_mode = snapshot.wrap_mode;
+        self.nb_free_cells = snapshot.nb_free_cells;
+        self.nb_alive_snakes = snapshot.nb_alive_snakes;
+        self.cells.clone_from(&snapshot.cells);
+        self.terrain.clone_from(&snapshot.terrain);
+        self.food_positions.clone_from(&snapshot.food_positions);
+        self.food_spawn_steps.clone_from(&snapshot.food_spawn_steps);
+        self.head_positions.clone_from(&snapshot.head_positions);
+        self.step = snapshot.step;
+    }
+
+    /// The positions of all the food on the board, in increasing order.
+    #[allow(dead_code)]
+    pub fn food_positions(&self) -> impl Iterator<Item = Position> + '_ {
+        self.food_positions.iter().cloned()
+    }
+
+    /// The position of the head of each snake on the board, by increasing
+    /// ID, without scanning the board.
+    pub fn head_positions(&self) -> impl Iterator<Item = (SnakeId, Position)> + '_ {
+        self.head_positions.iter().map(|(&id, &pos)| (id, pos))
+    }
+
+    #[inline]
+    pub fn size(&self) -> BoardSize {
+        self.size
+    }
+
+    #[inline]
+    pub fn wrap_mode(&self) -> WrapMode {
+        self.wrap_mode
+    }
+
+    /// Whether the board wraps around its edges, see `Game::wrap_mode()`.
+    pub fn set_wrap_mode(&mut self, wrap_mode: WrapMode) -> &mut Self {
+        self.wrap_mode = wrap_mode;
+        self
+    }
+
+    /// The distance between two cells ignoring what's on the board, but
+    /// going through the edges of wrapping boards. See `bfs_distance()` for
+    /// the length of the paths.
+    #[inline]
+    pub fn distance(&self, a: &Coordinate, b: &Coordinate) -> i32 {
+        a.distance(b, self.size, self.wrap_mode)
+    }
+
+    #[inline]
+    pub fn width(&self) -> i32 {
+        self.size.width
+    }
+
+    #[inline]
+    pub fn height(&self) -> i32 {
+        self.size.height
+    }
+
+    /// Out of the board, the cells are walls, unless the board wraps around.
+    pub fn get_tile_at_coord(&self, coord: &Coordinate) -> Cell {
+        match coord.clone().on_board(self.size, self.wrap_mode) {
+            Some(coord) => self.get_tile_at_pos(coord.to_pos(self.size)),
+            None => Cell::Wall,
+        }
+    }
+
+    pub fn get_tile_at_pos(&self, pos: Position) -> Cell {
+        // assert!(pos >= 0 && *pos < self.size.width * self.size.height);
+        self.cells[pos as usize]
+    }
+
+    /// Iterates over all the positions of the board, row by row.
+    pub fn iter_positions(&self) -> impl Iterator<Item = Position> {
+        0..self.size.nb_cells() as Position
+    }
+
+    /// Iterates over all the cells of the board with their coordinates, row
+    /// by row.
+    pub fn iter_cells(&self) -> impl Iterator<Item = (Coordinate, Cell)> + '_ {
+        self.iter_positions().map(move |pos| {
+            let coord = Coordinate::from_pos(pos, self.size);
+            (coord, self.cells[pos as usize])
+        })
+    }
+
+    /// Iterates over the cells next to `coord` which are on the board, in the
+    /// order of `Orientation::ALL`. Unless the board wraps around, there are
+    /// only two neighbors in the corners.
+    pub fn neighbors(&self, coord: &Coordinate) -> impl Iterator<Item = (Coordinate, Cell)> + '_ {
+        let Coordinate { x, y } = *coord;
+        let neighbors = [
+            Coordinate { x, y: y - 1 },
+            Coordinate { x: x + 1, y },
+            Coordinate { x, y: y + 1 },
+            Coordinate { x: x - 1, y },
+        ];
+        IntoIterator::into_iter(neighbors)
+            .filter_map(move |neighbor| neighbor.on_board(self.size, self.wrap_mode))
+            .map(move |neighbor| {
+                let cell = self.cells[neighbor.to_pos(self.size) as usize];
+                (neighbor, cell)
+            })
+    }
+
+    /// Fails if `coord` isn't on the board.
+    pub fn set_tile_at_coord(&mut self, coord: &Coordinate, cell: Cell) -> Result<(), BoardError> {
+        if coord.is_out_of_bounds(self.size) {
+            return Err(BoardError::OffBoard {
+                coord: coord.clone(),
+                width: self.size.width,
+                height: self.size.height,
+            });
+        }
+        self.set_tile_at_coord_unchecked(coord, cell);
+        Ok(())
+    }
+
+    /// Same as `set_tile_at_coord()`, for the coordinates known to be on the
+    /// board, e.g. the ones given by `next_coord_towards()`.
+    pub fn set_tile_at_coord_unchecked(&mut self, coord: &Coordinate, cell: Cell) {
+        debug_assert!(!coord.is_out_of_bounds(self.size));
+        self.set_tile_at_pos_unchecked(coord.to_pos(self.size), cell)
+    }
+
+    /// Fails if `pos` isn't on the board.
+    pub fn set_tile_at_pos(&mut self, pos: Position, cell: Cell) -> Result<(), BoardError> {
+        if !(0..self.size.nb_cells() as Position).contains(&pos) {
+            return Err(BoardError::OutOfBounds {
+                pos,
+                width: self.size.width,
+                height: self.size.height,
+            });
+        }
+        self.set_tile_at_pos_unchecked(pos, cell);
+        Ok(())
+    }
+
+    /// Same as `set_tile_at_pos()`, for the positions known to be on the
+    /// board, e.g. in the hot paths. Panics otherwise.
+    pub fn set_tile_at_pos_unchecked(&mut self, pos: Position, cell: Cell) {
+        // Keep the food index up-to-date
+        if self.cells[pos as usize] == Cell::Food {
+            self.food_positions.remove(&pos);
+            self.food_spawn_steps.remove(&pos);
+        }
+        if cell == Cell::Food {
+            self.food_positions.insert(pos);
+            self.food_spawn_steps.insert(pos, self.step);
+        }
+
+        // Same for the heads, the new head can be set before the old one
+        // is overwritten
+        if let Cell::SnakeHead(id) = self.cells[pos as usize] {
+            if self.head_positions.get(&id) == Some(&pos) {
+                self.head_positions.remove(&id);
+            }
+        }
+        if let Cell::SnakeHead(id) = cell {
+            self.head_positions.insert(id, pos);
+        }
+
+        self.cells[pos as usize] = cell;
+    }
+
+    pub fn is_suicide_moves(
+        &self,
+        from: &Coordinate,
+        orientation: &Orientation,
+        action: &Action,
+    ) -> bool {
+        let next_orientation = next_orientation(orientation, action);
+        let next_coord = next_coord_towards(from, &next_orientation, self.size, self.wrap_mode);
+        if next_coord.is_none() {
+            return true;
+        }
+        let next_coord = next_coord.unwrap();
+        assert!(!next_coord.is_out_of_bounds(self.size)); // TODO: Remove -> useless
+
+        !self.is_coord_free_or_food(&next_coord)
+    }
+
+    /// Puts back the terrain (i.e. `Cell::Empty` or `Cell::Mud`) on a cell
+    /// which was occupied by a snake or by food.
+    pub fn clear_tile_at_pos(&mut self, pos: Position) {
+        self.set_tile_at_pos_unchecked(pos, self.terrain[pos as usize])
+    }
+
+    /// The number of cells matching `predicate`, e.g.
+    /// `board.count_cells(|cell| cell == Cell::Obstacle)`.
+    pub fn count_cells(&self, predicate: impl Fn(Cell) -> bool) -> i32 {
+        self.cells.iter().filter(|&&cell| predicate(cell)).count() as i32
+    }
+
+    /// Note: Mud is considered free, as the snakes can go through it.
+    #[inline]
+    pub fn is_pos_free_or_food(&self, pos: Position) -> bool {
+        !self.get_tile_at_pos(pos).is_blocking()
+    }
+
+    /// Note: Mud is considered free, as the snakes can go through it.
+    #[inline]
+    pub fn is_coord_free_or_food(&self, coord: &Coordinate) -> bool {
+        !self.get_tile_at_coord(coord).is_blocking()
+    }
+
+    /// Visits the free cells (see `is_coord_free_or_food`) reachable from
+    /// `from` in breadth-first order, with their distance to `from`. `from`
+    /// doesn't need to be free (e.g. a snake head), and it's only visited if
+    /// it is. The traversal stops as soon as `visit` returns `false`.
+    ///
+    /// Note: Each cell costs one step, mud included.
+    fn visit_free_cells<F>(&self, from: &Coordinate, mut visit: F)
+    where
+        F: FnMut(&Coordinate, i32) -> bool,
+    {
+        let mut added = vec![false; self.size.nb_cells()];
+        let mut queue = VecDeque::new();
+        queue.push_back((from.clone(), 0));
+        if !from.is_out_of_bounds(self.size) {
+            added[from.to_pos(self.size) as usize] = true;
+            if self.is_coord_free_or_food(from) && !visit(from, 0) {
+                return;
+            }
+        }
+
+        while let Some((coord, dist)) = queue.pop_front() {
+            for (neighbor, cell) in self.neighbors(&coord) {
+                let pos = neighbor.to_pos(self.size) as usize;
+                if added[pos] || cell.is_blocking() {
+                    continue;
+                }
+                if !visit(&neighbor, dist + 1) {
+                    return;
+                }
+                added[pos] = true;
+                queue.push_back((neighbor, dist + 1));
+            }
+        }
+    }
+
+    /// Returns the number of free cells (see `is_coord_free_or_food`)
+    /// reachable from `from`, including `from` if it's free. `from` doesn't
+    /// need to be free, so that the region of a snake head can be measured.
+    pub fn accessible_region_size(&self, from: &Coordinate) -> usize {
+        let mut size = 0;
+        self.visit_free_cells(from, |_, _| {
+            size += 1;
+            true
+        });
+        size
+    }
+
+    /// Same as `accessible_region_size`, but stops as soon as `limit` cells
+    /// or food are found, so the count is at most `limit`.
+    /// Returns the count and whether food is reachable.
+    fn flood_fill(&self, from: &Coordinate, limit: usize) -> (usize, bool) {
+        let mut size = 0;
+        let mut food = false;
+        self.visit_free_cells(from, |coord, _| {
+            if self.get_tile_at_coord(coord) == Cell::Food {
+                food = true;
+                return false;
+            }
+            size += 1;
+            size < limit
+        });
+        (size, food)
+    }
+
+    /// Returns the length of the shortest path from `from` to `to` going
+    /// only through free cells (see `is_coord_free_or_food`), or `None` if
+    /// `to` isn't reachable. `from` doesn't need to be free, so that the path
+    /// can start from a snake head.
+    ///
+    /// Note: Each cell costs one step, mud included.
+    pub fn bfs_distance(&self, from: &Coordinate, to: &Coordinate) -> Option<i32> {
+        if from == to {
+            return Some(0);
+        }
+
+        let mut distance = None;
+        self.visit_free_cells(from, |coord, dist| {
+            if coord == to {
+                distance = Some(dist);
+            }
+            distance.is_none()
+        });
+        distance
+    }
+
+    pub fn get_non_suicide_moves(
+        &self,
+        from: &Coordinate,
+        orientation: &Orientation,
+    ) -> Vec<Action> {
+        [Action::Left, Action::Front, Action::Right]
+            .iter()
+            .filter_map(|action| {
+                if self.is_suicide_moves(from, orientation, action) {
+                    None
+                } else {
+                    Some(action.clone())
+                }
+            })
+            .collect()
+    }
+
+    #[allow(dead_code)]
+    pub fn print(&self) {
+        print!("+");
+        for _ in 0..self.size.width {
+            print!("-");
+        }
+        println!("+");
+
+        for (coord, cell) in self.iter_cells() {
+            if coord.x == 0 {
+                print!("|");
+            }
+            print!("{}", cell);
+            if coord.x == self.size.width - 1 {
+                println!("|");
+            }
+        }
+
+        print!("+");
+        for _ in 0..self.size.width {
+            print!("-");
+        }
+        println!("+");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::prelude::*;
+
+    use super::*;
+    use crate::heuristic_bot::HeuristicBot;
+    use crate::random_bot::RandomBot;
+
+    /// A bot which always goes forward.
+    struct FrontBot;
+
+    impl SnakeBot for FrontBot {
+        fn get_next_action(&mut self, _: &SnakeState, _: &GameBoard) -> Action {
+            Action::Front
+        }
+    }
+
+    /// A bot turning in circles, chasing its tail.
+    struct TurnRightBot;
+
+    impl SnakeBot for TurnRightBot {
+        fn get_next_action(&mut self, _: &SnakeState, _: &GameBoard) -> Action {
+            Action::Right
+        }
+    }
+
+    /// A bot playing random moves, reproducible thanks to its seed.
+    struct SeededRandomBot(StdRng);
+
+    impl SnakeBot for SeededRandomBot {
+        fn get_next_action(&mut self, _: &SnakeState, _: &GameBoard) -> Action {
+            [Action::Left, Action::Front, Action::Right]
+                .choose(&mut self.0)
+                .unwrap()
+                .clone()
+        }
+    }
+
+    /// Panics if the board and the snakes of `game` are out of sync.
+    fn check_invariants(game: &Game) {
+        let board = &game.state.board;
+        let nb_cells = board.size().nb_cells();
+        let mut owner: Vec<Option<SnakeId>> = vec![None; nb_cells];
+
+        for snake in game.state.snakes.iter().filter(|snake| snake.state.alive) {
+            let id = snake.state.id;
+            let positions = &snake.state.positions;
+            assert!(!positions.is_empty(), "Snake {} has no position.", id);
+            for (i, &pos) in positions.iter().enumerate() {
+                assert_eq!(owner[pos as usize], None, "Snakes overlap at {}.", pos);
+                owner[pos as usize] = Some(id);
+
+                let expected = if i == 0 {
+                    Cell::SnakeHead(id)
+                } else if i == positions.len() - 1 {
+                    Cell::SnakeTail(id)
+                } else {
+                    Cell::SnakeBody(id)
+                };
+                assert_eq!(board.get_tile_at_pos(pos), expected, "At {}.", pos);
+            }
+
+            let count = |cell: Cell| board.count_cells(|c| c == cell) as usize;
+            assert_eq!(count(Cell::SnakeHead(id)), 1);
+            assert_eq!(count(Cell::SnakeTail(id)), (positions.len() > 1) as usize);
+            let nb_snake_cells = count(Cell::SnakeHead(id))
+                + count(Cell::SnakeBody(id))
+                + count(Cell::SnakeTail(id));
+            assert_eq!(nb_snake_cells, positions.len(), "Length of snake {}.", id);
+        }
+
+        for (pos, cell) in board.cells.iter().enumerate() {
+            match cell {
+                _ if cell.is_snake() => {
+                    assert!(owner[pos].is_some(), "Leftover snake cell at {}.", pos)
+                }
+                Cell::Food => {
+                    assert!(board.food_positions.contains(&(pos as Position)));
+                    assert!(board.food_spawn_steps.contains_key(&(pos as Position)));
+                }
+                _ => {}
+            }
+        }
+        assert!(board
+            .food_positions
+            .iter()
+            .all(|&pos| board.cells[pos as usize] == Cell::Food));
+        let mut heads: Vec<(SnakeId, Position)> = game
+            .state
+            .snakes
+            .iter()
+            .filter(|snake| snake.state.alive)
+            .map(|snake| (snake.state.id, snake.state.positions[0]))
+            .collect();
+        heads.sort();
+        assert_eq!(board.head_positions().collect::<Vec<_>>(), heads);
+
+        let nb_free_cells = board.count_cells(|cell| !cell.is_blocking());
+        assert_eq!(board.nb_free_cells, nb_free_cells);
+    }
+
+    proptest::proptest! {
+        #[test]
+        fn board_invariants_hold_after_each_step(
+            seed: u64,
+            nb_snakes in 1..=4_u32,
+            initial_length in 1..=5_usize,
+            growth_rate in 1..=4_i32,
+            hunger in proptest::option::of(5..=30_u32),
+            nb_mud_patches in 0..=5_u32,
+        ) {
+            let mut game = Game::with_seed(seed);
+            game.continue_simulation_if_known_winner(true)
+                .with_mud(nb_mud_patches);
+            if let Some(max_health) = hunger {
+                game.with_hunger(max_health);
+            }
+            for id in 0..nb_snakes {
+                game.add_snake_with_config(
+                    id,
+                    Box::new(SeededRandomBot(StdRng::seed_from_u64(seed ^ id as u64))),
+                    SnakeConfig {
+                        initial_length,
+                        growth_rate,
+                    },
+                );
+            }
+            game.initialize();
+
+            for _ in 0..200 {
+                if game.state.snakes.iter().all(|snake| !snake.state.alive) {
+                    break;
+                }
+                game.step();
+                check_invariants(&game);
+            }
+        }
+    }
+
+    /// Two snakes chasing their tails on an empty board, until `max_steps`.
+    /// The second one is longer.
+    fn tail_chasing(draw_policy: DrawPolicy, max_steps: u32) -> GameResults {
+        let board = GameBoard::from_ascii(&[".........."; 5].join("\n")).unwrap();
+        Game::with_seed(0)
+            .with_board(board)
+            .without_food()
+            .with_growth_mode(GrowthMode::FoodOnly)
+            .with_start_positions(vec![
+                (0, Coordinate { x: 2, y: 2 }, Orientation::North),
+                (1, Coordinate { x: 7, y: 2 }, Orientation::North),
+            ])
+            .with_draw_policy(draw_policy)
+            .max_steps(max_steps)
+            .add_snake(0, Box::new(TurnRightBot))
+            .add_snake_with_config(
+                1,
+                Box::new(TurnRightBot),
+                SnakeConfig {
+                    initial_length: 3,
+                    ..SnakeConfig::default()
+                },
+            )
+            .initialize()
+            .run_to_end()
+    }
+
+    #[test]
+    fn games_end_at_the_max_steps() {
+        let results = tail_chasing(DrawPolicy::default(), 100);
+        assert_eq!(results.steps, 100);
+        assert!(matches!(results.winner, Some(GameResultWinner::Draw)));
+        assert!(results
+            .snakes
+            .iter()
+            .all(|snake| snake.died_at_step.is_none()));
+
+        let results = tail_chasing(DrawPolicy::LongestWins, 42);
+        assert_eq!(results.steps, 42);
+        assert!(matches!(results.winner, Some(GameResultWinner::Winner(1))));
+    }
+
+    /// Two snakes on the row `y = 8` going towards each other, which collide
+    /// head-on at (10, 8) on the second step. The first one eats food on the
+    /// way, and the second one is longer.
+    fn head_on_collision(draw_policy: DrawPolicy, second_length: usize) -> GameResults {
+        Game::with_seed(0)
+            .with_start_positions(vec![
+                (0, Coordinate { x: 8, y: 8 }, Orientation::East),
+                (1, Coordinate { x: 12, y: 8 }, Orientation::West),
+            ])
+            .with_food_schedule(vec![(0, Coordinate { x: 9, y: 8 })])
+            .with_draw_policy(draw_policy)
+            .add_snake(0, Box::new(FrontBot))
+            .add_snake_with_config(
+                1,
+                Box::new(FrontBot),
+                SnakeConfig {
+                    initial_length: second_length,
+                    ..SnakeConfig::default()
+                },
+            )
+            .initialize()
+            .run_to_end()
+    }
+
+    #[test]
+    fn mud_fits_small_boards_of_any_size() {
+        // The board is smaller than a patch, and resized after the request
+        let mut game = Game::with_seed(0);
+        game.with_mud(4)
+            .with_board_size(3, 2)
+            .with_obstacles(0, 0, 0)
+            .without_food()
+            .initialize();
+        assert!(game.state.board.count_cells(|cell| cell == Cell::Mud) > 0);
+    }
+
+    fn heuristic_game_fingerprint(seed: u64) -> u64 {
+        let mut game = Game::with_seed(seed);
+        game.record(true)
+            .with_hunger(50)
+            .with_mud(3)
+            .continue_simulation_if_known_winner(false);
+        for id in 0..4 {
+            game.add_snake(id, Box::new(HeuristicBot::default()));
+        }
+        game.initialize().run_to_end();
+        game.replay().unwrap().fingerprint()
+    }
+
+    #[test]
+    fn replays_have_a_frame_per_step() {
+        let mut game = Game::with_seed(5);
+        game.record(true);
+        for id in 0..2 {
+            game.add_snake(id, Box::new(HeuristicBot::default()));
+        }
+        game.initialize();
+        let initial = game.state.board.cells.clone();
+        let results = game.run_to_end();
+        let replay = game.replay().unwrap();
+
+        assert_eq!(replay.len(), results.steps as usize + 1);
+        assert_eq!(replay.frame(0), Some(initial.as_slice()));
+        assert_eq!(
+            replay.frame(replay.len() - 1),
+            Some(game.state.board.cells.as_slice())
+        );
+        assert_eq!(replay.frame(replay.len()), None);
+    }
+
+    #[test]
+    fn replays_survive_the_binary_format() {
+        let mut game = Game::with_seed(3);
+        game.record(true).with_mud(2);
+        for id in 0..3 {
+            game.add_snake(id, Box::new(HeuristicBot::default()));
+        }
+        game.initialize().run_to_end();
+        let replay = game.replay().unwrap();
+
+        let bytes = replay.to_bytes().unwrap();
+        assert_eq!(Replay::from_bytes(&bytes).as_ref(), Ok(replay));
+
+        assert_eq!(
+            Replay::from_bytes(&bytes[..bytes.len() - 10]),
+            Err(ReplayFormatError::BadChecksum)
+        );
+        let mut corrupted = bytes.clone();
+        corrupted[100] ^= 1;
+        assert_eq!(
+            Replay::from_bytes(&corrupted),
+            Err(ReplayFormatError::BadChecksum)
+        );
+    }
+
+    #[test]
+    fn replays_too_large_for_the_binary_format_are_rejected() {
+        let small = BoardSize {
+            width: 2,
+            height: 2,
+        };
+        let replay = |size: BoardSize, actions: Vec<Vec<Action>>| Replay {
+            size,
+            frames: vec![],
+            actions,
+        };
+
+        assert!(replay(small, vec![vec![Action::Front; 255]])
+            .to_bytes()
+            .is_ok());
+        assert_eq!(
+            replay(small, vec![vec![Action::Front; 256]]).to_bytes(),
+            Err(ReplayFormatError::TooLarge)
+        );
+        let wide = BoardSize {
+            width: 70_000,
+            height: 2,
+        };
+        assert_eq!(
+            replay(wide, vec![]).to_bytes(),
+            Err(ReplayFormatError::TooLarge)
+        );
+    }
+
+    #[test]
+    fn initial_orientations_are_seeded() {
+        let orientations = |seed: u64, random: bool| {
+            let mut game = Game::with_seed(seed);
+            game.random_initial_orientation(random);
+            for id in 0..4 {
+                game.add_snake(id, Box::new(FrontBot));
+            }
+            game.initialize();
+            game.state
+                .snakes()
+                .map(|snake| snake.current_orientation.clone())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(orientations(0, false), vec![Orientation::North; 4]);
+        assert_eq!(orientations(7, true), orientations(7, true));
+        assert!((0..10).any(|seed| orientations(seed, true) != vec![Orientation::North; 4]));
+    }
+
+    #[test]
+    fn seeded_games_are_reproducible() {
+        assert_eq!(Game::with_seed(42).seed(), 42);
+
+        assert_eq!(
+            heuristic_game_fingerprint(42),
+            heuristic_game_fingerprint(42)
+        );
+        assert_ne!(
+            heuristic_game_fingerprint(42),
+            heuristic_game_fingerprint(43)
+        );
+
+        // Down to the bytes, with seeded random bots
+        let random_game_bytes = |seed: u64| {
+            let mut game = Game::with_seed(seed);
+            game.record(true).continue_simulation_if_known_winner(false);
+            for id in 0..3 {
+                game.add_snake(id, Box::new(RandomBot::with_seed(seed + id as u64)));
+            }
+            game.initialize().run_to_end();
+            game.replay().unwrap().to_bytes().unwrap()
+        };
+        assert_eq!(random_game_bytes(5), random_game_bytes(5));
+        assert_ne!(random_game_bytes(5), random_game_bytes(6));
+    }
+
+    #[test]
+    fn recorded_actions_replay_the_same_game() {
+        let set_up = |game: &mut Game| {
+            game.record(true).continue_simulation_if_known_winner(false);
+            for id in 0..3 {
+                game.add_snake(id, Box::new(RandomBot::new()));
+            }
+        };
+        let mut recorded = Game::with_seed(7);
+        set_up(&mut recorded);
+        recorded.initialize().run_to_end();
+
+        // The random bots play differently, but their actions are replaced
+        let actions = recorded.replay().unwrap().actions().to_vec();
+        let mut replayed = Game::replay_actions(7, actions);
+        set_up(&mut replayed);
+        replayed.initialize().run_to_end();
+
+        assert_eq!(
+            replayed.replay().unwrap().fingerprint(),
+            recorded.replay().unwrap().fingerprint()
+        );
+        assert_eq!(replayed.state.board.cells, recorded.state.board.cells);
+    }
+
+    #[test]
+    fn snakes_are_never_boxed_in_by_the_obstacles() {
+        for seed in 0..50 {
+            let mut game = Game::with_seed(seed);
+            game.with_obstacles(60, 1, 8);
+            for id in 0..4 {
+                game.add_snake_with_config(
+                    id,
+                    Box::new(FrontBot),
+                    SnakeConfig {
+                        initial_length: 4,
+                        ..SnakeConfig::default()
+                    },
+                );
+            }
+            game.initialize();
+
+            let board = &game.state.board;
+            for snake in &game.state.snakes {
+                let head = snake.state.get_head_coord(game.state.board.size());
+                assert!(
+                    !board
+                        .get_non_suicide_moves(&head, &snake.state.current_orientation)
+                        .is_empty(),
+                    "Snake {} has no safe move with the seed {}.",
+                    snake.state.id,
+                    seed
+                );
+                assert!(board.accessible_region_size(&head) >= 4);
+            }
+        }
+    }
+
+    #[test]
+    fn snakes_dont_spawn_in_pockets() {
+        // A one-cell pocket on the left, and a corridor on the right
+        let board = GameBoard::from_ascii(" #   ").unwrap();
+        for seed in 0..20 {
+            let mut game = Game::with_seed(seed);
+            game.with_board(board.clone())
+                .add_snake(0, Box::new(FrontBot))
+                .initialize();
+            assert!(
+                game.state.snakes[0]
+                    .state
+                    .get_head_coord(game.state.board.size())
+                    .x
+                    >= 2
+            );
+        }
+
+        // Only pockets
+        let board = GameBoard::from_ascii(" # #\n####\n# # ").unwrap();
+        let mut game = Game::with_seed(0);
+        game.with_board(board).add_snake(0, Box::new(FrontBot));
+        assert_eq!(
+            game.try_initialize().err(),
+            Some(InitializationError::NoRoomForSnake { id: 0, length: 1 })
+        );
+    }
+
+    #[test]
+    fn ascii_maps_must_be_rectangular() {
+        assert_eq!(
+            GameBoard::from_ascii("  \n \n").err(),
+            Some(ParseError::UnevenRow { row: 1 })
+        );
+        assert_eq!(
+            GameBoard::from_ascii(" x").err(),
+            Some(ParseError::UnknownCell { x: 1, y: 0, c: 'x' })
+        );
+        assert_eq!(GameBoard::from_ascii("").err(), Some(ParseError::Empty));
+        assert_eq!(
+            GameBoard::from_ascii("1.\n.1").err(),
+            Some(ParseError::DuplicateSnake(1))
+        );
+    }
+
+    #[test]
+    fn ascii_maps_round_trip() {
+        let map = "#..o.\n.0~..\n...#1";
+        let board = GameBoard::from_ascii(map).unwrap();
+
+        assert_eq!(board.size(), BoardSize::new(5, 3));
+        assert_eq!(
+            board.head_positions().collect::<Vec<_>>(),
+            vec![(0, 6), (1, 14)]
+        );
+        assert_eq!(board.nb_alive_snakes, 2);
+        assert_eq!(board.to_ascii(), map);
+        assert_eq!(
+            GameBoard::from_ascii(" #\n  ").unwrap().to_ascii(),
+            ".#\n.."
+        );
+    }
+
+    #[test]
+    fn food_is_topped_up_to_the_minimum_within_the_maximum() {
+        let mut board = GameBoard::new(StdRng::seed_from_u64(0));
+        board.food_add_probability = 0.;
+        board.min_food = 5;
+        board.update();
+        assert_eq!(board.food_positions().count(), 5);
+
+        // The maximum wins over the minimum and the random food
+        let mut board = GameBoard::new(StdRng::seed_from_u64(0));
+        board.food_add_probability = 1.;
+        board.min_food = 5;
+        board.max_food = Some(3);
+        for _ in 0..20 {
+            board.update();
+            assert_eq!(board.food_positions().count(), 3);
+        }
+    }
+
+    #[test]
+    fn snakes_grow_according_to_the_growth_mode() {
+        let length_after_10_steps = |growth_mode: Option<GrowthMode>| {
+            let mut game = Game::with_seed(0);
+            game.without_food()
+                .add_snake(0, Box::new(HeuristicBot::default()));
+            if let Some(growth_mode) = growth_mode {
+                game.with_growth_mode(growth_mode);
+            }
+            game.initialize();
+            for _ in 0..10 {
+                game.step();
+            }
+            assert!(game.state.snakes[0].state.alive);
+            game.state.snakes[0].state.length()
+        };
+
+        assert_eq!(
+            length_after_10_steps(None),
+            1 + 10 / Snake::GROWTH_RATE as usize
+        );
+        assert_eq!(length_after_10_steps(Some(GrowthMode::Periodic(2))), 1 + 5);
+        assert_eq!(length_after_10_steps(Some(GrowthMode::FoodOnly)), 1);
+    }
+
+    #[test]
+    fn initial_food_is_placed_within_the_maximum() {
+        let mut game = Game::with_seed(0);
+        game.with_initial_food(4)
+            .add_snake(0, Box::new(HeuristicBot::default()))
+            .initialize();
+        assert_eq!(game.state.board.food_positions().count(), 4);
+        assert!(game
+            .state
+            .board
+            .food_positions()
+            .all(|pos| game.state.board.get_tile_at_pos(pos) == Cell::Food));
+
+        let mut game = Game::with_seed(0);
+        game.with_initial_food(4)
+            .with_max_food(2)
+            .add_snake(0, Box::new(HeuristicBot::default()))
+            .initialize();
+        assert_eq!(game.state.board.food_positions().count(), 2);
+    }
+
+    #[test]
+    fn uneaten_food_expires() {
+        let mut board = GameBoard::new(StdRng::seed_from_u64(0));
+        board.food_add_probability = 0.;
+        board.food_lifetime = Some(2);
+        board
+            .set_tile_at_coord(&Coordinate { x: 1, y: 1 }, Cell::Food)
+            .unwrap();
+
+        board.update();
+        assert_eq!(board.food_positions().count(), 1);
+        board
+            .set_tile_at_coord(&Coordinate { x: 2, y: 2 }, Cell::Food)
+            .unwrap();
+        board.update();
+        assert_eq!(
+            board.food_positions().collect::<Vec<_>>(),
+            vec![Coordinate { x: 2, y: 2 }.to_pos(board.size())]
+        );
+        assert_eq!(
+            board.get_tile_at_coord(&Coordinate { x: 1, y: 1 }),
+            Cell::Empty
+        );
+        board.update();
+        assert_eq!(board.food_positions().count(), 0);
+    }
+
+    #[test]
+    fn interrupted_games_have_partial_results() {
+        let mut game = Game::with_seed(0);
+        game.add_snake(0, Box::new(HeuristicBot::default()))
+            .add_snake(1, Box::new(HeuristicBot::default()))
+            .initialize();
+        game.step().step();
+
+        let results = game.interrupt();
+        assert!(results.interrupted);
+        assert!(results.winner.is_none());
+        assert_eq!(results.steps, 2);
+        assert_eq!(results.snakes.len(), 2);
+        assert!(game.is_game_over());
+    }
+
+    #[test]
+    fn timing_counts_the_moves_of_each_bot() {
+        let mut game = Game::with_seed(0);
+        game.with_timing()
+            .add_snake(0, Box::new(HeuristicBot::default()))
+            .add_snake(1, Box::new(HeuristicBot::default()))
+            .initialize();
+        for _ in 0..3 {
+            game.step();
+        }
+
+        let timings = game.timings().unwrap();
+        assert_eq!(timings.len(), 2);
+        assert_eq!(timings[1].id, 1);
+        assert_eq!(timings[1].nb_moves, 3);
+        assert!(timings[1].min <= timings[1].average());
+        assert!(timings[1].average() <= timings[1].max);
+        assert!(Game::default().timings().is_none());
+    }
+
+    #[test]
+    fn snakes_start_around_generated_layouts() {
+        // Only the first column is free
+        let mut game = Game::with_seed(0);
+        game.with_obstacles(0, 0, 0)
+            .with_board_generator(|board| {
+                for (coord, _) in board.iter_cells().collect::<Vec<_>>() {
+                    if coord.x > 0 {
+                        board.set_tile_at_coord(&coord, Cell::Obstacle).unwrap();
+                    }
+                }
+            })
+            .add_snake(0, Box::new(FrontBot))
+            .initialize();
+
+        assert_eq!(
+            game.state.snakes[0]
+                .state
+                .get_head_coord(game.state.board.size())
+                .x,
+            0
+        );
+        assert_eq!(
+            game.state
+                .board
+                .get_tile_at_coord(&Coordinate { x: 1, y: 0 }),
+            Cell::Obstacle
+        );
+    }
+
+    #[test]
+    fn snakes_know_their_cells() {
+        let mut game = Game::with_seed(0);
+        game.add_snake_with_config(
+            0,
+            Box::new(HeuristicBot::default()),
+            SnakeConfig {
+                initial_length: 3,
+                ..SnakeConfig::default()
+            },
+        )
+        .initialize();
+        let snake = &game.state.snakes[0].state;
+
+        assert_eq!(snake.length(), 3);
+        assert!(snake.body_contains(snake.get_head_pos()));
+        assert!(snake.body_contains(*snake.positions.back().unwrap()));
+        let outside = game
+            .state
+            .board
+            .iter_positions()
+            .find(|&pos| !snake.positions.contains(&pos))
+            .unwrap();
+        assert!(!snake.body_contains(outside));
+    }
+
+    #[test]
+    fn cells_know_their_snake() {
+        assert_eq!(Cell::SnakeTail(3).snake_id(), Some(3));
+        assert!(Cell::SnakeHead(0).is_snake());
+        assert!(!Cell::Food.is_snake());
+
+        assert!(Cell::SnakeBody(1).is_blocking());
+        assert!(Cell::Wall.is_blocking());
+        assert!(!Cell::Mud.is_blocking());
+    }
+
+    #[test]
+    fn neighbors_stay_on_the_board() {
+        let board = GameBoard::from_ascii(".o.\n~#.\n...").unwrap();
+
+        let corner: Vec<(Coordinate, Cell)> = board.neighbors(&Coordinate { x: 0, y: 0 }).collect();
+        assert_eq!(
+            corner,
+            vec![
+                (Coordinate { x: 1, y: 0 }, Cell::Food),
+                (Coordinate { x: 0, y: 1 }, Cell::Mud),
+            ]
+        );
+        assert_eq!(board.neighbors(&Coordinate { x: 1, y: 1 }).count(), 4);
+    }
+
+    #[test]
+    fn cells_are_counted_by_predicate() {
+        let board = GameBoard::from_ascii(".o\n~#\no.").unwrap();
+        assert_eq!(board.count_cells(|cell| cell == Cell::Food), 2);
+        assert_eq!(board.count_cells(|cell| cell == Cell::Mud), 1);
+        assert_eq!(
+            board.count_cells(|cell| !cell.is_blocking()),
+            board.nb_free_cells
+        );
+    }
+
+    #[test]
+    fn iter_cells_scans_the_board_row_by_row() {
+        let board = GameBoard::from_ascii(".o\n~#").unwrap();
+        let cells: Vec<(Coordinate, Cell)> = board.iter_cells().collect();
+
+        assert_eq!(cells.len(), board.size().nb_cells());
+        assert_eq!(cells[1], (Coordinate { x: 1, y: 0 }, Cell::Food));
+        let second_row = board.width() as usize;
+        assert_eq!(cells[second_row], (Coordinate { x: 0, y: 1 }, Cell::Mud));
+        assert!(cells
+            .iter()
+            .zip(board.iter_positions())
+            .all(|((coord, _), pos)| coord.to_pos(board.size()) == pos));
+    }
+
+    #[test]
+    fn ghosts_wait_instead_of_colliding() {
+        let mut game = Game::with_seed(0);
+        game.with_ghost_mode()
+            .with_obstacles(0, 0, 0)
+            .with_start_positions(vec![
+                (0, Coordinate { x: 8, y: 8 }, Orientation::East),
+                (1, Coordinate { x: 11, y: 8 }, Orientation::West),
+            ])
+            .add_snake(0, Box::new(FrontBot))
+            .add_snake(1, Box::new(FrontBot))
+            .initialize();
+        for _ in 0..5 {
+            game.step();
+        }
+
+        assert!(!game.is_game_over());
+        let heads: Vec<Coordinate> = game
+            .state
+            .snakes
+            .iter()
+            .map(|snake| snake.state.get_head_coord(game.state.board.size()))
+            .collect();
+        assert_eq!(
+            heads,
+            vec![Coordinate { x: 9, y: 8 }, Coordinate { x: 10, y: 8 }]
+        );
     }
 
-    pub fn initialize(&mut self) -> &mut Self {
-        let mut rng = thread_rng();
-        let nb_cells = BOARD_WIDTH * BOARD_HEIGHT;
+    #[test]
+    fn games_can_be_played_on_boards_of_any_size() {
+        for &(width, height) in &[(10, 10), (50, 30)] {
+            let mut game = Game::new(width, height);
+            game.record(true)
+                .add_snake(0, Box::new(HeuristicBot::default()))
+                .add_snake(1, Box::new(HeuristicBot::default()))
+                .initialize();
+            assert_eq!(
+                game.state.board.iter_cells().count(),
+                (width * height) as usize
+            );
+            game.run_to_end();
+            check_invariants(&game);
 
-        // Place the snakes on the board
-        let head_positions = vec![];
-        for snake in &mut self.snakes {
-            let mut pos = None;
-            for _ in 0..10_000 {
-                let p = rng.gen_range(0, nb_cells);
-
-                // Don't want two snakes at the same position
-                if head_positions.contains(&p) {
-                    continue;
-                }
+            let replay = game.replay().unwrap();
+            assert_eq!(replay.size(), BoardSize::new(width, height));
+            let bytes = replay.to_bytes().unwrap();
+            assert_eq!(&Replay::from_bytes(&bytes).unwrap(), replay);
+        }
 
-                // Check that the cell is free
-                match self.board.get_tile_at_pos(p) {
-                    Cell::Empty | Cell::Food => {
-                        pos = Some(p);
-                        break;
-                    }
-                    _ => {} // Retry
-                }
-            }
-            let pos = pos.expect("Not able to find an initial position for the snake.");
+        let size = BoardSize::new(50, 30);
+        let coord = Coordinate { x: 49, y: 29 };
+        assert_eq!(coord.to_pos(size), 50 * 30 - 1);
+        assert_eq!(Coordinate::from_pos(coord.to_pos(size), size), coord);
+        assert!(Coordinate { x: 32, y: 0 }.is_out_of_bounds(BoardSize::default()));
+        assert!(!Coordinate { x: 32, y: 0 }.is_out_of_bounds(size));
+    }
 
-            // Update the snake
-            snake.state.positions.push_front(pos);
-            snake.state.current_orientation = Orientation::North;
+    #[test]
+    fn snakes_go_through_the_edges_of_wrapping_boards() {
+        let (max_x, max_y) = (31, 15);
+        let crossings = [
+            (
+                Coordinate { x: 5, y: 0 },
+                Orientation::North,
+                Coordinate { x: 5, y: max_y },
+            ),
+            (
+                Coordinate { x: max_x, y: 5 },
+                Orientation::East,
+                Coordinate { x: 0, y: 5 },
+            ),
+            (
+                Coordinate { x: 5, y: max_y },
+                Orientation::South,
+                Coordinate { x: 5, y: 0 },
+            ),
+            (
+                Coordinate { x: 0, y: 5 },
+                Orientation::West,
+                Coordinate { x: max_x, y: 5 },
+            ),
+        ];
+        for (start, orientation, expected) in crossings.iter() {
+            let mut game = Game::with_seed(0);
+            game.wrap_mode(WrapMode::Wrap)
+                .with_obstacles(0, 0, 0)
+                .with_start_positions(vec![(0, start.clone(), orientation.clone())])
+                .add_snake(0, Box::new(FrontBot))
+                .initialize();
+            let board = &game.state.board;
+            assert!(board
+                .get_non_suicide_moves(start, orientation)
+                .contains(&Action::Front));
 
-            // Update the board
-            self.board
-                .set_tile_at_pos(pos, Cell::SnakeHead(snake.state.id));
+            game.step();
+            let snake = &game.state.snakes[0].state;
+            assert!(snake.alive, "Died going {:?}.", orientation);
+            assert_eq!(&snake.get_head_coord(game.state.board.size()), expected);
         }
-        self.initialized = true;
-        self
+
+        // Without wrapping, the edges are walls
+        let mut game = Game::with_seed(0);
+        game.with_start_positions(vec![(0, Coordinate { x: 0, y: 5 }, Orientation::West)])
+            .add_snake(0, Box::new(FrontBot))
+            .initialize();
+        game.step();
+        assert!(!game.state.snakes[0].state.alive);
     }
 
-    #[allow(dead_code)]
-    pub fn print(&mut self) -> &mut Self {
-        self.board.print();
-        self
+    #[test]
+    fn distances_go_through_the_edges_of_wrapping_boards() {
+        let mut board = GameBoard::with_size(StdRng::seed_from_u64(0), BoardSize::new(10, 6));
+        let (a, b) = (Coordinate { x: 1, y: 0 }, Coordinate { x: 8, y: 4 });
+        assert_eq!(board.distance(&a, &b), 11);
+        board.wrap_mode = WrapMode::Wrap;
+        assert_eq!(board.distance(&a, &b), 5);
+        assert_eq!(board.distance(&b, &a), 5);
     }
 
-    pub fn step(&mut self) -> &mut Self {
-        assert!(self.initialized);
-        //        println!("Running step {}...", self.step);
+    #[test]
+    fn bfs_distances_go_around_the_obstacles() {
+        let mut board = GameBoard::from_ascii(
+            "0.#..\n\
+             ..#.#\n\
+             ....#\n\
+             ####.",
+        )
+        .unwrap();
+        let head = Coordinate { x: 0, y: 0 };
+        let cell = |x, y| Coordinate { x, y };
 
-        // Before-step callbacks
-        for before_each_step in &self.before_each_step {
-            before_each_step(&self.board);
-        }
+        assert_eq!(board.bfs_distance(&head, &head), Some(0));
+        // From the head, around the wall
+        assert_eq!(board.bfs_distance(&head, &cell(3, 0)), Some(7));
+        // Walled in, and blocking
+        assert_eq!(board.bfs_distance(&head, &cell(4, 3)), None);
+        assert_eq!(board.bfs_distance(&head, &cell(2, 0)), None);
 
-        // Remember which snakes are still alive
-        let prev_nb_alive = self.snakes.iter().filter(|snake| snake.state.alive).count();
+        // Through the edges, where the pocket opens
+        board.wrap_mode = WrapMode::Wrap;
+        assert_eq!(board.bfs_distance(&head, &cell(4, 3)), Some(2));
+        assert_eq!(board.bfs_distance(&head, &cell(3, 0)), Some(2));
+    }
 
-        // Update the board before calling the bots
-        self.board.nb_alive_snakes = prev_nb_alive;
+    #[test]
+    fn tiles_out_of_the_board_cant_be_set() {
+        let mut board = GameBoard::with_size(StdRng::seed_from_u64(0), BoardSize::new(4, 3));
+        assert_eq!(board.set_tile_at_pos(11, Cell::Food), Ok(()));
+        assert_eq!(
+            board.get_tile_at_coord(&Coordinate { x: 3, y: 2 }),
+            Cell::Food
+        );
+        assert_eq!(board.food_positions().collect::<Vec<_>>(), vec![11]);
 
-        // Take the snakes' next actions
-        let mut actions = vec![];
-        for snake in self.snakes.iter_mut().filter(|snake| snake.state.alive) {
-            actions.push(snake.get_next_action(&self.board));
+        for &pos in &[-1, 12] {
+            assert_eq!(
+                board.set_tile_at_pos(pos, Cell::Obstacle),
+                Err(BoardError::OutOfBounds {
+                    pos,
+                    width: 4,
+                    height: 3
+                })
+            );
         }
-
-        // Move the snakes
-        for (ref mut snake, ref action) in self
-            .snakes
-            .iter_mut()
-            .filter(|snake| snake.state.alive)
-            .zip(actions)
-        {
-            if let Some(action) = action {
-                snake.execute_action(&mut self.board, action);
-            }
+        // Just outside of the board, not in the next or the previous row
+        for &(x, y) in &[(-1, 1), (4, 1), (0, 3)] {
+            let coord = Coordinate { x, y };
+            assert_eq!(
+                board.set_tile_at_coord(&coord, Cell::Obstacle),
+                Err(BoardError::OffBoard {
+                    coord: coord.clone(),
+                    width: 4,
+                    height: 3
+                })
+            );
         }
+        assert_eq!(board.count_cells(|cell| cell == Cell::Obstacle), 0);
+    }
 
-        // Check head collisions
-        for snake in self.snakes.iter_mut().filter(|snake| snake.state.alive) {
-            if let Some(head) = snake.state.positions.front() {
-                if let Cell::SnakeHead(id) = self.board.get_tile_at_pos(*head) {
-                    if id != snake.state.id {
-                        snake.just_died = true;
-                    }
-                }
-            }
-        }
+    #[test]
+    fn snakes_are_placed_by_elimination_order() {
+        let mut game = Game::with_seed(0);
+        game.with_obstacles(0, 0, 0)
+            .with_start_positions(vec![
+                (0, Coordinate { x: 1, y: 4 }, Orientation::West),
+                (1, Coordinate { x: 6, y: 8 }, Orientation::West),
+                (2, Coordinate { x: 1, y: 12 }, Orientation::West),
+            ])
+            .add_snake(0, Box::new(FrontBot))
+            .add_snake(1, Box::new(FrontBot))
+            .add_snake(2, Box::new(FrontBot))
+            .initialize();
+        let results = game.run_to_end();
 
-        // Remove the dead snakes from the board
-        let dead_snakes_id = self
-            .snakes
-            .iter_mut()
-            .filter(|snake| snake.just_died)
-            .map(|snake| {
-                snake.just_died = false;
-                snake.state.alive = false;
-                snake.state.id
-            })
-            .collect();
-        self.board.remove_dead_snakes(dead_snakes_id, &self.snakes);
+        assert_eq!(results.placements, vec![(1, 1), (0, 2), (2, 2)]);
+        assert_eq!(results.snakes[0].died_at_step, Some(1));
+        assert_eq!(results.snakes[1].died_at_step, None);
+    }
 
-        // Count the live snakes
-        let nb_alive = self.snakes.iter().filter(|snake| snake.state.alive).count();
+    #[test]
+    fn searching_doesnt_change_the_game() {
+        let mut game = Game::with_seed(0);
+        game.with_start_positions(vec![
+            (0, Coordinate { x: 1, y: 8 }, Orientation::West),
+            (1, Coordinate { x: 12, y: 8 }, Orientation::West),
+        ])
+        .add_snake(0, Box::new(FrontBot))
+        .add_snake(1, Box::new(FrontBot))
+        .initialize();
 
-        // Verify if win/loose/draw
-        if self.results.is_none() {
-            // Draw/end: all die
-            if prev_nb_alive > 0 && nb_alive == 0 {
-                self.results = Some(GameResults {
-                    winner: if self.snakes.len() > 1 {
-                        Some(GameResultWinner::Draw)
-                    } else {
-                        None // solo, no winner
-                    },
-                    steps: self.step + 1,
-                });
-            }
-            // Winner: last alive, >1 snake total
-            if prev_nb_alive > 0 && nb_alive == 1 && self.snakes.len() > 1 {
-                let winner_id: SnakeId = self
-                    .snakes
-                    .iter()
-                    .filter(|snake| snake.state.alive)
-                    .map(|snake| snake.state.id)
-                    .next()
-                    .expect("Logic error: nb_alive == 1 but none found in self.snakes.");
-                self.results = Some(GameResults {
-                    winner: Some(GameResultWinner::Winner(winner_id)),
-                    steps: self.step + 1,
-                });
-            }
+        let mut state = game.clone_for_search();
+        state.step_with(&[Action::Front, Action::Right]);
+        let head = state
+            .snakes()
+            .next()
+            .unwrap()
+            .get_head_coord(state.board().size());
+        assert_eq!(head, Coordinate { x: 0, y: 8 });
+        state.step_with(&[Action::Front, Action::Front]);
+        assert_eq!(state.snakes().filter(|snake| snake.alive).count(), 1);
+        assert_eq!(state.board().nb_alive_snakes, 1);
+
+        // The game is untouched, and continues with its bots
+        let head = game.state.snakes[0]
+            .state
+            .get_head_coord(game.state.board.size());
+        assert_eq!(head, Coordinate { x: 1, y: 8 });
+        game.step();
+        assert!(game.state.snakes.iter().all(|snake| snake.state.alive));
+    }
+
+    #[test]
+    fn states_are_rebuilt_from_the_boards() {
+        let map = ["........"; 6].join("\n");
+        let mut game = Game::with_seed(0);
+        game.with_board(GameBoard::from_ascii(&map).unwrap())
+            .without_food()
+            .with_start_positions(vec![
+                (0, Coordinate { x: 1, y: 1 }, Orientation::East),
+                (1, Coordinate { x: 6, y: 1 }, Orientation::North),
+                (2, Coordinate { x: 4, y: 4 }, Orientation::West),
+            ]);
+        for (id, initial_length) in [(0, 2), (1, 1), (2, 3)] {
+            game.add_snake_with_config(
+                id,
+                Box::new(FrontBot),
+                SnakeConfig {
+                    initial_length,
+                    ..SnakeConfig::default()
+                },
+            );
         }
+        game.initialize();
 
-        // Update the board
-        self.board.update();
+        let mut rebuilt = GameState::from_board(game.state.board());
+        let mut cloned = game.clone_for_search();
+        let states = |state: &GameState| {
+            state
+                .snakes()
+                .map(|snake| {
+                    (
+                        snake.id,
+                        snake.positions.clone(),
+                        snake.current_orientation.clone(),
+                    )
+                })
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(states(&rebuilt), states(&cloned));
 
-        // After-step callbacks
-        for after_each_step in &self.after_each_step {
-            after_each_step(&self.board);
+        // The rebuilt state plays like the game
+        for _ in 0..3 {
+            let actions = vec![Action::Right; cloned.snakes().filter(|snake| snake.alive).count()];
+            rebuilt.step_with(&actions);
+            cloned.step_with(&actions);
+            assert_eq!(states(&rebuilt), states(&cloned));
         }
+    }
 
-        self.step += 1;
-        self
+    #[test]
+    fn solo_games_end_with_solo_game_over() {
+        let results = Game::with_seed(0)
+            .add_snake(0, Box::new(FrontBot))
+            .initialize()
+            .run_to_end();
+        assert!(matches!(
+            results.winner,
+            Some(GameResultWinner::SoloGameOver)
+        ));
+        assert!(!results.interrupted);
     }
 
-    pub fn run_to_end(&mut self) -> GameResults {
-        while self.results.is_none()
-            || (!self.lazy_simulation
-                && self.snakes.iter().filter(|snake| snake.state.alive).count() > 0)
-        {
-            self.step();
-        }
-        self.results
-            .clone()
-            .expect("Logic error, no result in run_to_end().")
+    #[test]
+    fn simultaneous_deaths_are_a_true_draw_by_default() {
+        let results = head_on_collision(DrawPolicy::default(), 4);
+        assert_eq!(results.steps, 2);
+        assert!(matches!(results.winner, Some(GameResultWinner::Draw)));
     }
 
-    #[allow(dead_code)]
-    pub fn is_game_over(&self) -> bool {
-        self.results.is_some()
+    #[test]
+    fn longest_snake_wins_simultaneous_deaths() {
+        let results = head_on_collision(DrawPolicy::LongestWins, 4);
+        assert!(matches!(results.winner, Some(GameResultWinner::Winner(1))));
+        assert_eq!(results.snakes[0].length, 2);
+        assert_eq!(results.snakes[1].length, 4);
+
+        // Same length
+        let results = head_on_collision(DrawPolicy::LongestWins, 2);
+        assert!(matches!(results.winner, Some(GameResultWinner::Draw)));
     }
 
-    #[allow(dead_code)]
-    pub fn get_results(&self) -> Option<GameResults> {
-        self.results.clone()
+    #[test]
+    fn most_food_wins_simultaneous_deaths() {
+        let results = head_on_collision(DrawPolicy::MostFoodWins, 4);
+        assert!(matches!(results.winner, Some(GameResultWinner::Winner(0))));
+        assert_eq!(results.snakes[0].food_eaten, 1);
+        assert_eq!(results.snakes[1].food_eaten, 0);
     }
-}
 
-pub const BOARD_WIDTH: i32 = 32;
-pub const BOARD_HEIGHT: i32 = 16;
+    #[test]
+    fn snakes_entering_the_same_cell_all_die() {
+        // The three snakes go to the empty cell (9, 8) during the first step
+        let mut game = Game::with_seed(0);
+        game.with_obstacles(0, 0, 0)
+            .without_food()
+            .with_start_positions(vec![
+                (0, Coordinate { x: 8, y: 8 }, Orientation::East),
+                (1, Coordinate { x: 10, y: 8 }, Orientation::West),
+                (2, Coordinate { x: 9, y: 9 }, Orientation::North),
+            ])
+            .add_snake(0, Box::new(FrontBot))
+            .add_snake(1, Box::new(FrontBot))
+            .add_snake(2, Box::new(FrontBot))
+            .initialize();
+        let results = game.run_to_end();
 
-/// Represents the game board.
-///
-/// `cells` is a 1D representation of the 2D board, where rows are
-/// "concatenated" on one single row, so `(x, y)` is the `(x + y * width)`-th
-/// value.
-pub struct GameBoard {
-    /// The number of non-OBSTACLE cells.
-    pub nb_free_cells: i32,
-    pub nb_alive_snakes: usize,
-    cells: [Cell; (BOARD_WIDTH * BOARD_HEIGHT) as usize],
+        assert_eq!(results.steps, 1);
+        assert!(matches!(results.winner, Some(GameResultWinner::Draw)));
+        assert!(results
+            .snakes
+            .iter()
+            .all(|snake| matches!(snake.death_cause, Some(DeathCause::HeadOn(_)))));
+        check_invariants(&game);
+    }
 
-    rng: ThreadRng,
-    food_add_probability: f32,
-}
+    #[test]
+    fn snakes_entering_the_same_food_both_eat_it() {
+        // Both snakes reach the food at (9, 8) during the first step
+        let mut game = Game::with_seed(0);
+        game.with_obstacles(0, 0, 0)
+            .with_start_positions(vec![
+                (0, Coordinate { x: 8, y: 8 }, Orientation::East),
+                (1, Coordinate { x: 10, y: 8 }, Orientation::West),
+            ])
+            .with_food_schedule(vec![(0, Coordinate { x: 9, y: 8 })])
+            .with_draw_policy(DrawPolicy::MostFoodWins)
+            .add_snake(0, Box::new(FrontBot))
+            .add_snake(1, Box::new(FrontBot))
+            .initialize();
+        let results = game.run_to_end();
 
-impl GameBoard {
-    fn new() -> Self {
-        GameBoard {
-            nb_free_cells: BOARD_WIDTH * BOARD_HEIGHT,
-            nb_alive_snakes: 0,
-            cells: [Cell::Empty; (BOARD_WIDTH * BOARD_HEIGHT) as usize],
+        assert_eq!(results.steps, 1);
+        assert!(matches!(results.winner, Some(GameResultWinner::Draw)));
+        for (snake, enemy) in results.snakes.iter().zip(&[1, 0]) {
+            assert_eq!(snake.food_eaten, 1);
+            assert_eq!(snake.length, 2);
+            assert_eq!(snake.death_cause, Some(DeathCause::HeadOn(*enemy)));
+        }
+        check_invariants(&game);
+        assert_eq!(game.state.board.food_positions().count(), 0);
+    }
 
-            rng: thread_rng(),
-            food_add_probability: 0.1,
+    /// Runs a game on `map` without food, with snakes of the given length
+    /// starting at the given positions, and returns the outcome of the
+    /// snake 0.
+    fn first_snake_result(
+        map: &str,
+        snakes: Vec<(Coordinate, Orientation, usize, Box<dyn SnakeBot>)>,
+    ) -> SnakeResult {
+        let mut game = Game::with_seed(0);
+        game.with_board(GameBoard::from_ascii(map).unwrap())
+            .without_food()
+            .with_growth_mode(GrowthMode::FoodOnly)
+            .max_steps(100);
+        let mut start_positions = vec![];
+        for (id, (start, orientation, initial_length, bot)) in snakes.into_iter().enumerate() {
+            start_positions.push((id as SnakeId, start, orientation));
+            game.add_snake_with_config(
+                id as SnakeId,
+                bot,
+                SnakeConfig {
+                    initial_length,
+                    ..SnakeConfig::default()
+                },
+            );
         }
+        let results = game
+            .with_start_positions(start_positions)
+            .initialize()
+            .run_to_end();
+        results.snakes[0].clone()
     }
 
-    fn add_random_obstacles(&mut self, nb_obstacles: u32, max_size_obstacle: u32) {
-        let mut rng = thread_rng();
+    #[test]
+    fn death_causes_are_classified() {
+        let empty_map = ["....."; 8].join("\n");
+        let result = first_snake_result(
+            &empty_map,
+            vec![(
+                Coordinate { x: 2, y: 1 },
+                Orientation::North,
+                1,
+                Box::new(FrontBot),
+            )],
+        );
+        assert_eq!(result.death_cause, Some(DeathCause::Wall));
+        assert_eq!(result.died_at_step, Some(1));
 
-        for _ in 0..nb_obstacles {
-            let w: i32 = rng.gen_range(0, max_size_obstacle as i32) + 1;
-            let x: i32 = rng.gen_range(0, BOARD_WIDTH - w);
-            let y: i32 = rng.gen_range(0, BOARD_HEIGHT - w);
+        let result = first_snake_result(
+            "...#.",
+            vec![(
+                Coordinate { x: 0, y: 0 },
+                Orientation::East,
+                1,
+                Box::new(FrontBot),
+            )],
+        );
+        assert_eq!(result.death_cause, Some(DeathCause::Obstacle));
+        assert_eq!(result.died_at_step, Some(2));
 
-            for i in 0..w {
-                for j in 0..w {
-                    let coord = Coordinate { x: x + i, y: y + j };
-                    self.cells[coord.to_pos() as usize] = Cell::Obstacle;
-                    self.nb_free_cells -= 1;
-                }
-            }
-        }
+        // Too long to chase its tail
+        let result = first_snake_result(
+            &empty_map,
+            vec![(
+                Coordinate { x: 2, y: 2 },
+                Orientation::North,
+                5,
+                Box::new(TurnRightBot),
+            )],
+        );
+        assert_eq!(result.death_cause, Some(DeathCause::OwnBody));
+        assert_eq!(result.length, 5);
+
+        // The snake 1 crosses the way of the snake 0 just before it
+        let result = first_snake_result(
+            &empty_map,
+            vec![
+                (
+                    Coordinate { x: 3, y: 4 },
+                    Orientation::North,
+                    1,
+                    Box::new(FrontBot),
+                ),
+                (
+                    Coordinate { x: 2, y: 2 },
+                    Orientation::East,
+                    3,
+                    Box::new(FrontBot),
+                ),
+            ],
+        );
+        assert_eq!(result.death_cause, Some(DeathCause::SnakeBody(1)));
+        assert_eq!(result.died_at_step, Some(1));
     }
 
-    fn update(&mut self) {
-        self.nb_free_cells = 0;
-        for i in 0..(BOARD_WIDTH * BOARD_HEIGHT) as usize {
-            match self.cells[i] {
-                Cell::Empty | Cell::Food => self.nb_free_cells += 1,
-                _ => {}
-            }
-        }
+    #[test]
+    fn results_show_the_outcome_of_each_snake() {
+        let summary = head_on_collision(DrawPolicy::MostFoodWins, 4).to_string();
+        assert!(
+            summary.contains("Player 0: length 2, 1 food eaten, collided head-on with snake 1.")
+        );
+        assert!(
+            summary.contains("Player 1: length 4, 0 food eaten, collided head-on with snake 0.")
+        );
+    }
 
-        self.update_food();
+    #[test]
+    fn restore_undoes_the_changes_since_the_snapshot() {
+        let mut board = GameBoard::new(StdRng::seed_from_u64(0));
+        board
+            .set_tile_at_coord(&Coordinate { x: 1, y: 1 }, Cell::Food)
+            .unwrap();
+        board
+            .set_tile_at_coord(&Coordinate { x: 2, y: 2 }, Cell::SnakeHead(0))
+            .unwrap();
+        let snapshot = board.snapshot();
+
+        board
+            .set_tile_at_coord(&Coordinate { x: 1, y: 1 }, Cell::SnakeHead(0))
+            .unwrap();
+        board
+            .set_tile_at_coord(&Coordinate { x: 2, y: 2 }, Cell::SnakeBody(0))
+            .unwrap();
+        board
+            .set_tile_at_coord(&Coordinate { x: 3, y: 3 }, Cell::Food)
+            .unwrap();
+        board.restore(&snapshot);
+
+        assert_eq!(
+            board.get_tile_at_coord(&Coordinate { x: 1, y: 1 }),
+            Cell::Food
+        );
+        assert_eq!(
+            board.get_tile_at_coord(&Coordinate { x: 2, y: 2 }),
+            Cell::SnakeHead(0)
+        );
+        assert_eq!(
+            board.get_tile_at_coord(&Coordinate { x: 3, y: 3 }),
+            Cell::Empty
+        );
+        assert_eq!(
+            board.food_positions().collect::<Vec<_>>(),
+            vec![Coordinate { x: 1, y: 1 }.to_pos(board.size())]
+        );
     }
 
-    fn update_food(&mut self) {
-        let p = self.rng.gen_range(0., 1.);
-        if p < self.food_add_probability {
-            let x = self.rng.gen_range(0, BOARD_WIDTH);
-            let y = self.rng.gen_range(0, BOARD_HEIGHT);
-            let coord = Coordinate { x, y };
-            let pos = coord.to_pos();
-            if self.is_pos_free_or_food(pos) {
-                self.set_tile_at_pos(pos, Cell::Food);
-            }
-        }
+    #[cfg(feature = "serde")]
+    #[test]
+    fn snapshots_can_be_saved() {
+        let board = GameBoard::from_ascii("#.o\n.0~").unwrap();
+        let json = serde_json::to_string(&board.snapshot()).unwrap();
+        let snapshot: BoardSnapshot = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(snapshot.size(), BoardSize::new(3, 2));
+        assert_eq!(snapshot.nb_alive_snakes(), 1);
+        let loaded = GameBoard::from_snapshot(StdRng::seed_from_u64(0), &snapshot);
+        assert_eq!(loaded.to_ascii(), board.to_ascii());
     }
 
-    fn remove_dead_snakes(&mut self, dead_snake_ids: Vec<SnakeId>, snakes: &[Snake]) {
-        if !dead_snake_ids.is_empty() {
-            // Remove the dead
-            for snake in snakes.iter() {
-                if dead_snake_ids.contains(&snake.state.id) {
-                    for position in snake.state.positions.iter().cloned() {
-                        self.set_tile_at_pos(position, Cell::Empty);
-                    }
-                }
-            }
-            // Show the alive
-            for snake in snakes.iter() {
-                let id = snake.state.id;
-                if snake.state.alive {
-                    for position in snake.state.positions.iter().cloned() {
-                        self.set_tile_at_pos(position, Cell::SnakeBody(id));
-                    }
-                    if let Some(head_pos) = snake.state.positions.front() {
-                        self.set_tile_at_pos(*head_pos, Cell::SnakeHead(id));
-                    }
-                    if let Some(tail_pos) = snake.state.positions.back() {
-                        self.set_tile_at_pos(*tail_pos, Cell::SnakeTail(id));
-                    }
-                }
-            }
-        }
+    #[test]
+    fn no_food_appears_without_food() {
+        use crate::random_bot::RandomBot;
+
+        let results = Game::with_seed(0)
+            .without_food()
+            .continue_simulation_if_known_winner(true)
+            .add_snake(0, Box::new(RandomBot::new()))
+            .add_snake(1, Box::new(RandomBot::new()))
+            .initialize()
+            .after_each_step(|board| assert_eq!(board.food_positions().count(), 0))
+            .run_to_end();
+        assert!(results.steps > 0);
     }
 
-    pub fn get_tile_at_coord(&self, coord: &Coordinate) -> Cell {
-        if coord.is_out_of_bounds() {
-            return Cell::Wall;
+    #[test]
+    fn scheduled_food_appears_at_its_step() {
+        use crate::random_bot::RandomBot;
+        use std::{cell::RefCell, rc::Rc};
+
+        let food = Coordinate { x: 0, y: 0 };
+        let food_seen = Rc::new(RefCell::new(vec![]));
+        let food_seen_inner = food_seen.clone();
+        let mut game = Game::with_seed(0);
+        game.with_start_positions(vec![(0, Coordinate { x: 16, y: 8 }, Orientation::North)])
+            .with_food_schedule(vec![(3, food.clone())])
+            .add_snake(0, Box::new(RandomBot::new()))
+            .after_each_step(move |board| {
+                let has_food = board.get_tile_at_coord(&food) == Cell::Food;
+                food_seen_inner.borrow_mut().push(has_food);
+            })
+            .initialize();
+        for _ in 0..5 {
+            game.step();
         }
-        self.get_tile_at_pos(coord.to_pos())
+
+        assert_eq!(*food_seen.borrow(), vec![false, false, false, true, true]);
+        assert_eq!(game.state.board.food_positions().count(), 1);
     }
 
-    pub fn get_tile_at_pos(&self, pos: Position) -> Cell {
-        // assert!(pos >= 0 && *pos < BOARD_WIDTH * BOARD_HEIGHT);
-        self.cells[pos as usize]
+    #[test]
+    fn bot_turns_towards_scheduled_food() {
+        use crate::heuristic_bot::HeuristicBot;
+        use std::{cell::RefCell, rc::Rc};
+
+        // Food appears two cells to the left of the snake on the first step
+        let actions = Rc::new(RefCell::new(vec![]));
+        let actions_inner = actions.clone();
+        let mut game = Game::with_seed(0);
+        game.with_start_positions(vec![(0, Coordinate { x: 16, y: 8 }, Orientation::North)])
+            .with_food_schedule(vec![(0, Coordinate { x: 14, y: 8 })])
+            .add_snake(0, Box::new(HeuristicBot::default()))
+            .on_event(move |event| {
+                if let GameEvent::SnakeMoved { action, .. } = event {
+                    actions_inner.borrow_mut().push(action.clone());
+                }
+            })
+            .initialize()
+            .step();
+
+        assert_eq!(*actions.borrow(), vec![Action::Left]);
     }
 
-    #[allow(dead_code)]
-    pub fn set_tile_at_coord(&mut self, coord: &Coordinate, cell: Cell) {
-        self.set_tile_at_pos(coord.to_pos(), cell)
+    #[test]
+    fn food_and_death_callbacks_come_before_the_step_callbacks() {
+        use std::{cell::RefCell, rc::Rc};
+
+        // The snake eats the food in front of it, and then hits the wall
+        let log = Rc::new(RefCell::new(vec![]));
+        let (food_log, death_log, step_log) = (log.clone(), log.clone(), log.clone());
+        let mut game = Game::with_seed(0);
+        game.with_board(GameBoard::from_ascii(".o.").unwrap())
+            .with_start_positions(vec![(0, Coordinate { x: 0, y: 0 }, Orientation::East)])
+            .add_snake(0, Box::new(FrontBot))
+            .on_food_eaten(move |id, coord| {
+                food_log
+                    .borrow_mut()
+                    .push(format!("{} ate at ({}, {})", id, coord.x, coord.y))
+            })
+            .on_snake_death(move |id, cause| {
+                death_log.borrow_mut().push(format!("{} {}", id, cause))
+            })
+            .after_each_step(move |_| step_log.borrow_mut().push("step".to_string()))
+            .initialize()
+            .run_to_end();
+
+        assert_eq!(
+            *log.borrow(),
+            vec!["0 ate at (1, 0)", "step", "step", "0 hit a wall", "step"]
+        );
     }
 
-    pub fn set_tile_at_pos(&mut self, pos: Position, cell: Cell) {
-        if pos >= 0 && pos < BOARD_WIDTH * BOARD_HEIGHT {
-            self.cells[pos as usize] = cell;
-        } else {
-            panic!(format!(
-                "Position {} out-of-bounds: W={} H={} W*H={}",
-                pos,
-                BOARD_WIDTH,
-                BOARD_HEIGHT,
-                BOARD_WIDTH * BOARD_HEIGHT
-            ));
-        }
+    #[test]
+    fn region_size_stops_at_the_walls() {
+        // A 3-cell pocket in the top-left corner, closed by obstacles
+        let mut board = GameBoard::new(StdRng::seed_from_u64(0));
+        board
+            .set_tile_at_coord(&Coordinate { x: 0, y: 1 }, Cell::Obstacle)
+            .unwrap();
+        board
+            .set_tile_at_coord(&Coordinate { x: 1, y: 1 }, Cell::Obstacle)
+            .unwrap();
+        board
+            .set_tile_at_coord(&Coordinate { x: 2, y: 1 }, Cell::Obstacle)
+            .unwrap();
+        board
+            .set_tile_at_coord(&Coordinate { x: 3, y: 0 }, Cell::Obstacle)
+            .unwrap();
+        let head = Coordinate { x: 0, y: 0 };
+        board.set_tile_at_coord(&head, Cell::SnakeHead(0)).unwrap();
+
+        assert_eq!(board.flood_fill(&head, 100), (2, false));
+        assert_eq!(board.flood_fill(&head, 1), (1, false));
+        assert_eq!(board.accessible_region_size(&head), 2);
+        assert_eq!(board.accessible_region_size(&Coordinate { x: 1, y: 0 }), 2);
+        assert_eq!(
+            board.accessible_region_size(&Coordinate { x: 10, y: 10 }),
+            board.size().nb_cells() - 7
+        );
+
+        board
+            .set_tile_at_coord(&Coordinate { x: 2, y: 0 }, Cell::Food)
+            .unwrap();
+        assert!(board.flood_fill(&head, 100).1);
+
+        // The outside is big
+        let outside = Coordinate { x: 10, y: 10 };
+        assert_eq!(board.flood_fill(&outside, 100), (100, false));
     }
 
-    pub fn is_suicide_moves(
-        &self,
-        from: &Coordinate,
-        orientation: &Orientation,
-        action: &Action,
-    ) -> bool {
-        let next_orientation = next_orientation(&orientation, action);
-        let next_coord = next_coord_towards(&from, &next_orientation);
-        if next_coord.is_none() {
-            return true;
-        }
-        let next_coord = next_coord.unwrap();
-        assert!(!next_coord.is_out_of_bounds()); // TODO: Remove -> useless
+    #[test]
+    fn games_end_when_the_snakes_are_trapped() {
+        // Each snake of length 3 goes into its own dead end, the snake 1 into
+        // the deeper one
+        let trapped_game = |nb_snakes: SnakeId, end_on_entrapment: bool| {
+            let mut game = Game::with_seed(0);
+            game.with_board(
+                GameBoard::from_ascii(
+                    "##########\n\
+                     ....#.....\n\
+                     ##########",
+                )
+                .unwrap(),
+            )
+            .without_food()
+            .with_growth_mode(GrowthMode::FoodOnly)
+            .end_on_entrapment(end_on_entrapment)
+            .with_start_positions(vec![
+                (0, Coordinate { x: 2, y: 1 }, Orientation::East),
+                (1, Coordinate { x: 7, y: 1 }, Orientation::West),
+            ]);
+            for id in 0..nb_snakes {
+                game.add_snake_with_config(
+                    id,
+                    Box::new(FrontBot),
+                    SnakeConfig {
+                        initial_length: 3,
+                        ..SnakeConfig::default()
+                    },
+                );
+            }
+            game.initialize().run_to_end()
+        };
 
-        !self.is_coord_free_or_food(&next_coord)
+        // Decided after the first step, before anyone dies
+        let results = trapped_game(2, true);
+        assert_eq!(results.steps, 1);
+        assert!(matches!(results.winner, Some(GameResultWinner::Winner(1))));
+        assert!(results
+            .snakes
+            .iter()
+            .all(|snake| snake.death_cause.is_none()));
+
+        let results = trapped_game(1, true);
+        assert_eq!(results.steps, 1);
+        assert!(matches!(
+            results.winner,
+            Some(GameResultWinner::SoloGameOver)
+        ));
+        assert_eq!(results.snakes[0].death_cause, None);
+
+        // Otherwise, the snakes run into the walls
+        let results = trapped_game(2, false);
+        assert_eq!(results.steps, 2);
+        assert!(matches!(results.winner, Some(GameResultWinner::Winner(1))));
+        assert_eq!(results.snakes[0].death_cause, Some(DeathCause::Obstacle));
+        assert_eq!(results.snakes[0].died_at_step, Some(1));
     }
 
-    #[inline]
-    #[allow(dead_code)]
-    pub fn is_pos_free_or_food(&self, pos: Position) -> bool {
-        match self.get_tile_at_pos(pos) {
-            Cell::Empty | Cell::Food => true,
-            _ => false,
+    #[test]
+    fn next_orientation_turns_relative_to_the_snake() {
+        use Action::*;
+        use Orientation::*;
+
+        let expected = [
+            (North, [West, North, East]),
+            (East, [North, East, South]),
+            (South, [East, South, West]),
+            (West, [South, West, North]),
+        ];
+        for (current, [left, front, right]) in expected.iter() {
+            assert_eq!(next_orientation(current, &Left), *left);
+            assert_eq!(next_orientation(current, &Front), *front);
+            assert_eq!(next_orientation(current, &Right), *right);
         }
     }
 
-    #[inline]
-    pub fn is_coord_free_or_food(&self, coord: &Coordinate) -> bool {
-        match self.get_tile_at_coord(coord) {
-            Cell::Empty | Cell::Food => true,
-            _ => false,
+    #[test]
+    fn next_orientation_goes_back_after_four_turns() {
+        for orientation in Orientation::ALL.iter() {
+            for action in [Action::Left, Action::Right].iter() {
+                let mut current = orientation.clone();
+                for _ in 0..4 {
+                    current = next_orientation(&current, action);
+                }
+                assert_eq!(current, *orientation);
+            }
+            assert_eq!(
+                next_orientation(
+                    &next_orientation(orientation, &Action::Left),
+                    &Action::Right
+                ),
+                *orientation
+            );
         }
     }
 
-    pub fn get_non_suicide_moves(
-        &self,
-        from: &Coordinate,
-        orientation: &Orientation,
-    ) -> Vec<Action> {
-        [Action::Left, Action::Front, Action::Right]
-            .iter()
-            .filter_map(|action| {
-                if self.is_suicide_moves(from, orientation, action) {
-                    None
-                } else {
-                    Some(action.clone())
-                }
-            })
-            .collect()
+    #[test]
+    fn orientations_have_an_opposite_and_a_delta() {
+        for orientation in Orientation::ALL.iter() {
+            assert_ne!(orientation.opposite(), *orientation);
+            assert_eq!(orientation.opposite().opposite(), *orientation);
+            assert_eq!(
+                orientation.turn(&Action::Right).turn(&Action::Right),
+                orientation.opposite()
+            );
+
+            let (dx, dy) = orientation.delta();
+            let (opposite_dx, opposite_dy) = orientation.opposite().delta();
+            assert_eq!(dx.abs() + dy.abs(), 1);
+            assert_eq!((dx + opposite_dx, dy + opposite_dy), (0, 0));
+        }
+        assert_eq!(Orientation::North.delta(), (0, -1));
+        assert_eq!(Orientation::West.delta(), (-1, 0));
     }
 
-    #[allow(dead_code)]
-    pub fn print(&self) {
-        print!("+");
-        for _ in 0..BOARD_WIDTH {
-            print!("-");
+    #[test]
+    fn next_coord_towards_moves_by_one() {
+        let from = Coordinate { x: 5, y: 5 };
+        let expected = [
+            (Orientation::North, Coordinate { x: 5, y: 4 }),
+            (Orientation::East, Coordinate { x: 6, y: 5 }),
+            (Orientation::South, Coordinate { x: 5, y: 6 }),
+            (Orientation::West, Coordinate { x: 4, y: 5 }),
+        ];
+        for (orientation, coord) in expected.iter() {
+            let next = next_coord_towards(&from, orientation, BoardSize::default(), WrapMode::None);
+            assert_eq!(next, Some(coord.clone()));
         }
-        println!("+");
+    }
 
-        let mut i = 0;
-        for _ in 0..BOARD_HEIGHT {
-            print!("|");
-            for _ in 0..BOARD_WIDTH {
-                print!("{}", self.cells[i]);
-                i += 1;
-            }
-            println!("|");
+    #[test]
+    fn next_coord_towards_stops_at_the_borders() {
+        let size = BoardSize::default();
+        let (max_x, max_y) = (size.width - 1, size.height - 1);
+
+        for x in 0..size.width {
+            let top = Coordinate { x, y: 0 };
+            let bottom = Coordinate { x, y: max_y };
+            assert_eq!(
+                next_coord_towards(&top, &Orientation::North, size, WrapMode::None),
+                None
+            );
+            assert_eq!(
+                next_coord_towards(&bottom, &Orientation::South, size, WrapMode::None),
+                None
+            );
+            assert_eq!(
+                next_coord_towards(&top, &Orientation::South, size, WrapMode::None),
+                Some(Coordinate { x, y: 1 })
+            );
+            assert_eq!(
+                next_coord_towards(&bottom, &Orientation::North, size, WrapMode::None),
+                Some(Coordinate { x, y: max_y - 1 })
+            );
         }
+        for y in 0..size.height {
+            let left = Coordinate { x: 0, y };
+            let right = Coordinate { x: max_x, y };
+            assert_eq!(
+                next_coord_towards(&left, &Orientation::West, size, WrapMode::None),
+                None
+            );
+            assert_eq!(
+                next_coord_towards(&right, &Orientation::East, size, WrapMode::None),
+                None
+            );
+            assert_eq!(
+                next_coord_towards(&left, &Orientation::East, size, WrapMode::None),
+                Some(Coordinate { x: 1, y })
+            );
+            assert_eq!(
+                next_coord_towards(&right, &Orientation::West, size, WrapMode::None),
+                Some(Coordinate { x: max_x - 1, y })
+            );
+        }
+    }
 
-        print!("+");
-        for _ in 0..BOARD_WIDTH {
-            print!("-");
+    #[test]
+    fn next_coord_towards_in_the_corners() {
+        let size = BoardSize::default();
+        let (max_x, max_y) = (size.width - 1, size.height - 1);
+        // Each corner with the two orientations leading outside of the board
+        let corners = [
+            ((0, 0), [Orientation::North, Orientation::West]),
+            ((max_x, 0), [Orientation::North, Orientation::East]),
+            ((0, max_y), [Orientation::South, Orientation::West]),
+            ((max_x, max_y), [Orientation::South, Orientation::East]),
+        ];
+        for ((x, y), blocked) in corners.iter() {
+            let corner = Coordinate { x: *x, y: *y };
+            for orientation in Orientation::ALL.iter() {
+                let next = next_coord_towards(&corner, orientation, size, WrapMode::None);
+                if blocked.contains(orientation) {
+                    assert_eq!(next, None, "{:?} from {:?}", orientation, corner);
+                } else {
+                    let next = next.unwrap();
+                    assert!(!next.is_out_of_bounds(size));
+                    assert_eq!(next.manhattan_distance(&corner), 1);
+                }
+            }
         }
-        println!("+");
     }
 }
@@ -0,0 +1,255 @@
+//! A fixed suite of hand-built scenarios to evaluate a bot. Unlike the win
+//! ratio of random matches, the outcome of each scenario is reproducible and
+//! tells what the bot is good or bad at.
+
+use std::{cell::Cell as StdCell, fmt, rc::Rc};
+
+use colored::Colorize;
+
+use crate::game_engine::*;
+
+/// What a bot must achieve in a `Scenario`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Goal {
+    /// Stay alive during the given number of steps.
+    Survive(u32),
+    /// Eat food within the given number of steps.
+    ReachFood(u32),
+}
+
+/// A solo game on a hand-built board, see `GameBoard::from_ascii()`. No food
+/// appears during the game, only the food of the map is available.
+#[derive(Clone)]
+pub struct Scenario {
+    pub name: String,
+    board: GameBoard,
+    start: (Coordinate, Orientation),
+    length: usize,
+    pub goal: Goal,
+}
+
+impl Scenario {
+    /// A scenario with a snake of `length` cells, the head at `start`
+    /// facing `orientation`, its body being laid out behind it.
+    pub fn new(
+        name: &str,
+        map: &str,
+        start: Coordinate,
+        orientation: Orientation,
+        length: usize,
+        goal: Goal,
+    ) -> Result<Self, ParseError> {
+        Ok(Scenario {
+            name: name.to_string(),
+            board: GameBoard::from_ascii(map)?,
+            start: (start, orientation),
+            length,
+            goal,
+        })
+    }
+
+    /// Plays the scenario. Returns whether the goal is achieved and after
+    /// how many steps the scenario ended.
+    fn run(&self, create_bot: &BotFactory) -> ScenarioResult {
+        let max_steps = match self.goal {
+            Goal::Survive(steps) | Goal::ReachFood(steps) => steps,
+        };
+        let food_eaten = Rc::new(StdCell::new(false));
+
+        let mut game = Game::with_seed(0);
+        let food_eaten_event = food_eaten.clone();
+        game.with_board(self.board.clone())
+            .without_food()
+            .with_start_positions(vec![(0, self.start.0.clone(), self.start.1.clone())])
+            .add_snake_with_config(
+                0,
+                create_bot(),
+                SnakeConfig {
+                    initial_length: self.length,
+                    ..SnakeConfig::default()
+                },
+            )
+            .on_event(move |event| {
+                if let GameEvent::FoodEaten { .. } = event {
+                    food_eaten_event.set(true);
+                }
+            })
+            .initialize();
+
+        let mut steps = 0;
+        while steps < max_steps && !game.is_game_over() && !food_eaten.get() {
+            game.step();
+            steps += 1;
+        }
+
+        let passed = match self.goal {
+            Goal::Survive(_) => !game.is_game_over(),
+            Goal::ReachFood(_) => food_eaten.get(),
+        };
+        ScenarioResult {
+            name: self.name.clone(),
+            passed,
+            steps,
+        }
+    }
+}
+
+/// The outcome of one `Scenario`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScenarioResult {
+    pub name: String,
+    pub passed: bool,
+    pub steps: u32,
+}
+
+/// The outcome of `Gauntlet::score()`, in the order of the scenarios.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GauntletReport(pub Vec<ScenarioResult>);
+
+impl GauntletReport {
+    pub fn nb_passed(&self) -> usize {
+        self.0.iter().filter(|result| result.passed).count()
+    }
+
+    /// The ratio of passed scenarios, in [0, 1].
+    pub fn score(&self) -> f64 {
+        if self.0.is_empty() {
+            return 0.;
+        }
+        self.nb_passed() as f64 / self.0.len() as f64
+    }
+}
+
+impl fmt::Display for GauntletReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for result in &self.0 {
+            let outcome = if result.passed {
+                "passed".green()
+            } else {
+                "failed".red()
+            };
+            writeln!(
+                f,
+                "  {:<20} {} ({} steps)",
+                result.name, outcome, result.steps
+            )?;
+        }
+        write!(f, "{}/{} scenarios passed", self.nb_passed(), self.0.len())
+    }
+}
+
+/// A suite of `Scenario`s.
+#[derive(Clone, Default)]
+pub struct Gauntlet {
+    scenarios: Vec<Scenario>,
+}
+
+impl Gauntlet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The built-in scenarios: a tight corridor, food in a pit and a spawn
+    /// facing a wall.
+    pub fn standard() -> Self {
+        let mut gauntlet = Gauntlet::new();
+        gauntlet
+            .add_scenario(
+                Scenario::new(
+                    "tight corridor",
+                    "######\n\
+                     #....#\n\
+                     #.##.#\n\
+                     #.##.#\n\
+                     #....#\n\
+                     ######",
+                    Coordinate { x: 1, y: 3 },
+                    Orientation::North,
+                    2,
+                    Goal::Survive(20),
+                )
+                .unwrap(),
+            )
+            .add_scenario(
+                Scenario::new(
+                    "food in a pit",
+                    "..........\n\
+                     ..........\n\
+                     ....#.#...\n\
+                     ....#o#...\n\
+                     ....###...",
+                    Coordinate { x: 1, y: 1 },
+                    Orientation::East,
+                    1,
+                    Goal::ReachFood(20),
+                )
+                .unwrap(),
+            )
+            .add_scenario(
+                Scenario::new(
+                    "near-wall spawn",
+                    "........\n\
+                     ........\n\
+                     ........\n\
+                     ........\n\
+                     ........\n\
+                     ........",
+                    Coordinate { x: 0, y: 0 },
+                    Orientation::North,
+                    1,
+                    Goal::Survive(10),
+                )
+                .unwrap(),
+            );
+        gauntlet
+    }
+
+    pub fn add_scenario(&mut self, scenario: Scenario) -> &mut Self {
+        self.scenarios.push(scenario);
+        self
+    }
+
+    /// Plays each scenario with a new bot.
+    pub fn score(&self, create_bot: &BotFactory) -> GauntletReport {
+        GauntletReport(
+            self.scenarios
+                .iter()
+                .map(|scenario| scenario.run(create_bot))
+                .collect(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::heuristic_bot::{HeuristicBot, GOOD_WEIGHTS};
+
+    /// A bot which always goes forward.
+    struct FrontBot;
+
+    impl SnakeBot for FrontBot {
+        fn get_next_action(&mut self, _: &SnakeState, _: &GameBoard) -> Action {
+            Action::Front
+        }
+    }
+
+    #[test]
+    fn heuristic_bot_passes_the_standard_gauntlet() {
+        let report = Gauntlet::standard().score(&HeuristicBot::factory(&GOOD_WEIGHTS));
+        assert_eq!(report.score(), 1., "\n{}", report);
+    }
+
+    #[test]
+    fn going_forward_fails_near_walls() {
+        let report = Gauntlet::standard()
+            .score(&(Box::new(|| Box::new(FrontBot) as Box<dyn SnakeBot>) as BotFactory));
+        let near_wall = report
+            .0
+            .iter()
+            .find(|result| result.name == "near-wall spawn")
+            .unwrap();
+        assert!(!near_wall.passed);
+        assert_eq!(near_wall.steps, 1);
+    }
+}
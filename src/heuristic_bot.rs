@@ -1,14 +1,21 @@
 use core::fmt;
 use std::{
     cmp::{min, Ordering},
-    collections::VecDeque,
+    collections::{HashMap, VecDeque},
+    fs, io,
+    path::Path,
 };
 
 use crate::game_engine::*;
 use crate::random_bot::get_non_suicide_random_action;
 
 /// The number of stats metrics.
-pub const NB_STATS: usize = 5;
+/// Note: The weights files saved with another number of stats can't be
+/// loaded, see `load_weights()`. The weights learned with fewer stats behave
+/// the same with zero weights for the new ones, which are the last of each
+/// group: e.g. before `dist_to_nearest_wall` and `min_corridor_width`, there
+/// were 15 weights in groups of five.
+pub const NB_STATS: usize = 7;
 
 /// The number of weights needed by the `HeuristicBot`.
 pub const NB_WEIGHTS: usize = NB_STATS * 3;
@@ -33,18 +40,142 @@ impl<'a> fmt::Display for PrettyWeights<'a> {
     }
 }
 
-/// The maximum depth for the BFS => sight distance.
-/// Attention: It's used to normalize `Stats::accessible_area`.
+/// Why some text isn't valid weights, see `parse_weights()`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParseWeightsError {
+    InvalidNumber(String),
+    /// The number of weights found, instead of `NB_WEIGHTS`.
+    WrongCount(usize),
+    /// The weights were saved by a build having another number of weights,
+    /// see `save_weights()`.
+    IncompatibleLength {
+        found: usize,
+        expected: usize,
+    },
+    /// The weights file can't be read.
+    Io(String),
+}
+
+impl fmt::Display for ParseWeightsError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseWeightsError::InvalidNumber(number) => {
+                write!(f, "'{}' isn't a valid weight.", number)
+            }
+            ParseWeightsError::WrongCount(count) => {
+                write!(f, "Got {} weights, but {} are needed.", count, NB_WEIGHTS)
+            }
+            ParseWeightsError::IncompatibleLength { found, expected } => write!(
+                f,
+                "The weights were saved for {} weights, but this version uses {}.",
+                found, expected
+            ),
+            ParseWeightsError::Io(error) => write!(f, "Unable to read the weights: {}", error),
+        }
+    }
+}
+
+/// Reads weights separated by commas and/or whitespaces, optionally between
+/// brackets, like `PrettyWeights` prints them. This allows to copy weights
+/// from the logs.
+///
+/// Note: `Weights` being a `Vec`, it can't implement `FromStr`.
+pub fn parse_weights(s: &str) -> Result<Weights, ParseWeightsError> {
+    let weights = s
+        .trim()
+        .trim_start_matches('[')
+        .trim_end_matches(']')
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|number| !number.is_empty())
+        .map(|number| {
+            number
+                .parse::<Weight>()
+                .map_err(|_| ParseWeightsError::InvalidNumber(number.to_string()))
+        })
+        .collect::<Result<Weights, _>>()?;
+
+    if weights.len() != NB_WEIGHTS {
+        return Err(ParseWeightsError::WrongCount(weights.len()));
+    }
+    Ok(weights)
+}
+
+/// The first line of the weights files, followed by the number of weights,
+/// so that the files of builds with other stats are detected.
+const WEIGHTS_FILE_HEADER: &str = "# genetic-snake-rs weights:";
+
+/// Writes the weights like `PrettyWeights`, after a header with their
+/// number. See `load_weights()`.
+pub fn save_weights(path: &Path, weights: &Weights) -> io::Result<()> {
+    fs::write(
+        path,
+        format!(
+            "{} {}\n{}\n",
+            WEIGHTS_FILE_HEADER,
+            weights.len(),
+            PrettyWeights(weights)
+        ),
+    )
+}
+
+/// Reads weights written by `save_weights()`. The files without header are
+/// read with `parse_weights()`.
+pub fn load_weights(path: &Path) -> Result<Weights, ParseWeightsError> {
+    let content = fs::read_to_string(path).map_err(|e| ParseWeightsError::Io(e.to_string()))?;
+    let weights = match content.trim_start().strip_prefix(WEIGHTS_FILE_HEADER) {
+        Some(content) => {
+            let (count, weights) = content.split_at(content.find('\n').unwrap_or(content.len()));
+            let count = count.trim();
+            let found = count
+                .parse()
+                .map_err(|_| ParseWeightsError::InvalidNumber(count.to_string()))?;
+            if found != NB_WEIGHTS {
+                return Err(ParseWeightsError::IncompatibleLength {
+                    found,
+                    expected: NB_WEIGHTS,
+                });
+            }
+            weights
+        }
+        None => &content,
+    };
+    parse_weights(weights)
+}
+
+/// The default maximum depth for the BFS => sight distance, see
+/// `HeuristicBot::with_depth()`.
+/// Note: `Stats::accessible_area` is normalized by the number of free cells,
+/// so a shorter sight makes it smaller.
 pub const MAX_DEPTH: i32 = 30;
 
+/// The widths of the passages are counted up to this, see
+/// `Stats::min_corridor_width`.
+pub const MAX_CORRIDOR_WIDTH: i32 = 5;
+
+/// The BFS distance needed to cross a `Cell::Mud`, instead of 1.
+pub const MUD_COST: i32 = 2;
+
+/// How much a starving snake is attracted by food, on top of the learned
+/// weights. The term grows with the square of the missing health, so it only
+/// matters when the snake is about to starve.
+/// Note: This isn't a weight of the genome, because it only makes sense in
+/// games having the hunger rule.
+pub const STARVATION_FOOD_WEIGHT: f64 = -5.;
+
+/// The penalty of a move which may end in a head-on collision with a snake
+/// at least as long, see `HeuristicBot::with_head_on_avoidance`. It's large
+/// enough to override the learned weights, but a deadly move can still be
+/// worse.
+pub const HEAD_ON_PENALTY: f64 = -10.;
+
 lazy_static! {
     /// Human-tuned good weights
     pub static ref GOOD_WEIGHTS: Weights = {
         #[cfg_attr(rustfmt, rustfmt_skip)]
         let weights: [f64; NB_WEIGHTS] = [
-            1., 0.8, 0.07, -0.1, -0.9,
-            1., 0.8, 0.07, -0.1, -0.9,
-            1., 0.8, 0.07, -0.1, -0.9,
+            1., 0.8, 0.07, -0.1, -0.9, 0., 0.,
+            1., 0.8, 0.07, -0.1, -0.9, 0., 0.,
+            1., 0.8, 0.07, -0.1, -0.9, 0., 0.,
         ];
         weights.to_vec()
     };
@@ -54,10 +185,24 @@ pub struct HeuristicBot<'a> {
     /// This is a reference to prevent an unneeded copy during
     /// the genetic algorithm learning process.
     weights: &'a [Weight],
+    /// If `true`, the bot prints why it chooses each move.
+    explain: bool,
+    /// If `true`, the enemy heads weights apply to the distance to the
+    /// nearest enemy head instead of the sum of the distances.
+    nearest_threat: bool,
+    /// If `true`, the bot avoids the cells where a longer enemy can move.
+    head_on_avoidance: bool,
+    /// The maximum depth of the BFS, see `MAX_DEPTH`.
+    max_depth: i32,
 }
 
 impl<'a> HeuristicBot<'a> {
     pub fn new(weights: &'a [Weight]) -> Self {
+        Self::with_depth(weights, MAX_DEPTH)
+    }
+
+    /// A bot seeing at most `max_depth` cells away, instead of `MAX_DEPTH`.
+    pub fn with_depth(weights: &'a [Weight], max_depth: i32) -> Self {
         assert_eq!(
             weights.len(),
             NB_WEIGHTS,
@@ -65,7 +210,44 @@ impl<'a> HeuristicBot<'a> {
             weights.len(),
             NB_WEIGHTS
         );
-        HeuristicBot { weights }
+        HeuristicBot {
+            weights,
+            explain: false,
+            nearest_threat: false,
+            head_on_avoidance: false,
+            max_depth,
+        }
+    }
+
+    /// Creates a new `HeuristicBot` with `weights` for each game.
+    pub fn factory(weights: &'a [Weight]) -> BotFactory<'a> {
+        Box::new(move || Box::new(HeuristicBot::new(weights)))
+    }
+
+    /// Prints, for each move, the stats of each action, their weighted
+    /// contributions and the chosen action. Useful to understand a surprising
+    /// move of a learned genome.
+    pub fn with_explain(mut self, explain: bool) -> Self {
+        self.explain = explain;
+        self
+    }
+
+    /// Uses the distance to the nearest enemy head instead of the sum of the
+    /// distances to all of them, so that the bot reacts to the closest threat
+    /// rather than to the crowd. The weights keep the same layout, so a genome
+    /// learned with one mode can be used, though not optimally, in the other.
+    pub fn with_nearest_threat(mut self, nearest_threat: bool) -> Self {
+        self.nearest_threat = nearest_threat;
+        self
+    }
+
+    /// Penalizes the moves to a cell where the head of an enemy at least as
+    /// long can move at the next step, since the resulting head-on collision
+    /// would kill this bot. This isn't learned: it's `HEAD_ON_PENALTY`, added
+    /// on top of the weighted stats.
+    pub fn with_head_on_avoidance(mut self, head_on_avoidance: bool) -> Self {
+        self.head_on_avoidance = head_on_avoidance;
+        self
     }
 }
 
@@ -80,39 +262,84 @@ impl<'a> Default for HeuristicBot<'a> {
 impl<'a> SnakeBot for HeuristicBot<'a> {
     fn get_next_action(&mut self, myself: &SnakeState, board: &GameBoard) -> Action {
         let current_orientation = &myself.current_orientation;
-        let head_pos = *myself.positions.front().unwrap();
-        let head_coord = Coordinate::from_pos(head_pos);
+        let head_coord = match myself.try_get_head_coord(board.size()) {
+            Some(head_coord) => head_coord,
+            None => return Action::Front,
+        };
+
+        // The urgency to eat, in [0, 1]
+        let starvation = match (myself.health, myself.max_health) {
+            (Some(health), Some(max_health)) => 1. - health as f64 / max_health as f64,
+            _ => 0.,
+        };
+
+        // The enemies don't move during the three BFS
+        let enemies = EnemyCells::find(myself.id, board);
+
+        let evaluations: Vec<(Action, Stats, [f64; NB_STATS], f64)> =
+            [Action::Left, Action::Front, Action::Right]
+                .iter()
+                .enumerate()
+                .map(|(i, action)| {
+                    let next_orientation = next_orientation(current_orientation, action);
+                    let next_coord = next_coord_towards(
+                        &head_coord,
+                        &next_orientation,
+                        board.size(),
+                        board.wrap_mode(),
+                    );
+
+                    let stats =
+                        compute_stats_with_enemies(&next_coord, board, &enemies, self.max_depth);
+                    let offset = i * NB_STATS;
+                    let mut contributions = [0.; NB_STATS];
+                    for (s, stat) in stats.as_array(self.nearest_threat).iter().enumerate() {
+                        contributions[s] = stat * self.weights[offset + s];
+                    }
+                    let mut weight = contributions.iter().sum::<f64>()
+                        + stats.min_dist_to_food * starvation.powi(2) * STARVATION_FOOD_WEIGHT;
+                    if self.head_on_avoidance && enemies.threatens(&next_coord, myself.length()) {
+                        weight += HEAD_ON_PENALTY;
+                    }
 
-        [Action::Left, Action::Front, Action::Right]
+                    (action.clone(), stats, contributions, weight)
+                })
+                .collect();
+
+        let chosen = evaluations
             .iter()
-            .enumerate()
-            .map(|(i, action)| {
-                let next_orientation = next_orientation(current_orientation, &action);
-                let next_coord = next_coord_towards(&head_coord, &next_orientation);
-
-                let stats = compute_stats_from(myself.id, &next_coord, board);
-                let offset = i * NB_STATS;
-                let weight = stats.accessible_area * self.weights[offset]
-                    + stats.ratio_accessible_food * self.weights[offset + 1]
-                    + stats.sum_dist_enemy_heads * self.weights[offset + 2]
-                    + stats.sum_dist_enemy_tails * self.weights[offset + 3]
-                    + stats.min_dist_to_food * self.weights[offset + 4];
-
-                // println!("{:?}:\n\
-                //           \t-> {:?} => {:?}\n\
-                //           \t-> {:?}\n\
-                //           \t=> {}",
-                //          action,
-                //          head_coord, next_coord,
-                //          stats,
-                //          weight);
-
-                (action, weight)
-            })
-            .max_by_key(|(_, weight)| NonNan::new(*weight))
+            .max_by_key(|(_, _, _, weight)| NonNan::new(*weight))
             .unwrap()
             .0
-            .clone()
+            .clone();
+
+        if self.explain {
+            println!(
+                "Snake {} at {:?} facing {:?} (starvation: {:.2}):",
+                myself.id, head_coord, current_orientation, starvation
+            );
+            for (action, stats, contributions, weight) in &evaluations {
+                println!("  {:?} => {:.5}", action, weight);
+                let names = Stats::names(self.nearest_threat);
+                for (s, stat) in stats.as_array(self.nearest_threat).iter().enumerate() {
+                    println!(
+                        "    {:<22} {:9.5} -> {:9.5}",
+                        names[s], stat, contributions[s]
+                    );
+                }
+                if starvation > 0. || self.head_on_avoidance {
+                    println!(
+                        "    {:<22} {:>9} -> {:9.5}",
+                        "starvation/head-on",
+                        "",
+                        weight - contributions.iter().sum::<f64>()
+                    );
+                }
+            }
+            println!("  Chosen: {:?}", chosen);
+        }
+
+        chosen
     }
 }
 
@@ -123,40 +350,139 @@ pub struct Stats {
     pub sum_dist_enemy_heads: f64,
     pub sum_dist_enemy_tails: f64,
     pub min_dist_to_food: f64,
+    /// The distance to the nearest edge of the board, "inf"=1 on wrapping
+    /// boards.
+    pub dist_to_nearest_wall: f64,
+    /// The width of the narrowest passage within sight, up to
+    /// `MAX_CORRIDOR_WIDTH`, see `passage_width()`.
+    pub min_corridor_width: f64,
+    /// The distance to the nearest enemy head. Only used by the bots with the
+    /// `nearest_threat` flag, in place of `sum_dist_enemy_heads`.
+    pub min_dist_enemy_head: f64,
 }
 
 impl Stats {
-    fn new(
-        accessible_area: f64,
-        num_accessible_food: f64,
-        sum_dist_enemy_heads: f64,
-        sum_dist_enemy_tails: f64,
-        min_dist_to_food: f64,
-    ) -> Self {
-        Stats {
-            accessible_area,
-            ratio_accessible_food: num_accessible_food,
-            sum_dist_enemy_heads,
-            sum_dist_enemy_tails,
-            min_dist_to_food,
+    /// The names of the stats, in the order of the weights.
+    /// See `Stats::names` for the names with the `nearest_threat` flag.
+    pub const NAMES: [&'static str; NB_STATS] = [
+        "accessible_area",
+        "ratio_accessible_food",
+        "sum_dist_enemy_heads",
+        "sum_dist_enemy_tails",
+        "min_dist_to_food",
+        "dist_to_nearest_wall",
+        "min_corridor_width",
+    ];
+
+    /// The names of the stats, in the order of the weights.
+    pub fn names(nearest_threat: bool) -> [&'static str; NB_STATS] {
+        let mut names = Self::NAMES;
+        if nearest_threat {
+            names[2] = "min_dist_enemy_head";
+        }
+        names
+    }
+
+    /// The stats, in the order of the weights. With `nearest_threat`, the
+    /// enemy heads slot contains `min_dist_enemy_head`.
+    pub fn as_array(&self, nearest_threat: bool) -> [f64; NB_STATS] {
+        [
+            self.accessible_area,
+            self.ratio_accessible_food,
+            if nearest_threat {
+                self.min_dist_enemy_head
+            } else {
+                self.sum_dist_enemy_heads
+            },
+            self.sum_dist_enemy_tails,
+            self.min_dist_to_food,
+            self.dist_to_nearest_wall,
+            self.min_corridor_width,
+        ]
+    }
+}
+
+/// The positions of the heads and tails of the enemies of a snake.
+pub struct EnemyCells {
+    pub heads: Vec<Position>,
+    pub tails: Vec<Position>,
+    /// The length of the enemy of each head, in the same order as `heads`.
+    pub lengths: Vec<usize>,
+    size: BoardSize,
+    wrap_mode: WrapMode,
+}
+
+impl EnemyCells {
+    pub fn find(snake_id: SnakeId, board: &GameBoard) -> Self {
+        let mut enemies = EnemyCells {
+            heads: vec![],
+            tails: vec![],
+            lengths: vec![],
+            size: board.size(),
+            wrap_mode: board.wrap_mode(),
+        };
+        let mut lengths = HashMap::new();
+        for (coord, cell) in board.iter_cells() {
+            match cell.snake_id() {
+                Some(id) if id != snake_id => *lengths.entry(id).or_insert(0) += 1,
+                _ => continue,
+            }
+            if let Cell::SnakeTail(_) = cell {
+                enemies.tails.push(coord.to_pos(board.size()));
+            }
         }
+        for (id, head) in board.head_positions() {
+            if id != snake_id {
+                enemies.heads.push(head);
+                enemies.lengths.push(lengths[&id]);
+            }
+        }
+        enemies
+    }
+
+    /// Returns whether the head of an enemy at least as long as `length` can
+    /// move to `coord` at the next step, i.e. whether moving there may end in
+    /// a losing head-on collision. The enemies can't move backward, so only
+    /// the free cells next to their heads are threatened.
+    pub fn threatens(&self, coord: &Option<Coordinate>, length: usize) -> bool {
+        let coord = match coord {
+            Some(coord) => coord,
+            None => return false,
+        };
+        self.heads
+            .iter()
+            .zip(&self.lengths)
+            .filter(|(_, &enemy_length)| enemy_length >= length)
+            .any(|(&head, _)| {
+                let head = Coordinate::from_pos(head, self.size);
+                head.distance(coord, self.size, self.wrap_mode) == 1
+            })
     }
 }
 
-/// `coord` is an Option because we don't forbid suicide.
+/// `coord` is an Option because we don't forbid suicide. The BFS stops at
+/// `max_depth`, usually `MAX_DEPTH`.
 pub fn compute_stats_from(
     snake_id: SnakeId,
     coord: &Option<Coordinate>,
     board: &GameBoard,
+    max_depth: i32,
 ) -> Stats {
-    assert!(MAX_DEPTH > 0);
-    assert!(BOARD_WIDTH > 0);
-    assert!(BOARD_HEIGHT > 0);
+    compute_stats_with_enemies(coord, board, &EnemyCells::find(snake_id, board), max_depth)
+}
 
-    let board_diag_size = ((BOARD_WIDTH.pow(2) + BOARD_HEIGHT.pow(2)) as f64)
+/// Same as `compute_stats_from`, with the enemies already found.
+pub fn compute_stats_with_enemies(
+    coord: &Option<Coordinate>,
+    board: &GameBoard,
+    enemies: &EnemyCells,
+    max_depth: i32,
+) -> Stats {
+    let size = board.size();
+    let board_diag_size = ((size.width.pow(2) + size.height.pow(2)) as f64)
         .sqrt()
         .ceil();
-    const NB_CELLS: usize = (BOARD_WIDTH * BOARD_HEIGHT) as usize;
+    let nb_cells = size.nb_cells();
 
     // The stats
     let mut accessible_area = 0.;
@@ -164,19 +490,24 @@ pub fn compute_stats_from(
     let mut sum_dist_enemy_heads = 0.;
     let mut sum_dist_enemy_tails = 0.;
     let mut min_dist_to_food = board_diag_size as i32;
+    // 0 if the BFS isn't performed
+    let mut min_corridor_width = None;
 
     // Added set and fringe queue
-    let mut added = [false; NB_CELLS];
-    let mut queue = [(0, 0); NB_CELLS];
+    // Note: Mud cells can be queued twice, see below.
+    let mut added = vec![false; nb_cells];
+    // The distance at which each cell has been expanded, -1 if it hasn't
+    let mut expanded_dist = vec![-1; nb_cells];
+    let mut queue = vec![(0, 0, false); 2 * nb_cells];
     let mut queue_front: usize = 0;
     let mut queue_back: usize = 0;
 
     // Only add the start coordinate if it's in a free cell
     // => don't perform the BFS if not free
     if let Some(coord) = coord {
-        if board.is_coord_free_or_food(&coord) {
-            let pos = coord.to_pos();
-            queue[queue_back] = (pos, 0_i32);
+        if board.is_coord_free_or_food(coord) {
+            let pos = coord.to_pos(size);
+            queue[queue_back] = (pos, 0_i32, false);
             queue_back += 1;
             added[pos as usize] = true;
         }
@@ -185,17 +516,25 @@ pub fn compute_stats_from(
     // BFS
     while queue_front < queue_back {
         // Pop the next position
-        let (pos, dist) = queue[queue_front];
+        let (pos, dist, delayed) = queue[queue_front];
         queue_front += 1;
 
         // Check the max depth
-        if dist > MAX_DEPTH {
+        if dist > max_depth {
             break;
         }
 
         // Update the stats depending on the current free-tile type
         match board.get_tile_at_pos(pos) {
-            Cell::Empty => accessible_area += 1.,
+            Cell::Mud if !delayed => {
+                // Crossing mud is slower, so re-queue the cell with the extra
+                // cost instead of expanding it now. This keeps the queue
+                // sorted by distance.
+                queue[queue_back] = (pos, dist + MUD_COST - 1, true);
+                queue_back += 1;
+                continue;
+            }
+            Cell::Empty | Cell::Mud => accessible_area += 1.,
             Cell::Food => {
                 accessible_area += 1.;
                 num_accessible_food += 1;
@@ -203,41 +542,41 @@ pub fn compute_stats_from(
             }
             _ => {}
         }
+        expanded_dist[pos as usize] = dist;
+        let width = passage_width(board, &Coordinate::from_pos(pos, size));
+        min_corridor_width =
+            Some(min_corridor_width.map_or(width, |min_width| min(min_width, width)));
 
         // Add the neighbors to the fringe
-        let Coordinate { x, y } = Coordinate::from_pos(pos);
-        [
-            Coordinate { x: x - 1, y },
-            Coordinate { x: x + 1, y },
-            Coordinate { x, y: y - 1 },
-            Coordinate { x, y: y + 1 },
-        ]
-        .iter()
-        .for_each(|coord| {
-            let pos = coord.to_pos();
-            if !coord.is_out_of_bounds() && !added[pos as usize] {
-                // Update the stats depending on the neighbor non-free-tile type
-                match board.get_tile_at_pos(pos) {
-                    Cell::SnakeHead(id) => {
-                        if id != snake_id {
-                            sum_dist_enemy_heads += dist as f64;
-                        }
-                    }
-                    Cell::SnakeTail(id) => {
-                        if id != snake_id {
-                            sum_dist_enemy_tails += dist as f64;
-                        }
-                    }
-                    Cell::Empty | Cell::Food => {
-                        // Add the neighbor the the fringe
-                        queue[queue_back] = (pos, dist + 1);
-                        queue_back += 1;
-                    }
-                    _ => {}
-                }
+        for (coord, cell) in board.neighbors(&Coordinate::from_pos(pos, size)) {
+            let pos = coord.to_pos(size);
+            if !added[pos as usize] && !cell.is_blocking() {
+                queue[queue_back] = (pos, dist + 1, false);
+                queue_back += 1;
                 added[pos as usize] = true;
             }
-        });
+        }
+    }
+
+    // The distance to an enemy head or tail is the one of the closest
+    // expanded cell next to it
+    let dist_to = |pos: Position| {
+        board
+            .neighbors(&Coordinate::from_pos(pos, size))
+            .map(|(coord, _)| expanded_dist[coord.to_pos(size) as usize])
+            .filter(|dist| *dist >= 0)
+            .min()
+    };
+    let mut min_dist_enemy_head = None;
+    for &head in &enemies.heads {
+        if let Some(dist) = dist_to(head) {
+            sum_dist_enemy_heads += dist as f64;
+            min_dist_enemy_head =
+                Some(min_dist_enemy_head.map_or(dist, |min_dist| min(min_dist, dist)));
+        }
+    }
+    for &tail in &enemies.tails {
+        sum_dist_enemy_tails += dist_to(tail).unwrap_or(0) as f64;
     }
 
     // dists are "inf"=1 by default
@@ -253,18 +592,58 @@ pub fn compute_stats_from(
         sum_dist_enemy_tails = max_sum_dist_enemy;
     }
 
+    // The farthest a cell can be from the edges is the middle of the board
+    let dist_to_nearest_wall = match (coord, board.wrap_mode()) {
+        (_, WrapMode::Wrap) => 1.,
+        (None, WrapMode::None) => 0.,
+        (Some(coord), WrapMode::None) => {
+            let dist = min(
+                min(coord.x, size.width - 1 - coord.x),
+                min(coord.y, size.height - 1 - coord.y),
+            );
+            let max_dist = (min(size.width, size.height) - 1) / 2;
+            if max_dist == 0 {
+                0.
+            } else {
+                dist as f64 / max_dist as f64
+            }
+        }
+    };
+
     // Return normalized stats
     let nb_free_cells = board.nb_free_cells;
-    Stats::new(
-        accessible_area as f64 / nb_free_cells as f64,
-        num_accessible_food as f64 / nb_free_cells as f64,
-        sum_dist_enemy_heads as f64 / max_sum_dist_enemy,
-        sum_dist_enemy_tails as f64 / max_sum_dist_enemy,
-        min_dist_to_food as f64 / board_diag_size,
-    )
+    Stats {
+        accessible_area: accessible_area / nb_free_cells as f64,
+        ratio_accessible_food: num_accessible_food as f64 / nb_free_cells as f64,
+        sum_dist_enemy_heads: sum_dist_enemy_heads / max_sum_dist_enemy,
+        sum_dist_enemy_tails: sum_dist_enemy_tails / max_sum_dist_enemy,
+        min_dist_to_food: min_dist_to_food as f64 / board_diag_size,
+        dist_to_nearest_wall,
+        min_corridor_width: min_corridor_width.unwrap_or(0) as f64 / MAX_CORRIDOR_WIDTH as f64,
+        // Unreachable enemy heads are "inf"=1, like the sums
+        min_dist_enemy_head: min_dist_enemy_head.map_or(1., |dist| dist as f64 / board_diag_size),
+    }
 }
 
-#[derive(PartialEq, PartialOrd)]
+/// The width of the passage at `coord`: the number of free cells in a line
+/// through it, horizontally or vertically, whichever is the smallest. The
+/// cells are counted up to `MAX_CORRIDOR_WIDTH`.
+fn passage_width(board: &GameBoard, coord: &Coordinate) -> i32 {
+    let run = |dx: i32, dy: i32| {
+        (1..MAX_CORRIDOR_WIDTH)
+            .take_while(|i| {
+                board.is_coord_free_or_food(&Coordinate {
+                    x: coord.x + i * dx,
+                    y: coord.y + i * dy,
+                })
+            })
+            .count() as i32
+    };
+    let width = min(1 + run(-1, 0) + run(1, 0), 1 + run(0, -1) + run(0, 1));
+    min(width, MAX_CORRIDOR_WIDTH)
+}
+
+#[derive(PartialEq)]
 struct NonNan(f64);
 
 impl NonNan {
@@ -279,9 +658,260 @@ impl NonNan {
 
 impl Eq for NonNan {}
 
+impl PartialOrd for NonNan {
+    #[inline]
+    fn partial_cmp(&self, other: &NonNan) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
 impl Ord for NonNan {
     #[inline]
     fn cmp(&self, other: &NonNan) -> Ordering {
-        self.partial_cmp(other).unwrap()
+        self.0.partial_cmp(&other.0).unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::prelude::*;
+
+    use super::*;
+
+    /// A lonely snake at (10, 8) facing North. On its left, there is a small
+    /// corridor with food, and the rest of the board is empty.
+    fn food_in_corridor_board() -> (GameBoard, SnakeState) {
+        let mut board = GameBoard::new(StdRng::seed_from_u64(0));
+        for x in 7..=9 {
+            board
+                .set_tile_at_coord(&Coordinate { x, y: 7 }, Cell::Obstacle)
+                .unwrap();
+            board
+                .set_tile_at_coord(&Coordinate { x, y: 9 }, Cell::Obstacle)
+                .unwrap();
+        }
+        board
+            .set_tile_at_coord(&Coordinate { x: 6, y: 8 }, Cell::Obstacle)
+            .unwrap();
+        board
+            .set_tile_at_coord(&Coordinate { x: 7, y: 8 }, Cell::Food)
+            .unwrap();
+
+        let head = Coordinate { x: 10, y: 8 };
+        board.set_tile_at_coord(&head, Cell::SnakeHead(0)).unwrap();
+        let myself = SnakeState::with_head(head, Orientation::North, board.size());
+        (board, myself)
+    }
+
+    #[test]
+    fn food_stats_are_the_worst_without_food() {
+        let board = GameBoard::new(StdRng::seed_from_u64(0));
+        let stats = compute_stats_from(0, &Some(Coordinate { x: 5, y: 5 }), &board, MAX_DEPTH);
+        assert_eq!(stats.ratio_accessible_food, 0.);
+        assert_eq!(stats.min_dist_to_food, 1.);
+    }
+
+    #[test]
+    fn dist_to_nearest_wall_is_zero_on_the_edges() {
+        let board = GameBoard::from_ascii(
+            ".....\n\
+             .....\n\
+             .....\n\
+             .....\n\
+             .....",
+        )
+        .unwrap();
+        let dist_at = |x, y, board: &GameBoard| {
+            compute_stats_from(0, &Some(Coordinate { x, y }), board, MAX_DEPTH).dist_to_nearest_wall
+        };
+        assert_eq!(dist_at(0, 3, &board), 0.);
+        assert_eq!(dist_at(4, 4, &board), 0.);
+        assert_eq!(dist_at(1, 3, &board), 0.5);
+        assert_eq!(dist_at(2, 2, &board), 1.);
+
+        // On a flat board, all the cells are on an edge
+        let board = GameBoard::from_ascii(".....\n.....").unwrap();
+        assert_eq!(dist_at(2, 1, &board), 0.);
+    }
+
+    #[test]
+    fn min_corridor_width_detects_tunnels() {
+        // A tunnel on the left, an open area on the right
+        let board = GameBoard::from_ascii(
+            ".#.......\n\
+             .#.......\n\
+             .#.......\n\
+             .#.......\n\
+             .#.......",
+        )
+        .unwrap();
+        let width_at = |x, y| {
+            compute_stats_from(0, &Some(Coordinate { x, y }), &board, MAX_DEPTH).min_corridor_width
+        };
+        assert_eq!(width_at(0, 2), 1. / MAX_CORRIDOR_WIDTH as f64);
+        assert_eq!(width_at(5, 2), 1.);
+        assert_eq!(width_at(1, 2), 0.);
+    }
+
+    #[test]
+    fn the_bfs_stops_at_the_max_depth() {
+        let board = GameBoard::new(StdRng::seed_from_u64(0));
+        let from = Some(Coordinate { x: 5, y: 5 });
+        let nb_free_cells = board.nb_free_cells as f64;
+
+        // The start and its 4 neighbors
+        let stats = compute_stats_from(0, &from, &board, 1);
+        assert_eq!(stats.accessible_area, 5. / nb_free_cells);
+        // The whole board, (31, 15) being 36 cells away
+        let stats = compute_stats_from(0, &from, &board, 36);
+        assert_eq!(stats.accessible_area, 1.);
+    }
+
+    #[test]
+    fn min_dist_enemy_head_is_the_nearest_threat() {
+        let mut board = GameBoard::new(StdRng::seed_from_u64(0));
+        board
+            .set_tile_at_coord(&Coordinate { x: 5, y: 2 }, Cell::SnakeHead(1))
+            .unwrap();
+        board
+            .set_tile_at_coord(&Coordinate { x: 15, y: 5 }, Cell::SnakeHead(2))
+            .unwrap();
+        board.nb_alive_snakes = 3;
+
+        let stats = compute_stats_from(0, &Some(Coordinate { x: 5, y: 5 }), &board, MAX_DEPTH);
+        let board_diag_size = ((board.width().pow(2) + board.height().pow(2)) as f64)
+            .sqrt()
+            .ceil();
+        assert_eq!(stats.min_dist_enemy_head, 2. / board_diag_size);
+        assert_eq!(stats.as_array(true)[2], stats.min_dist_enemy_head);
+        assert_eq!(stats.as_array(false)[2], stats.sum_dist_enemy_heads);
+
+        // Without enemies, the nearest threat is at "inf"
+        let board = GameBoard::new(StdRng::seed_from_u64(0));
+        let stats = compute_stats_from(0, &Some(Coordinate { x: 5, y: 5 }), &board, MAX_DEPTH);
+        assert_eq!(stats.min_dist_enemy_head, 1.);
+    }
+
+    #[test]
+    fn only_longer_enemies_threaten_head_on_collisions() {
+        // An enemy of length 3 at (12, 8) facing West
+        let mut board = GameBoard::new(StdRng::seed_from_u64(0));
+        board
+            .set_tile_at_coord(&Coordinate { x: 12, y: 8 }, Cell::SnakeHead(1))
+            .unwrap();
+        board
+            .set_tile_at_coord(&Coordinate { x: 13, y: 8 }, Cell::SnakeBody(1))
+            .unwrap();
+        board
+            .set_tile_at_coord(&Coordinate { x: 14, y: 8 }, Cell::SnakeTail(1))
+            .unwrap();
+
+        let enemies = EnemyCells::find(0, &board);
+        assert_eq!(enemies.lengths, vec![3]);
+
+        let next_to_head = Some(Coordinate { x: 11, y: 8 });
+        assert!(enemies.threatens(&next_to_head, 2));
+        assert!(enemies.threatens(&next_to_head, 3));
+        assert!(!enemies.threatens(&next_to_head, 4));
+        assert!(!enemies.threatens(&Some(Coordinate { x: 10, y: 8 }), 2));
+        assert!(!enemies.threatens(&None, 2));
+    }
+
+    #[test]
+    fn head_on_threats_go_through_the_edges() {
+        // An enemy on the left edge
+        let mut board = GameBoard::from_ascii(
+            "1....\n\
+             .....",
+        )
+        .unwrap();
+        let across_the_edge = Some(Coordinate { x: 4, y: 0 });
+        assert!(!EnemyCells::find(0, &board).threatens(&across_the_edge, 1));
+
+        board.set_wrap_mode(WrapMode::Wrap);
+        assert!(EnemyCells::find(0, &board).threatens(&across_the_edge, 1));
+    }
+
+    #[test]
+    fn weights_round_trip_through_pretty_weights() {
+        let weights: Weights = (0..NB_WEIGHTS)
+            .map(|i| (i as f64 * 12345. - 86415.) / 100_000.)
+            .collect();
+        let printed = PrettyWeights(&weights).to_string();
+        assert_eq!(parse_weights(&printed), Ok(weights));
+
+        assert_eq!(
+            parse_weights("1, 2, 3"),
+            Err(ParseWeightsError::WrongCount(3))
+        );
+        assert_eq!(
+            parse_weights("[1, two]"),
+            Err(ParseWeightsError::InvalidNumber("two".to_string()))
+        );
+    }
+
+    #[test]
+    fn weights_files_from_other_versions_are_rejected() {
+        let path = std::env::temp_dir().join("genetic-snake-rs-weights-test.txt");
+        save_weights(&path, &GOOD_WEIGHTS).unwrap();
+        assert_eq!(load_weights(&path), Ok(GOOD_WEIGHTS.clone()));
+
+        // Saved by a build with fewer stats
+        let older_weights = ["0.5"; NB_WEIGHTS - 3].join(", ");
+        let header = format!("{} {}", WEIGHTS_FILE_HEADER, NB_WEIGHTS - 3);
+        fs::write(&path, format!("{}\n[{}]\n", header, older_weights)).unwrap();
+        assert_eq!(
+            load_weights(&path),
+            Err(ParseWeightsError::IncompatibleLength {
+                found: NB_WEIGHTS - 3,
+                expected: NB_WEIGHTS
+            })
+        );
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn snakes_without_head_dont_panic() {
+        let (board, mut myself) = food_in_corridor_board();
+        myself.positions.clear();
+        assert_eq!(myself.try_get_head_coord(board.size()), None);
+
+        let mut bot = HeuristicBot::default();
+        assert_eq!(bot.get_next_action(&myself, &board), Action::Front);
+    }
+
+    #[test]
+    fn prefers_space_when_not_hungry() {
+        let (board, mut myself) = food_in_corridor_board();
+        let mut bot = HeuristicBot::default();
+
+        assert_ne!(bot.get_next_action(&myself, &board), Action::Left);
+
+        myself.health = Some(100);
+        myself.max_health = Some(100);
+        assert_ne!(bot.get_next_action(&myself, &board), Action::Left);
+    }
+
+    #[test]
+    fn explaining_doesnt_change_the_move() {
+        let (board, mut myself) = food_in_corridor_board();
+        myself.health = Some(5);
+        myself.max_health = Some(100);
+
+        let action = HeuristicBot::default().get_next_action(&myself, &board);
+        let explained_action = HeuristicBot::default()
+            .with_explain(true)
+            .get_next_action(&myself, &board);
+        assert_eq!(action, explained_action);
+    }
+
+    #[test]
+    fn goes_for_food_when_starving() {
+        let (board, mut myself) = food_in_corridor_board();
+        let mut bot = HeuristicBot::default();
+
+        myself.health = Some(5);
+        myself.max_health = Some(100);
+        assert_eq!(bot.get_next_action(&myself, &board), Action::Left);
     }
 }
@@ -1,4 +1,5 @@
-use console::Style;
+use colored::Colorize;
+use console::{Key, Style, Term};
 use dialoguer::{theme::ColorfulTheme, Confirmation, Input, Select};
 use indicatif::{ProgressBar, ProgressStyle};
 
@@ -6,26 +7,91 @@ use crate::game_engine::*;
 use crate::DIALOG_THEME;
 
 /// This bot ask on stdin the next action to realize.
+///
+/// When stdout is a terminal, a single key press is enough: left arrow or `a`
+/// to turn left, up arrow or `w` to go forward, and right arrow or `d` to
+/// turn right. Otherwise, the action is chosen in a menu.
+///
+/// The moves leading to a certain death are marked, to help the human.
 #[derive(Default)]
 pub struct InteractiveBot;
 
 impl SnakeBot for InteractiveBot {
-    fn get_next_action(&mut self, _: &SnakeState, _: &GameBoard) -> Action {
-        // TODO: Colorize this prompt with the same color than the snakes
-        let choice = Select::with_theme(&*DIALOG_THEME)
-            .with_prompt("What is your next action?")
-            .default(1)
-            .item("left")
-            .item("front")
-            .item("right")
-            .interact()
-            .unwrap_or(1);
-
-        match choice {
-            0 => Action::Left,
-            1 => Action::Front,
-            2 => Action::Right,
-            _ => unreachable!(),
+    fn get_next_action(&mut self, myself: &SnakeState, board: &GameBoard) -> Action {
+        let safe_moves = match myself.try_get_head_coord(board.size()) {
+            Some(head) => board.get_non_suicide_moves(&head, &myself.current_orientation),
+            None => vec![],
+        };
+        read_action_from_key(&safe_moves).unwrap_or_else(|| read_action_from_menu(&safe_moves))
+    }
+}
+
+const ACTIONS: [Action; 3] = [Action::Left, Action::Front, Action::Right];
+
+/// The name of the action, with a red "(dies)" suffix if it isn't safe.
+fn describe_action(name: &str, action: &Action, safe_moves: &[Action]) -> String {
+    if safe_moves.contains(action) {
+        name.to_string()
+    } else {
+        format!("{} {}", name, "(dies)".red())
+    }
+}
+
+/// Waits for a key press, without needing Enter.
+/// Returns None if the terminal doesn't support reading single keys.
+fn read_action_from_key(safe_moves: &[Action]) -> Option<Action> {
+    let term = Term::stdout();
+    if !term.is_term() {
+        return None;
+    }
+
+    // TODO: Colorize this prompt with the same color than the snakes
+    let prompt = Style::new().yellow().bold();
+    term.write_line(&format!(
+        "{} [←/a] {}, [↑/w] {}, [→/d] {}",
+        prompt.apply_to("What is your next action?"),
+        describe_action("left", &Action::Left, safe_moves),
+        describe_action("front", &Action::Front, safe_moves),
+        describe_action("right", &Action::Right, safe_moves)
+    ))
+    .ok()?;
+
+    loop {
+        match term.read_key().ok()? {
+            Key::ArrowLeft => return Some(Action::Left),
+            Key::ArrowUp => return Some(Action::Front),
+            Key::ArrowRight => return Some(Action::Right),
+            Key::Char(c) => match c.to_ascii_lowercase() {
+                'a' => return Some(Action::Left),
+                'w' => return Some(Action::Front),
+                'd' => return Some(Action::Right),
+                _ => {}
+            },
+            _ => {}
         }
     }
 }
+
+fn read_action_from_menu(safe_moves: &[Action]) -> Action {
+    // Select a safe move by default, going forward if possible
+    let default = if safe_moves.contains(&Action::Front) {
+        1
+    } else {
+        ACTIONS
+            .iter()
+            .position(|action| safe_moves.contains(action))
+            .unwrap_or(1)
+    };
+
+    // TODO: Colorize this prompt with the same color than the snakes
+    let choice = Select::with_theme(&*DIALOG_THEME)
+        .with_prompt("What is your next action?")
+        .default(default)
+        .item(&describe_action("left", &Action::Left, safe_moves))
+        .item(&describe_action("front", &Action::Front, safe_moves))
+        .item(&describe_action("right", &Action::Right, safe_moves))
+        .interact()
+        .unwrap_or(default);
+
+    ACTIONS[choice].clone()
+}
@@ -0,0 +1,61 @@
+//! A global Ctrl+C handler.
+//!
+//! `ctrlc::set_handler` can only be called once per process, so the handler
+//! is installed once at program start and only raises a flag. The long
+//! running loops (learning, matches...) catch the interruptions with
+//! `catch_interrupts()` and poll the flag. When nothing catches them, Ctrl+C
+//! quits the program as usual.
+
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+/// Raised by the handler, cleared by the catcher.
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+/// The number of alive `InterruptCatcher`s.
+static NB_CATCHERS: AtomicUsize = AtomicUsize::new(0);
+
+/// Installs the global Ctrl+C handler (if the feature is enabled).
+/// Note: This must only be called once.
+#[cfg(feature = "ctrlc")]
+pub fn install_handler() {
+    ctrlc::set_handler(|| {
+        if NB_CATCHERS.load(Ordering::SeqCst) > 0 {
+            INTERRUPTED.store(true, Ordering::SeqCst);
+        } else {
+            std::process::exit(130);
+        }
+    })
+    .unwrap_or_else(|_| eprintln!("Error setting Ctrl-C handler."));
+}
+
+/// No-op (if the feature is disabled)
+#[cfg(not(feature = "ctrlc"))]
+pub fn install_handler() {}
+
+/// While the returned value is alive, Ctrl+C doesn't quit the program but
+/// raises the flag polled with `InterruptCatcher::take()`.
+pub fn catch_interrupts() -> InterruptCatcher {
+    if NB_CATCHERS.fetch_add(1, Ordering::SeqCst) == 0 {
+        // Forget the old interruptions
+        INTERRUPTED.store(false, Ordering::SeqCst);
+    }
+    InterruptCatcher { _private: () }
+}
+
+/// See `catch_interrupts()`.
+pub struct InterruptCatcher {
+    _private: (),
+}
+
+impl InterruptCatcher {
+    /// Returns whether Ctrl+C was pressed since the last call.
+    pub fn take(&self) -> bool {
+        INTERRUPTED.swap(false, Ordering::SeqCst)
+    }
+}
+
+impl Drop for InterruptCatcher {
+    fn drop(&mut self) {
+        NB_CATCHERS.fetch_sub(1, Ordering::SeqCst);
+    }
+}
@@ -1,9 +1,14 @@
 use std::{
-    fs::OpenOptions,
-    io::Write,
+    collections::{hash_map::DefaultHasher, HashMap},
+    fmt,
+    fs::{self, OpenOptions},
+    hash::{Hash, Hasher},
+    io::{self, Write},
+    path::Path,
+    str::FromStr,
     sync::{
-        atomic::{AtomicBool, Ordering},
-        Arc,
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex, RwLock,
     },
     thread,
     time::{Duration, Instant},
@@ -18,8 +23,11 @@ use indicatif::{ProgressBar, ProgressStyle};
 use colored::Colorize;
 
 use genevo::{
-    genetic::FitnessFunction, operator::prelude::*, population::ValueEncodedGenomeBuilder,
-    prelude::*, types::fmt::Display,
+    genetic::{Children, FitnessFunction, Parents},
+    operator::{prelude::*, CrossoverOp, GeneticOperator, MutationOp},
+    population::ValueEncodedGenomeBuilder,
+    prelude::*,
+    types::fmt::Display,
 };
 
 use rayon::prelude::*;
@@ -27,8 +35,10 @@ use rayon::prelude::*;
 use chrono::prelude::*;
 
 use crate::game_engine::{GameResultWinner::*, *};
+use crate::gauntlet::Gauntlet;
 use crate::heuristic_bot::*;
 use crate::interactive_bot::InteractiveBot;
+use crate::interrupt::catch_interrupts;
 use crate::random_bot::RandomBot;
 use crate::DIALOG_THEME;
 
@@ -39,17 +49,43 @@ pub const GENOME_LENGTH: usize = NB_WEIGHTS;
 pub const GENOME_MIN_VALUE: f64 = -1.;
 pub const GENOME_MAX_VALUE: f64 = 1.;
 
-#[derive(Debug)]
-struct Parameters {
-    population_size: usize,
-    generation_limit: u64,
-    num_individuals_per_parents: usize,
-    selection_ratio: f64,
-    num_crossover_points: usize,
-    mutation_rate: f64,
-    mutation_range: f64,
-    mutation_precision: u8,
-    reinsertion_ratio: f64,
+/// The parameters of the genetic algorithm.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Parameters {
+    pub population_size: usize,
+    pub generation_limit: u64,
+    pub num_individuals_per_parents: usize,
+    pub selection_ratio: f64,
+    pub num_crossover_points: usize,
+    pub mutation_rate: f64,
+    pub mutation_range: f64,
+    pub mutation_precision: u8,
+    pub reinsertion_ratio: f64,
+    pub crossover: CrossoverKind,
+    pub mutator: MutatorKind,
+    /// The number of matches played to evaluate a genome. The matches are
+    /// seeded from the genome, see `match_seed()`.
+    pub nb_matches: usize,
+    /// The points of a genome for each match it wins, draws or loses.
+    pub win_score: usize,
+    pub draw_score: usize,
+    pub loss_score: usize,
+    /// Whether each match is played twice on the same seed, the genome
+    /// playing each side once, to cancel the advantage of a side.
+    pub mirrored_matches: bool,
+    /// With the `render` feature, the best genome of every Nth generation
+    /// plays a game which is written as PNG frames.
+    pub render_every: Option<u64>,
+    /// Whether the fitness of a genome is only computed once, e.g. not again
+    /// for the survivors of each generation. It's faster, and the matches of
+    /// a genome always have the same seeds, so its fitness would be the same
+    /// anyway.
+    pub cache_fitness: bool,
+    /// The whole population is saved every Nth generation, to be able to
+    /// resume the learning, see `save_checkpoint()`.
+    pub checkpoint_every: Option<u64>,
+    /// Against whom the genomes are evaluated.
+    pub fitness: FitnessKind,
 }
 
 impl Default for Parameters {
@@ -64,33 +100,675 @@ impl Default for Parameters {
             mutation_range: 0.1,
             mutation_precision: 2,
             reinsertion_ratio: 0.7,
+            crossover: CrossoverKind::MultiPoint,
+            mutator: MutatorKind::BreederValue,
+            nb_matches: 20,
+            win_score: 2,
+            draw_score: 1,
+            loss_score: 0,
+            mirrored_matches: false,
+            render_every: None,
+            cache_fitness: false,
+            checkpoint_every: Some(50),
+            fitness: FitnessKind::Static,
         }
     }
 }
 
-/// The fitness function for `GeneticBotGenome`s.
+impl Parameters {
+    /// Reads the parameters from a config file, e.g. `ga_config.toml`, of
+    /// `field = value` lines. The fields which aren't in the file keep their
+    /// default value. This is a small subset of TOML: the values are on one
+    /// line, and the optional fields are `"none"` or a value.
+    pub fn from_file(path: &Path) -> Result<Parameters, ConfigError> {
+        let content = fs::read_to_string(path).map_err(|e| ConfigError::Io(e.to_string()))?;
+        let mut params = Parameters::default();
+        for (i, line) in content.lines().enumerate() {
+            let line = line.split('#').next().unwrap().trim();
+            if line.is_empty() {
+                continue;
+            }
+            let invalid = || ConfigError::InvalidLine(i + 1);
+            let (key, value) = line.split_at(line.find('=').ok_or_else(invalid)?);
+            let value = value[1..].trim().trim_matches('"');
+            params.set(key.trim(), value).ok_or_else(invalid)?;
+        }
+
+        for &(field, value) in &[
+            ("selection_ratio", params.selection_ratio),
+            ("mutation_rate", params.mutation_rate),
+        ] {
+            if !(0. ..=1.).contains(&value) {
+                return Err(ConfigError::NotARatio { field, value });
+            }
+        }
+        Ok(params)
+    }
+
+    /// Sets the field named `key`. Returns `None` if there's no such field or
+    /// if `value` is invalid. The optional fields are `none` or a value.
+    fn set(&mut self, key: &str, value: &str) -> Option<()> {
+        fn optional<T: FromStr>(value: &str) -> Option<Option<T>> {
+            match value {
+                "none" => Some(None),
+                _ => value.parse().ok().map(Some),
+            }
+        }
+        match key {
+            "population_size" => self.population_size = value.parse().ok()?,
+            "generation_limit" => self.generation_limit = value.parse().ok()?,
+            "num_individuals_per_parents" => {
+                self.num_individuals_per_parents = value.parse().ok()?
+            }
+            "selection_ratio" => self.selection_ratio = value.parse().ok()?,
+            "num_crossover_points" => self.num_crossover_points = value.parse().ok()?,
+            "mutation_rate" => self.mutation_rate = value.parse().ok()?,
+            "mutation_range" => self.mutation_range = value.parse().ok()?,
+            "mutation_precision" => self.mutation_precision = value.parse().ok()?,
+            "reinsertion_ratio" => self.reinsertion_ratio = value.parse().ok()?,
+            "crossover" => self.crossover = value.parse().ok()?,
+            "mutator" => self.mutator = value.parse().ok()?,
+            "nb_matches" => self.nb_matches = value.parse().ok()?,
+            "win_score" => self.win_score = value.parse().ok()?,
+            "draw_score" => self.draw_score = value.parse().ok()?,
+            "loss_score" => self.loss_score = value.parse().ok()?,
+            "mirrored_matches" => self.mirrored_matches = value.parse().ok()?,
+            "render_every" => self.render_every = optional(value)?,
+            "cache_fitness" => self.cache_fitness = value.parse().ok()?,
+            "checkpoint_every" => self.checkpoint_every = optional(value)?,
+            "fitness" => self.fitness = value.parse().ok()?,
+            _ => return None,
+        }
+        Some(())
+    }
+
+    /// The fitness of a genome winning all its matches, or surviving until
+    /// the end of all of them.
+    pub fn max_fitness(&self) -> usize {
+        match self.fitness {
+            FitnessKind::Static | FitnessKind::SelfPlay => self.nb_matches * self.win_score,
+            FitnessKind::Survival => self.nb_matches * SurvivalFitnessCalc::MAX_STEPS as usize,
+        }
+    }
+
+    /// The default target: 90% of the `max_fitness()`, e.g. winning 90% of
+    /// the matches.
+    pub fn default_target_fitness(&self) -> usize {
+        self.max_fitness() * 9 / 10
+    }
+
+    fn crossover_op(&self) -> Crossover {
+        match self.crossover {
+            CrossoverKind::Discrete => Crossover::Discrete(UniformCrossBreeder::new()),
+            CrossoverKind::MultiPoint => {
+                Crossover::MultiPoint(MultiPointCrossBreeder::new(self.num_crossover_points))
+            }
+        }
+    }
+
+    fn mutation_op(&self) -> Mutation {
+        match self.mutator {
+            MutatorKind::RandomValue => Mutation::RandomValue(RandomValueMutator::new(
+                self.mutation_rate,
+                GENOME_MIN_VALUE,
+                GENOME_MAX_VALUE,
+            )),
+            MutatorKind::BreederValue => Mutation::BreederValue(BreederValueMutator::new(
+                self.mutation_rate,
+                self.mutation_range,
+                self.mutation_precision,
+                GENOME_MIN_VALUE * 10_f64,
+                GENOME_MAX_VALUE * 10_f64,
+            )),
+        }
+    }
+}
+
+/// Why the parameters can't be read by `Parameters::from_file()`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConfigError {
+    /// The line, starting at 1, isn't a known field with a valid value.
+    InvalidLine(usize),
+    /// The field must be in [0, 1].
+    NotARatio { field: &'static str, value: f64 },
+    /// The config file can't be read.
+    Io(String),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ConfigError::InvalidLine(line) => {
+                write!(f, "The line {} of the config is invalid.", line)
+            }
+            ConfigError::NotARatio { field, value } => {
+                write!(f, "{} must be in [0, 1], got {}.", field, value)
+            }
+            ConfigError::Io(error) => write!(f, "Unable to read the config: {}", error),
+        }
+    }
+}
+
+/// The crossover operator of the learning.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CrossoverKind {
+    /// Each weight comes from a random parent (genevo's `UniformCrossBreeder`).
+    Discrete,
+    /// The genomes are cut at `num_crossover_points` random points.
+    MultiPoint,
+}
+
+impl FromStr for CrossoverKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "discrete" => Ok(CrossoverKind::Discrete),
+            "multi-point" => Ok(CrossoverKind::MultiPoint),
+            _ => Err(format!("Unknown crossover: {}.", s)),
+        }
+    }
+}
+
+impl fmt::Display for CrossoverKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CrossoverKind::Discrete => write!(f, "discrete"),
+            CrossoverKind::MultiPoint => write!(f, "multi-point"),
+        }
+    }
+}
+
+/// The mutation operator of the learning.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MutatorKind {
+    /// The mutated weights get a new random value.
+    RandomValue,
+    /// The mutated weights are moved by up to `mutation_range`.
+    BreederValue,
+}
+
+impl FromStr for MutatorKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "random" => Ok(MutatorKind::RandomValue),
+            "breeder" => Ok(MutatorKind::BreederValue),
+            _ => Err(format!("Unknown mutator: {}.", s)),
+        }
+    }
+}
+
+impl fmt::Display for MutatorKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MutatorKind::RandomValue => write!(f, "random"),
+            MutatorKind::BreederValue => write!(f, "breeder"),
+        }
+    }
+}
+
+/// The opponents of the genomes during their evaluation.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FitnessKind {
+    /// The human-tuned bot, see `WinRatioFitnessCalc`.
+    Static,
+    /// The best genomes of the previous generation, see
+    /// `SelfPlayFitnessCalc`.
+    SelfPlay,
+    /// No opponent: the steps survived alone, see `SurvivalFitnessCalc`.
+    Survival,
+}
+
+impl FromStr for FitnessKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "static" => Ok(FitnessKind::Static),
+            "self-play" => Ok(FitnessKind::SelfPlay),
+            "survival" => Ok(FitnessKind::Survival),
+            _ => Err(format!("Unknown fitness: {}.", s)),
+        }
+    }
+}
+
+impl fmt::Display for FitnessKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FitnessKind::Static => write!(f, "static"),
+            FitnessKind::SelfPlay => write!(f, "self-play"),
+            FitnessKind::Survival => write!(f, "survival"),
+        }
+    }
+}
+
+/// The crossover operator chosen by `CrossoverKind`. The genevo builder is
+/// statically typed, so a runtime choice needs a single operator type.
+#[derive(Clone, Debug)]
+enum Crossover {
+    Discrete(UniformCrossBreeder),
+    MultiPoint(MultiPointCrossBreeder),
+}
+
+impl GeneticOperator for Crossover {
+    fn name() -> String {
+        "Selectable-Cross-Breeder".to_string()
+    }
+}
+
+impl CrossoverOp<GeneticBotGenome> for Crossover {
+    fn crossover<R>(
+        &self,
+        parents: Parents<GeneticBotGenome>,
+        rng: &mut R,
+    ) -> Children<GeneticBotGenome>
+    where
+        R: Rng + Sized,
+    {
+        match self {
+            Crossover::Discrete(op) => op.crossover(parents, rng),
+            Crossover::MultiPoint(op) => op.crossover(parents, rng),
+        }
+    }
+}
+
+/// The mutation operator chosen by `MutatorKind`, see `Crossover`.
+#[derive(Clone, Debug)]
+enum Mutation {
+    RandomValue(RandomValueMutator<GeneticBotGenome>),
+    BreederValue(BreederValueMutator<GeneticBotGenome>),
+}
+
+impl GeneticOperator for Mutation {
+    fn name() -> String {
+        "Selectable-Value-Mutator".to_string()
+    }
+}
+
+impl MutationOp<GeneticBotGenome> for Mutation {
+    fn mutate<R>(&self, genome: GeneticBotGenome, rng: &mut R) -> GeneticBotGenome
+    where
+        R: Rng + Sized,
+    {
+        match self {
+            Mutation::RandomValue(op) => op.mutate(genome, rng),
+            Mutation::BreederValue(op) => op.mutate(genome, rng),
+        }
+    }
+}
+
+/// The fitness function for `GeneticBotGenome`s: the points scored in
+/// matches against the human-tuned bot.
 #[derive(Clone, Debug)]
 pub struct WinRatioFitnessCalc {
-    target_fitness: usize,
+    nb_matches: usize,
+    win_score: usize,
+    draw_score: usize,
+    loss_score: usize,
+    mirrored_matches: bool,
+    cache: Option<Arc<FitnessCache>>,
 }
 
 impl WinRatioFitnessCalc {
-    const NB_MATCHES: usize = 20;
+    pub fn new(params: &Parameters) -> Self {
+        WinRatioFitnessCalc {
+            nb_matches: params.nb_matches,
+            win_score: params.win_score,
+            draw_score: params.draw_score,
+            loss_score: params.loss_score,
+            mirrored_matches: params.mirrored_matches,
+            cache: if params.cache_fitness {
+                Some(Arc::new(FitnessCache::default()))
+            } else {
+                None
+            },
+        }
+    }
 
-    fn new(target_fitness: usize) -> Self {
-        WinRatioFitnessCalc { target_fitness }
+    /// The cache shared by the clones of this fitness function, if
+    /// `Parameters::cache_fitness` is set.
+    pub fn cache(&self) -> Option<&FitnessCache> {
+        self.cache.as_deref()
+    }
+
+    /// Plays the matches of the genome, without looking at the cache.
+    fn evaluate(&self, genome: &GeneticBotGenome) -> usize {
+        let total: usize = (0..self.nb_matches)
+            .into_par_iter()
+            .map(|i| self.play_sides(genome, &GOOD_WEIGHTS, match_seed(genome, i)))
+            .sum();
+
+        // The average of the two sides
+        total / 2
+    }
+
+    /// Plays a match against `opponent`, or two if the matches are mirrored,
+    /// and returns twice the points of the genome for one match.
+    fn play_sides(&self, genome: &[Weight], opponent: &[Weight], seed: u64) -> usize {
+        if self.mirrored_matches {
+            self.play_match(genome, opponent, seed, 0) + self.play_match(genome, opponent, seed, 1)
+        } else {
+            2 * self.play_match(genome, opponent, seed, 0)
+        }
+    }
+
+    /// Plays one match against `opponent`, the genome controlling the snake
+    /// `genome_id`, and returns the points of the genome.
+    fn play_match(
+        &self,
+        genome: &[Weight],
+        opponent: &[Weight],
+        seed: u64,
+        genome_id: SnakeId,
+    ) -> usize {
+        let mut game = Game::with_seed(seed);
+        game.continue_simulation_if_known_winner(false)
+            .end_on_entrapment(true)
+            .max_steps(Game::MAX_UNWATCHED_STEPS);
+        // The snakes are placed in the order they are added
+        if genome_id == 0 {
+            game.add_snake(0, Box::from(HeuristicBot::new(genome)))
+                .add_snake(1, Box::from(HeuristicBot::new(opponent)));
+        } else {
+            game.add_snake(0, Box::from(HeuristicBot::new(opponent)))
+                .add_snake(1, Box::from(HeuristicBot::new(genome)));
+        }
+        let results = game.initialize().run_to_end();
+
+        match results.winner {
+            Some(GameResultWinner::Winner(id)) if id == genome_id => self.win_score,
+            Some(GameResultWinner::Draw) => self.draw_score,
+            Some(GameResultWinner::Winner(_)) => self.loss_score,
+            Some(GameResultWinner::SoloGameOver) | None => {
+                unreachable!("Two-player games always end with a winner or a draw.")
+            }
+        }
     }
 }
 
 impl FitnessFunction<GeneticBotGenome, usize> for WinRatioFitnessCalc {
     fn fitness_of(&self, genome: &GeneticBotGenome) -> usize {
-        (0..Self::NB_MATCHES as usize)
+        match &self.cache {
+            Some(cache) => cache.get_or_evaluate(genome, || self.evaluate(genome)),
+            None => self.evaluate(genome),
+        }
+    }
+
+    fn average(&self, fitness_values: &[usize]) -> usize {
+        fitness_values.iter().sum::<usize>() / fitness_values.len()
+    }
+
+    fn highest_possible_fitness(&self) -> usize {
+        self.nb_matches * self.win_score
+    }
+
+    fn lowest_possible_fitness(&self) -> usize {
+        0
+    }
+}
+
+/// The fitness function of the self-play: the points scored in matches
+/// against the best genomes of the previous generation, so that the genomes
+/// have to beat evolving opponents rather than a fixed one. The opponents are
+/// the human-tuned bot until the first generation is evaluated.
+///
+/// Note: The fitness values of two generations aren't comparable, and the
+/// fitness isn't cached, since the opponents change.
+#[derive(Clone, Debug)]
+pub struct SelfPlayFitnessCalc {
+    /// Scores the matches, without cache.
+    scoring: WinRatioFitnessCalc,
+    opponents: Arc<RwLock<Vec<GeneticBotGenome>>>,
+}
+
+impl SelfPlayFitnessCalc {
+    /// The number of genomes of the previous generation to play against.
+    pub const NB_OPPONENTS: usize = 4;
+
+    pub fn new(params: &Parameters) -> Self {
+        SelfPlayFitnessCalc {
+            scoring: WinRatioFitnessCalc {
+                cache: None,
+                ..WinRatioFitnessCalc::new(params)
+            },
+            opponents: Arc::new(RwLock::new(vec![GOOD_WEIGHTS.clone()])),
+        }
+    }
+
+    /// Makes the fittest genomes of an evaluated generation the opponents
+    /// of the next one. Shared by the clones of this fitness function.
+    pub fn update_opponents(&self, genomes: &[GeneticBotGenome], fitness_values: &[usize]) {
+        *self.opponents.write().unwrap() = fittest(genomes, fitness_values, Self::NB_OPPONENTS);
+    }
+}
+
+impl FitnessFunction<GeneticBotGenome, usize> for SelfPlayFitnessCalc {
+    fn fitness_of(&self, genome: &GeneticBotGenome) -> usize {
+        let opponents = self.opponents.read().unwrap().clone();
+        let total: usize = (0..self.scoring.nb_matches)
             .into_par_iter()
-            .map(|_| {
-                let results = Game::new()
+            .map(|i| {
+                let opponent = &opponents[i % opponents.len()];
+                self.scoring
+                    .play_sides(genome, opponent, match_seed(genome, i))
+            })
+            .sum();
+        total / 2
+    }
+
+    fn average(&self, fitness_values: &[usize]) -> usize {
+        self.scoring.average(fitness_values)
+    }
+
+    fn highest_possible_fitness(&self) -> usize {
+        self.scoring.highest_possible_fitness()
+    }
+
+    fn lowest_possible_fitness(&self) -> usize {
+        0
+    }
+}
+
+/// The fitness function of the solo training: the number of steps survived
+/// alone on the board, summed over the matches. There's no opponent, so the
+/// genomes only learn to eat and not to trap themselves.
+#[derive(Clone, Debug)]
+pub struct SurvivalFitnessCalc {
+    nb_matches: usize,
+}
+
+impl SurvivalFitnessCalc {
+    /// A game lasting this number of steps is a success, so that the best
+    /// genomes don't play forever.
+    pub const MAX_STEPS: u32 = 1_000;
+
+    pub fn new(params: &Parameters) -> Self {
+        SurvivalFitnessCalc {
+            nb_matches: params.nb_matches,
+        }
+    }
+
+    /// Plays one solo game and returns the number of steps survived.
+    fn play_solo(genome: &[Weight], seed: u64) -> usize {
+        let results = Game::with_seed(seed)
+            .end_on_entrapment(true)
+            .max_steps(Self::MAX_STEPS)
+            .add_snake(0, Box::from(HeuristicBot::new(genome)))
+            .initialize()
+            .run_to_end();
+
+        let snake = &results.snakes[0];
+        snake.died_at_step.map_or(results.steps, |step| step + 1) as usize
+    }
+}
+
+impl FitnessFunction<GeneticBotGenome, usize> for SurvivalFitnessCalc {
+    fn fitness_of(&self, genome: &GeneticBotGenome) -> usize {
+        (0..self.nb_matches)
+            .into_par_iter()
+            .map(|i| Self::play_solo(genome, match_seed(genome, i)))
+            .sum()
+    }
+
+    fn average(&self, fitness_values: &[usize]) -> usize {
+        fitness_values.iter().sum::<usize>() / fitness_values.len()
+    }
+
+    fn highest_possible_fitness(&self) -> usize {
+        self.nb_matches * Self::MAX_STEPS as usize
+    }
+
+    fn lowest_possible_fitness(&self) -> usize {
+        0
+    }
+}
+
+/// The seed of the match `i` of `genome`. The seeds of a genome are the
+/// same at each evaluation, so that its fitness doesn't depend on luck, as
+/// long as its opponents are the same. They are compared like in the
+/// `FitnessCache`, and only reproducible with the same build.
+fn match_seed(genome: &GeneticBotGenome, i: usize) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    FitnessCache::key(genome).hash(&mut hasher);
+    i.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// The fitness function chosen by `FitnessKind`, see `Crossover`.
+#[derive(Clone, Debug)]
+enum Fitness {
+    Static(WinRatioFitnessCalc),
+    SelfPlay(SelfPlayFitnessCalc),
+    Survival(SurvivalFitnessCalc),
+}
+
+impl Fitness {
+    fn new(params: &Parameters) -> Self {
+        match params.fitness {
+            FitnessKind::Static => Fitness::Static(WinRatioFitnessCalc::new(params)),
+            FitnessKind::SelfPlay => Fitness::SelfPlay(SelfPlayFitnessCalc::new(params)),
+            FitnessKind::Survival => Fitness::Survival(SurvivalFitnessCalc::new(params)),
+        }
+    }
+}
+
+impl FitnessFunction<GeneticBotGenome, usize> for Fitness {
+    fn fitness_of(&self, genome: &GeneticBotGenome) -> usize {
+        match self {
+            Fitness::Static(calc) => calc.fitness_of(genome),
+            Fitness::SelfPlay(calc) => calc.fitness_of(genome),
+            Fitness::Survival(calc) => calc.fitness_of(genome),
+        }
+    }
+
+    fn average(&self, fitness_values: &[usize]) -> usize {
+        fitness_values.iter().sum::<usize>() / fitness_values.len()
+    }
+
+    fn highest_possible_fitness(&self) -> usize {
+        match self {
+            Fitness::Static(calc) => calc.highest_possible_fitness(),
+            Fitness::SelfPlay(calc) => calc.highest_possible_fitness(),
+            Fitness::Survival(calc) => calc.highest_possible_fitness(),
+        }
+    }
+
+    fn lowest_possible_fitness(&self) -> usize {
+        0
+    }
+}
+
+/// The fitness of the genomes already evaluated, see
+/// `Parameters::cache_fitness`. The genomes are compared once quantized, so
+/// that tiny differences don't matter.
+#[derive(Debug, Default)]
+pub struct FitnessCache {
+    fitness_values: Mutex<HashMap<Vec<i64>, usize>>,
+    nb_lookups: AtomicUsize,
+    nb_hits: AtomicUsize,
+}
+
+impl FitnessCache {
+    /// The cache is emptied when it reaches this size, to bound its memory.
+    const MAX_ENTRIES: usize = 100_000;
+    /// The weights are rounded to this precision to compare the genomes.
+    const PRECISION: f64 = 1e-6;
+
+    fn key(genome: &GeneticBotGenome) -> Vec<i64> {
+        genome
+            .iter()
+            .map(|weight| (weight / Self::PRECISION).round() as i64)
+            .collect()
+    }
+
+    /// Returns the cached fitness of `genome`, or computes it with
+    /// `evaluate` and caches it. The cache isn't locked during `evaluate`.
+    fn get_or_evaluate<F>(&self, genome: &GeneticBotGenome, evaluate: F) -> usize
+    where
+        F: FnOnce() -> usize,
+    {
+        let key = Self::key(genome);
+        self.nb_lookups.fetch_add(1, Ordering::Relaxed);
+        if let Some(&fitness) = self.fitness_values.lock().unwrap().get(&key) {
+            self.nb_hits.fetch_add(1, Ordering::Relaxed);
+            return fitness;
+        }
+
+        let fitness = evaluate();
+        let mut fitness_values = self.fitness_values.lock().unwrap();
+        if fitness_values.len() >= Self::MAX_ENTRIES {
+            fitness_values.clear();
+        }
+        fitness_values.insert(key, fitness);
+        fitness
+    }
+
+    /// The ratio of the evaluations skipped thanks to the cache, in [0, 1].
+    pub fn hit_rate(&self) -> f64 {
+        let nb_lookups = self.nb_lookups.load(Ordering::Relaxed);
+        if nb_lookups == 0 {
+            return 0.;
+        }
+        self.nb_hits.load(Ordering::Relaxed) as f64 / nb_lookups as f64
+    }
+
+    pub fn len(&self) -> usize {
+        self.fitness_values.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// The fitness function of the co-evolution, see `coevolve_weights()`.
+#[derive(Clone, Debug)]
+pub struct CoevolutionFitnessCalc {
+    /// The best genomes of the other population.
+    opponents: Arc<RwLock<Vec<GeneticBotGenome>>>,
+}
+
+impl CoevolutionFitnessCalc {
+    /// The number of genomes of the other population to play against.
+    const NB_OPPONENTS: usize = 4;
+    const NB_MATCHES_PER_OPPONENT: usize = 5;
+}
+
+impl FitnessFunction<GeneticBotGenome, usize> for CoevolutionFitnessCalc {
+    fn fitness_of(&self, genome: &GeneticBotGenome) -> usize {
+        let opponents = self.opponents.read().unwrap().clone();
+        (0..opponents.len() * Self::NB_MATCHES_PER_OPPONENT)
+            .into_par_iter()
+            .map(|i| {
+                let opponent = &opponents[i / Self::NB_MATCHES_PER_OPPONENT];
+                let results = Game::with_seed(match_seed(genome, i))
                     .continue_simulation_if_known_winner(false)
+                    .end_on_entrapment(true)
+                    .max_steps(Game::MAX_UNWATCHED_STEPS)
                     .add_snake(0, Box::from(HeuristicBot::new(genome)))
-                    .add_snake(1, Box::from(HeuristicBot::default()))
+                    .add_snake(1, Box::from(HeuristicBot::new(opponent)))
                     .initialize()
                     .run_to_end();
 
@@ -108,7 +786,7 @@ impl FitnessFunction<GeneticBotGenome, usize> for WinRatioFitnessCalc {
     }
 
     fn highest_possible_fitness(&self) -> usize {
-        self.target_fitness
+        2 * Self::NB_OPPONENTS * Self::NB_MATCHES_PER_OPPONENT
     }
 
     fn lowest_possible_fitness(&self) -> usize {
@@ -118,7 +796,36 @@ impl FitnessFunction<GeneticBotGenome, usize> for WinRatioFitnessCalc {
 
 #[allow(dead_code)]
 pub fn learning() {
-    if let Some(learned_weights) = learn_weights() {
+    let mode = Select::with_theme(&*DIALOG_THEME)
+        .with_prompt("Which learning mode?")
+        .default(0)
+        .item("against the human-tuned bot")
+        .item("co-evolution of two populations")
+        .interact()
+        .unwrap_or(0);
+    let learned_weights = match mode {
+        0 => learn_weights(),
+        _ => coevolve_weights(&Parameters::default()).map(|(weights, _)| weights),
+    };
+
+    if let Some(learned_weights) = learned_weights {
+        let report = Gauntlet::standard().score(&HeuristicBot::factory(&learned_weights));
+        println!("Gauntlet:\n{}\n", report);
+
+        // Keep the genome for later, see `test_saved_weights()`
+        if Confirmation::with_theme(&*DIALOG_THEME)
+            .with_text("Do you want to save the found genome?")
+            .default(true)
+            .interact()
+            .unwrap_or(false)
+        {
+            let path = format!("best_genome_{}.json", timestamp());
+            match save_weights(Path::new(&path), &learned_weights) {
+                Ok(()) => println!("Best weights saved to {}.", path),
+                Err(e) => println!("{} {}", "Unable to save the weights:".red(), e),
+            }
+        }
+
         // Ask the user if he/she wants the play against the found genome
         if Confirmation::with_theme(&*DIALOG_THEME)
             .with_text("Do you want to test the found genome?")
@@ -132,84 +839,341 @@ pub fn learning() {
     }
 }
 
-/// Add a Ctrl+C handler (if the feature is enabled)
-///
-/// Returns: (handler_enabled, ctrlc_interrupted, learning_stopped)
-#[cfg(feature = "ctrlc")]
-fn install_ctrlc_handler() -> (Arc<AtomicBool>, Arc<AtomicBool>, Arc<AtomicBool>) {
-    let handler_enabled = Arc::new(AtomicBool::new(true));
-    let handler_enabled_inner = handler_enabled.clone();
-    let ctrlc_interrupted = Arc::new(AtomicBool::new(false));
-    let ctrlc_interrupted_inner = ctrlc_interrupted.clone();
-    let learning_stopped = Arc::new(AtomicBool::new(false));
-    let learning_stopped_inner = learning_stopped.clone();
-    ctrlc::set_handler(move || {
-        if handler_enabled_inner.load(Ordering::SeqCst) {
-            ctrlc_interrupted_inner.store(true, Ordering::SeqCst);
-
-            // Ask the reason of the Ctrl+C
-            let ctrlc_choice = Select::with_theme(&*DIALOG_THEME)
-                .with_prompt(" Ctrl+C received, what do you want to do?")
-                .default(0)
-                .item("oops, nothing")
-                .item("stop the learning")
-                .item("quit")
-                .interact()
-                .unwrap_or(2);
-
-            // Execute the action
-            match ctrlc_choice {
-                0 => {}
-                1 => {
-                    learning_stopped_inner.store(true, Ordering::SeqCst);
-                }
-                2 => {
-                    ::std::process::exit(0);
-                }
-                _ => unreachable!(),
-            }
-
-            ctrlc_interrupted_inner.store(false, Ordering::SeqCst);
+/// Asks for a genome file, e.g. saved at the end of `learning()`, and
+/// tests its genome like a freshly learned one.
+pub fn test_saved_weights() {
+    let path: String = match Input::with_theme(&*DIALOG_THEME)
+        .with_prompt("Path of the genome file")
+        .interact()
+    {
+        Ok(path) => path,
+        Err(_) => return,
+    };
+    let weights = match load_weights(Path::new(&path)) {
+        Ok(weights) => weights,
+        Err(e) => {
+            println!("{}", e.to_string().red());
+            return;
         }
-    })
-    .unwrap_or_else(|_| eprintln!("Error setting Ctrl-C handler."));
+    };
+    println!("Loaded: {}\n", PrettyWeights(&weights));
 
-    (handler_enabled, ctrlc_interrupted, learning_stopped)
+    let report = Gauntlet::standard().score(&HeuristicBot::factory(&weights));
+    println!("Gauntlet:\n{}\n", report);
+    test_weights(weights);
 }
 
-/// No-op (if the feature is disabled)
-///
-/// Returns: (handler_enabled, ctrlc_interrupted, learning_stopped)
-#[cfg(not(feature = "ctrlc"))]
-fn install_ctrlc_handler() -> (Arc<AtomicBool>, Arc<AtomicBool>, Arc<AtomicBool>) {
-    let handler_enabled = Arc::new(AtomicBool::new(false));
-    let ctrlc_interrupted = Arc::new(AtomicBool::new(false));
-    let learning_stopped = Arc::new(AtomicBool::new(false));
+/// Asks the user what to do after a Ctrl+C.
+/// Returns whether the learning must be stopped.
+fn ask_stop_learning() -> bool {
+    let ctrlc_choice = Select::with_theme(&*DIALOG_THEME)
+        .with_prompt(" Ctrl+C received, what do you want to do?")
+        .default(0)
+        .item("oops, nothing")
+        .item("stop the learning")
+        .item("quit")
+        .interact()
+        .unwrap_or(2);
 
-    (handler_enabled, ctrlc_interrupted, learning_stopped)
+    match ctrlc_choice {
+        0 => false,
+        1 => true,
+        2 => ::std::process::exit(0),
+        _ => unreachable!(),
+    }
 }
 
 fn learn_weights() -> Option<Weights> {
-    let params = Parameters::default();
+    let checkpoint = prompt_checkpoint();
+    let mut params = match &checkpoint {
+        Some(checkpoint) => checkpoint.params.clone(),
+        None => default_config(),
+    };
 
-    // Create the initial population
-    let initial_population: Population<GeneticBotGenome> = build_population()
-        .with_genome_builder(ValueEncodedGenomeBuilder::new(
-            GENOME_LENGTH,
-            GENOME_MIN_VALUE,
-            GENOME_MAX_VALUE,
-        ))
-        .of_size(params.population_size)
-        .uniform_at_random();
-
-    // Ask the target fitness
-    const DEFAULT_TARGET_FITNESS: usize = (WinRatioFitnessCalc::NB_MATCHES as f32 * 1.8) as usize;
+    // Ask the number of matches per evaluation, and then the target fitness.
+    // A resumed learning keeps its matches, so that the fitness values stay
+    // comparable.
+    if checkpoint.is_none() {
+        params.fitness = match Select::with_theme(&*DIALOG_THEME)
+            .with_prompt("Against whom are the genomes evaluated?")
+            .default(0)
+            .item("the human-tuned bot")
+            .item("the best genomes of the previous generation (self-play)")
+            .item("nobody, the genomes have to survive alone")
+            .interact()
+            .unwrap_or(0)
+        {
+            0 => FitnessKind::Static,
+            1 => FitnessKind::SelfPlay,
+            _ => FitnessKind::Survival,
+        };
+        params.nb_matches = Input::with_theme(&*DIALOG_THEME)
+            .with_prompt("Matches per evaluation")
+            .default(params.nb_matches)
+            .interact()
+            .unwrap_or(params.nb_matches);
+    }
     let target_fitness = Input::with_theme(&*DIALOG_THEME)
-        .with_prompt("Target fitness")
-        .default(DEFAULT_TARGET_FITNESS)
+        .with_prompt(&format!("Target fitness (max. {})", params.max_fitness()))
+        .default(params.default_target_fitness())
         .interact()
-        .unwrap_or(DEFAULT_TARGET_FITNESS);
-    let fitness_calc = WinRatioFitnessCalc::new(target_fitness);
+        .unwrap_or_else(|_| params.default_target_fitness());
+
+    run_learning(&params, target_fitness, Path::new("."), true, checkpoint)
+}
+
+/// Runs the learning without any prompt, e.g. for batch experiments. The
+/// stats and the best weights are written in `out_dir`, which is created if
+/// needed. On Ctrl+C, the learning stops with the best weights so far.
+/// With a `checkpoint`, the learning continues from its population.
+pub fn learn_headless(
+    params: &Parameters,
+    target_fitness: usize,
+    out_dir: &Path,
+    checkpoint: Option<Checkpoint>,
+) -> io::Result<Option<Weights>> {
+    fs::create_dir_all(out_dir)?;
+    let best_weights = run_learning(params, target_fitness, out_dir, false, checkpoint);
+
+    if let Some(weights) = &best_weights {
+        let path = out_dir.join(format!("best_genome_{}.json", timestamp()));
+        save_weights(&path, weights)?;
+        println!("Best weights saved to {}.", path.display());
+    }
+    Ok(best_weights)
+}
+
+/// The current date, to name the output files.
+fn timestamp() -> String {
+    Local::now().format("%Y-%m-%d_%H:%M:%S").to_string()
+}
+
+/// Writes the weights of a genome as a JSON array, see `load_weights()`.
+pub fn save_weights(path: &Path, weights: &Weights) -> io::Result<()> {
+    let json = serde_json::to_string_pretty(weights)?;
+    fs::write(path, json)
+}
+
+/// Reads the weights written by `save_weights()`, which must be
+/// `NB_WEIGHTS`.
+pub fn load_weights(path: &Path) -> io::Result<Weights> {
+    let weights: Weights = serde_json::from_str(&fs::read_to_string(path)?)?;
+    if weights.len() != NB_WEIGHTS {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "The genome has {} weights instead of {}.",
+                weights.len(),
+                NB_WEIGHTS
+            ),
+        ));
+    }
+    Ok(weights)
+}
+
+/// A population during the learning, to resume it, see `save_checkpoint()`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Checkpoint {
+    /// The number of generations done.
+    pub generation: u64,
+    pub params: Parameters,
+    pub population: Vec<GeneticBotGenome>,
+}
+
+/// Why a checkpoint can't be read by `load_checkpoint()`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum CheckpointError {
+    /// The file isn't a checkpoint of this version, e.g. with another
+    /// number of weights.
+    NotACheckpoint,
+    /// The line, starting at 1, can't be read.
+    InvalidLine(usize),
+    Weights(ParseWeightsError),
+    /// The checkpoint file can't be read.
+    Io(String),
+}
+
+impl fmt::Display for CheckpointError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CheckpointError::NotACheckpoint => {
+                write!(f, "The file isn't a checkpoint of this version.")
+            }
+            CheckpointError::InvalidLine(line) => {
+                write!(f, "The line {} of the checkpoint is invalid.", line)
+            }
+            CheckpointError::Weights(error) => write!(f, "{}", error),
+            CheckpointError::Io(error) => write!(f, "Unable to read the checkpoint: {}", error),
+        }
+    }
+}
+
+/// The first line of the checkpoints, followed by the number of weights,
+/// like the weights files.
+const CHECKPOINT_HEADER: &str = "# genetic-snake-rs checkpoint:";
+
+/// Writes the generation, the parameters and then one genome per line, as
+/// `key value` lines after a header. See `load_checkpoint()`.
+pub fn save_checkpoint(path: &Path, checkpoint: &Checkpoint) -> io::Result<()> {
+    let params = &checkpoint.params;
+    let optional = |value: Option<u64>| value.map_or("none".to_string(), |v| v.to_string());
+    let mut content = format!("{} {}\n", CHECKPOINT_HEADER, NB_WEIGHTS);
+    for (key, value) in &[
+        ("generation", checkpoint.generation.to_string()),
+        ("population_size", params.population_size.to_string()),
+        ("generation_limit", params.generation_limit.to_string()),
+        (
+            "num_individuals_per_parents",
+            params.num_individuals_per_parents.to_string(),
+        ),
+        ("selection_ratio", params.selection_ratio.to_string()),
+        (
+            "num_crossover_points",
+            params.num_crossover_points.to_string(),
+        ),
+        ("mutation_rate", params.mutation_rate.to_string()),
+        ("mutation_range", params.mutation_range.to_string()),
+        ("mutation_precision", params.mutation_precision.to_string()),
+        ("reinsertion_ratio", params.reinsertion_ratio.to_string()),
+        ("crossover", params.crossover.to_string()),
+        ("mutator", params.mutator.to_string()),
+        ("nb_matches", params.nb_matches.to_string()),
+        ("win_score", params.win_score.to_string()),
+        ("draw_score", params.draw_score.to_string()),
+        ("loss_score", params.loss_score.to_string()),
+        ("mirrored_matches", params.mirrored_matches.to_string()),
+        ("render_every", optional(params.render_every)),
+        ("cache_fitness", params.cache_fitness.to_string()),
+        ("checkpoint_every", optional(params.checkpoint_every)),
+        ("fitness", params.fitness.to_string()),
+    ] {
+        content.push_str(&format!("{} {}\n", key, value));
+    }
+    for genome in &checkpoint.population {
+        let weights: Vec<String> = genome.iter().map(|weight| weight.to_string()).collect();
+        content.push_str(&format!("genome {}\n", weights.join(", ")));
+    }
+    fs::write(path, content)
+}
+
+/// Reads a checkpoint written by `save_checkpoint()`.
+pub fn load_checkpoint(path: &Path) -> Result<Checkpoint, CheckpointError> {
+    let content = fs::read_to_string(path).map_err(|e| CheckpointError::Io(e.to_string()))?;
+    let mut lines = content.lines();
+    if lines.next() != Some(&format!("{} {}", CHECKPOINT_HEADER, NB_WEIGHTS)) {
+        return Err(CheckpointError::NotACheckpoint);
+    }
+
+    let mut checkpoint = Checkpoint {
+        generation: 0,
+        params: Parameters::default(),
+        population: vec![],
+    };
+    for (i, line) in lines.enumerate() {
+        let line_nb = i + 2;
+        let (key, value) = match line.find(' ') {
+            Some(space) => (&line[..space], line[space..].trim()),
+            None => return Err(CheckpointError::InvalidLine(line_nb)),
+        };
+        let invalid = || CheckpointError::InvalidLine(line_nb);
+        match key {
+            "generation" => checkpoint.generation = value.parse().map_err(|_| invalid())?,
+            "genome" => checkpoint
+                .population
+                .push(parse_weights(value).map_err(CheckpointError::Weights)?),
+            _ => checkpoint.params.set(key, value).ok_or_else(invalid)?,
+        }
+    }
+    Ok(checkpoint)
+}
+
+/// The config file read by the interactive learning, if it exists.
+pub const DEFAULT_CONFIG_FILE: &str = "ga_config.toml";
+
+/// The parameters of `DEFAULT_CONFIG_FILE`, or the default ones if there's
+/// no such file.
+fn default_config() -> Parameters {
+    let path = Path::new(DEFAULT_CONFIG_FILE);
+    if !path.exists() {
+        return Parameters::default();
+    }
+    match Parameters::from_file(path) {
+        Ok(params) => {
+            println!("Parameters read from {}.", DEFAULT_CONFIG_FILE);
+            params
+        }
+        Err(e) => {
+            println!("{} {}", "Default parameters used:".red(), e);
+            Parameters::default()
+        }
+    }
+}
+
+/// Asks whether to resume the learning from a checkpoint, and reads it.
+fn prompt_checkpoint() -> Option<Checkpoint> {
+    loop {
+        let resume = Select::with_theme(&*DIALOG_THEME)
+            .with_prompt("Start a new learning or resume one?")
+            .default(0)
+            .item("start fresh")
+            .item("resume from a checkpoint")
+            .interact()
+            .unwrap_or(0);
+        if resume == 0 {
+            return None;
+        }
+
+        let path: String = Input::with_theme(&*DIALOG_THEME)
+            .with_prompt("Path of the checkpoint")
+            .default("checkpoint.txt".to_string())
+            .interact()
+            .ok()?;
+        match load_checkpoint(Path::new(&path)) {
+            Ok(checkpoint) => return Some(checkpoint),
+            Err(e) => println!("{}", e.to_string().red()),
+        }
+    }
+}
+
+/// Learns weights until `target_fitness` or the generation limit is reached.
+/// If `interactive`, the user is asked what to do on Ctrl+C.
+///
+/// The population is saved in `out_dir/checkpoint.txt` every
+/// `checkpoint_every` generations and when the learning is stopped. The
+/// learning starts from the population of `checkpoint`, if any, and counts
+/// the generations from it; its parameters aren't used, `params` are.
+fn run_learning(
+    params: &Parameters,
+    target_fitness: usize,
+    out_dir: &Path,
+    interactive: bool,
+    checkpoint: Option<Checkpoint>,
+) -> Option<Weights> {
+    let (first_generation, initial_population) = match checkpoint {
+        Some(checkpoint) => (
+            checkpoint.generation,
+            Population::with_individuals(checkpoint.population),
+        ),
+        None => (0, random_population(params.population_size)),
+    };
+    let checkpoint_path = out_dir.join("checkpoint.txt");
+    let write_checkpoint = |generation: u64, population: &[GeneticBotGenome]| {
+        let checkpoint = Checkpoint {
+            generation,
+            params: params.clone(),
+            population: population.to_vec(),
+        };
+        match save_checkpoint(&checkpoint_path, &checkpoint) {
+            Ok(()) => println!("Checkpoint saved to {}.", checkpoint_path.display()),
+            Err(e) => eprintln!("Unable to save the checkpoint: {}", e),
+        }
+    };
+    let fitness_calc = Fitness::new(params);
+    let (fitness_cache, self_play) = match &fitness_calc {
+        Fitness::Static(calc) => (calc.cache.clone(), None),
+        Fitness::SelfPlay(calc) => (None, Some(calc.clone())),
+        Fitness::Survival(_) => (None, None),
+    };
 
     // Configure the simulation
     let mut snake_simulation = simulate(
@@ -219,20 +1183,8 @@ fn learn_weights() -> Option<Weights> {
                 params.selection_ratio,
                 params.num_individuals_per_parents,
             ))
-            .with_crossover(MultiPointCrossBreeder::new(params.num_crossover_points))
-            // .with_crossover(DiscreteCrossBreeder::new())
-            // .with_mutation(RandomValueMutator::new(
-            //     params.mutation_rate,
-            //     GENOME_MIN_VALUE,
-            //     GENOME_MAX_VALUE,
-            // ))
-            .with_mutation(BreederValueMutator::new(
-                params.mutation_rate,
-                params.mutation_range,
-                params.mutation_precision,
-                GENOME_MIN_VALUE * 10_f64,
-                GENOME_MAX_VALUE * 10_f64,
-            ))
+            .with_crossover(params.crossover_op())
+            .with_mutation(params.mutation_op())
             .with_reinsertion(ElitistReinserter::new(
                 fitness_calc,
                 true,
@@ -243,7 +1195,12 @@ fn learn_weights() -> Option<Weights> {
     )
     .until(or(
         FitnessLimit::new(target_fitness),
-        GenerationLimit::new(params.generation_limit),
+        GenerationLimit::new(
+            params
+                .generation_limit
+                .saturating_sub(first_generation)
+                .max(1),
+        ),
     ))
     .build();
 
@@ -257,15 +1214,14 @@ fn learn_weights() -> Option<Weights> {
             .progress_chars("#>-"),
     );
 
-    // Add a Ctrl+C handler (if the feature is enabled)
-    let (handler_enabled, ctrlc_interrupted, learning_stopped) = install_ctrlc_handler();
+    // Catch Ctrl+C to be able to stop the learning
+    let interrupts = catch_interrupts();
 
     // Open a file to dump the stats
-    let dt = Local::now();
     let mut stats_file = OpenOptions::new()
         .create(true)
         .append(true)
-        .open(format!("stats_dump_{}.txt", dt.format("%Y-%m-%d_%H:%M:%S")));
+        .open(out_dir.join(format!("stats_dump_{}.txt", timestamp())));
     if let Ok(ref mut file) = stats_file {
         writeln!(file, "[")
             .map(|_| ())
@@ -276,44 +1232,73 @@ fn learn_weights() -> Option<Weights> {
 
     // Run the learning
     let mut best_weights = None;
-    while !learning_stopped.load(Ordering::SeqCst) {
+    loop {
         let result = snake_simulation.step();
         match result {
             Ok(SimResult::Intermediate(step)) => {
-                if !ctrlc_interrupted.load(Ordering::SeqCst) {
-                    let evaluated_population = step.result.evaluated_population;
-                    let best_solution = step.result.best_solution;
+                let generation = first_generation + step.iteration;
+                let evaluated_population = step.result.evaluated_population;
+                let best_solution = step.result.best_solution;
+                println!(
+                    "{}\n\
+                     --> population_size: {}, average_fitness: {}, best fitness: {}\n\
+                     --> duration: {}, processing_time: {}\n\
+                     {}\n\n",
+                    format!("[Generation {}]", generation).yellow(),
+                    evaluated_population.individuals().len(),
+                    evaluated_population.average_fitness(),
+                    best_solution.solution.fitness,
+                    step.duration.fmt(),
+                    step.processing_time.fmt(),
+                    PrettyWeights(&best_solution.solution.genome)
+                );
+                max_fitness_bar.set_position(best_solution.solution.fitness as u64);
+                if let Some(cache) = &fitness_cache {
                     println!(
-                        "{}\n\
-                         --> population_size: {}, average_fitness: {}, best fitness: {}\n\
-                         --> duration: {}, processing_time: {}\n\
-                         {}\n\n",
-                        format!("[Generation {}]", step.iteration).yellow(),
-                        evaluated_population.individuals().len(),
-                        evaluated_population.average_fitness(),
-                        best_solution.solution.fitness,
-                        step.duration.fmt(),
-                        step.processing_time.fmt(),
-                        PrettyWeights(&best_solution.solution.genome)
+                        "--> fitness cache: {:.1}% hits, {} genomes\n",
+                        100. * cache.hit_rate(),
+                        cache.len()
                     );
-                    max_fitness_bar.set_position(best_solution.solution.fitness as u64);
-
-                    if let Ok(ref mut file) = stats_file {
-                        let mut line = String::with_capacity(30 + 10 * params.population_size);
+                }
 
-                        let g = step.iteration;
-                        for f in evaluated_population.fitness_values().iter() {
-                            line.push_str(&format!("({},{}),", g, f));
-                        }
-                        if let Err(e) = writeln!(file, "{}", line) {
-                            eprintln!("Couldn't dump stats to file: {}", e);
+                #[cfg(feature = "render")]
+                {
+                    if let Some(every) = params.render_every {
+                        if generation % every == 0 {
+                            render_generation(&best_solution.solution.genome, generation, out_dir);
                         }
+                    }
+                }
+
+                if let Some(self_play) = &self_play {
+                    self_play.update_opponents(
+                        &evaluated_population.individuals(),
+                        evaluated_population.fitness_values(),
+                    );
+                }
 
-                        file.sync_all().unwrap();
+                if let Ok(ref mut file) = stats_file {
+                    let mut line = String::with_capacity(30 + 10 * params.population_size);
+
+                    let g = generation;
+                    for f in evaluated_population.fitness_values().iter() {
+                        line.push_str(&format!("({},{}),", g, f));
+                    }
+                    if let Err(e) = writeln!(file, "{}", line) {
+                        eprintln!("Couldn't dump stats to file: {}", e);
                     }
 
-                    if learning_stopped.load(Ordering::SeqCst) {
-                        best_weights = Some(best_solution.solution.genome.clone());
+                    file.sync_all().unwrap();
+                }
+
+                if interrupts.take() && (!interactive || ask_stop_learning()) {
+                    write_checkpoint(generation, &evaluated_population.individuals());
+                    best_weights = Some(best_solution.solution.genome.clone());
+                    break;
+                }
+                if let Some(every) = params.checkpoint_every {
+                    if generation % every == 0 {
+                        write_checkpoint(generation, &evaluated_population.individuals());
                     }
                 }
             }
@@ -328,8 +1313,8 @@ fn learn_weights() -> Option<Weights> {
                      --> population_size: {}, average_fitness: {}, best fitness: {}\n\
                      --> duration: {}, processing_time: {}\n\
                      {}\n\n",
-                    format!("[Generation {}]", step.iteration).yellow(),
-                    format!("{}", best_solution.generation).yellow(),
+                    format!("[Generation {}]", first_generation + step.iteration).yellow(),
+                    format!("{}", first_generation + best_solution.generation).yellow(),
                     stop_reason.green(),
                     evaluated_population.individuals().len(),
                     evaluated_population.average_fitness(),
@@ -350,9 +1335,8 @@ fn learn_weights() -> Option<Weights> {
         }
     }
 
-    // Disable the Ctrl+C handler
-    // TODO: Add a global handler
-    handler_enabled.store(false, Ordering::SeqCst);
+    // Ctrl+C quits again
+    drop(interrupts);
 
     // Add the closing bracket to the data (Python format)
     if let Ok(ref mut file) = stats_file {
@@ -364,6 +1348,138 @@ fn learn_weights() -> Option<Weights> {
     best_weights
 }
 
+/// Plays a seeded game of `genome` against the human-tuned bot, written as
+/// `out_dir/frames/genNNNN_stepNNNN.png`.
+#[cfg(feature = "render")]
+fn render_generation(genome: &GeneticBotGenome, generation: u64, out_dir: &Path) {
+    let mut game = Game::with_seed(0);
+    game.continue_simulation_if_known_winner(false)
+        .max_steps(Game::MAX_UNWATCHED_STEPS)
+        .add_snake(0, Box::from(HeuristicBot::new(genome)))
+        .add_snake(1, Box::from(HeuristicBot::default()))
+        .initialize();
+    let prefix = format!("gen{:04}", generation);
+    if let Err(e) = crate::render::render_game(&mut game, &out_dir.join("frames"), &prefix) {
+        eprintln!("Unable to render generation {}: {}", generation, e);
+    }
+}
+
+/// Creates a population of random genomes.
+fn random_population(size: usize) -> Population<GeneticBotGenome> {
+    build_population()
+        .with_genome_builder(ValueEncodedGenomeBuilder::new(
+            GENOME_LENGTH,
+            GENOME_MIN_VALUE,
+            GENOME_MAX_VALUE,
+        ))
+        .of_size(size)
+        .uniform_at_random()
+}
+
+/// Returns the `n` fittest genomes of a population.
+fn fittest(genomes: &[GeneticBotGenome], fitness_values: &[usize], n: usize) -> Vec<Weights> {
+    let mut indices: Vec<usize> = (0..genomes.len()).collect();
+    indices.sort_by_key(|&i| std::cmp::Reverse(fitness_values[i]));
+    indices
+        .iter()
+        .take(n)
+        .map(|&i| genomes[i].clone())
+        .collect()
+}
+
+/// Evolves two populations against each other, predator/prey style: the
+/// fitness of a genome is its results against the fittest genomes of the
+/// other population at the previous generation, the human-tuned bot being
+/// the first opponent. The fitness is relative, so the learning only stops
+/// at the generation limit or on Ctrl+C.
+/// Returns the best genome of each population at the last generation.
+fn coevolve_weights(params: &Parameters) -> Option<(Weights, Weights)> {
+    let opponents = || {
+        Arc::new(RwLock::new(vec![
+            GOOD_WEIGHTS.clone();
+            CoevolutionFitnessCalc::NB_OPPONENTS
+        ]))
+    };
+    let (opponents_a, opponents_b) = (opponents(), opponents());
+
+    // Configure the simulations, which only differ by their opponents
+    let build_simulation = |opponents: &Arc<RwLock<Vec<GeneticBotGenome>>>| {
+        let fitness_calc = CoevolutionFitnessCalc {
+            opponents: opponents.clone(),
+        };
+        simulate(
+            genetic_algorithm()
+                .with_evaluation(fitness_calc.clone())
+                .with_selection(MaximizeSelector::new(
+                    params.selection_ratio,
+                    params.num_individuals_per_parents,
+                ))
+                .with_crossover(params.crossover_op())
+                .with_mutation(params.mutation_op())
+                .with_reinsertion(ElitistReinserter::new(
+                    fitness_calc,
+                    true,
+                    params.reinsertion_ratio,
+                ))
+                .with_initial_population(random_population(params.population_size))
+                .build(),
+        )
+        .until(GenerationLimit::new(params.generation_limit))
+        .build()
+    };
+    let mut simulation_a = build_simulation(&opponents_a);
+    let mut simulation_b = build_simulation(&opponents_b);
+
+    // Catch Ctrl+C to be able to stop the learning
+    let interrupts = catch_interrupts();
+
+    loop {
+        let mut finished = false;
+        let mut bests = vec![];
+        for (name, simulation, other_opponents) in &mut [
+            ("A", &mut simulation_a, &opponents_b),
+            ("B", &mut simulation_b, &opponents_a),
+        ] {
+            let state = match simulation.step() {
+                Ok(SimResult::Intermediate(state)) => state,
+                Ok(SimResult::Final(state, _, _, _)) => {
+                    finished = true;
+                    state
+                }
+                Err(error) => {
+                    println!("{:?}", error);
+                    return None;
+                }
+            };
+
+            let population = &state.result.evaluated_population;
+            let best = fittest(
+                &population.individuals(),
+                population.fitness_values(),
+                CoevolutionFitnessCalc::NB_OPPONENTS,
+            );
+            println!(
+                "{} population {}: average_fitness: {}, highest fitness: {}\n{}\n",
+                format!("[Generation {}]", state.iteration).yellow(),
+                name,
+                population.average_fitness(),
+                population.highest_fitness(),
+                PrettyWeights(&best[0])
+            );
+            bests.push(best[0].clone());
+
+            // The fittest genomes become the opponents of the other population
+            *other_opponents.write().unwrap() = best;
+        }
+
+        if finished || (interrupts.take() && ask_stop_learning()) {
+            let best_b = bests.pop().unwrap();
+            let best_a = bests.pop().unwrap();
+            return Some((best_a, best_b));
+        }
+    }
+}
+
 fn test_weights(weights: Weights) {
     let mut bot_choice = 0;
     loop {
@@ -381,8 +1497,9 @@ fn test_weights(weights: Weights) {
             .unwrap_or(5);
 
         // Create the game
-        let mut game = Game::new();
+        let mut game = Game::default();
         game.continue_simulation_if_known_winner(false)
+            .interruptible(true)
             .add_snake(0, Box::from(HeuristicBot::new(&weights)));
 
         // Add the bot corresponding to the user's choice
@@ -394,11 +1511,7 @@ fn test_weights(weights: Weights) {
                 game.add_snake(1, Box::from(HeuristicBot::default()));
             }
             4 => {
-                println!(
-                    "You play the {} and start toward the {}.",
-                    "red snake".red(),
-                    "NORTH".yellow()
-                );
+                println!("You play the {}.", "red snake".red());
                 game.add_snake(1, Box::from(InteractiveBot {}));
             }
             _ => {
@@ -412,6 +1525,7 @@ fn test_weights(weights: Weights) {
         }
 
         // Run the game until its end
+        let seed = game.seed();
         let results = game
             .initialize()
             .print()
@@ -419,42 +1533,188 @@ fn test_weights(weights: Weights) {
             .run_to_end();
 
         // Show the results
-        print!("\n  => ");
-        if let Some(Winner(winner_id)) = results.winner {
-            if winner_id == 1 {
-                match bot_choice {
-                    0 | 2 => println!(
-                        "{}",
-                        format!("RandomBot won in {} moves!", results.steps).red()
-                    ),
-                    1 | 3 => println!(
-                        "{}",
-                        format!("HeuristicBot won in {} moves!", results.steps).red()
-                    ),
-                    4 => println!("{}", format!("You won in {} moves!", results.steps).green()),
-                    _ => unreachable!(),
-                }
-            } else if bot_choice == 4 {
-                // Human player, different message
-                println!(
-                    "{}",
-                    format!("The learned bot beat you in {} moves!", results.steps).red()
-                );
-            } else {
-                println!(
-                    "{}",
-                    format!("The learned bot won in {} moves!", results.steps).green()
-                );
-            }
-        } else {
-            println!(
-                "{}",
-                format!("It's a draw! ({} moves)", results.steps).yellow()
-            );
-        }
-        println!();
+        println!("\n{}", results);
+        println!("  (player 0 is the learned bot, seed {})\n", seed);
 
         // Reshow the weights, for convenience
         println!("You played against: {}\n", PrettyWeights(&weights));
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parameters_are_read_from_config_files() {
+        let path = std::env::temp_dir().join("genetic-snake-rs-config-test.toml");
+        fs::write(
+            &path,
+            "# Bigger population\n\
+             population_size = 400\n\
+             \n\
+             mutation_rate = 0.1  # per weight\n\
+             crossover = \"discrete\"\n\
+             render_every = \"none\"\n",
+        )
+        .unwrap();
+        assert_eq!(
+            Parameters::from_file(&path),
+            Ok(Parameters {
+                population_size: 400,
+                mutation_rate: 0.1,
+                crossover: CrossoverKind::Discrete,
+                ..Parameters::default()
+            })
+        );
+
+        fs::write(&path, "selection_ratio = 1.5\n").unwrap();
+        assert_eq!(
+            Parameters::from_file(&path),
+            Err(ConfigError::NotARatio {
+                field: "selection_ratio",
+                value: 1.5
+            })
+        );
+        fs::write(&path, "mutation_rate = 0.1\npopulation = 400\n").unwrap();
+        assert_eq!(
+            Parameters::from_file(&path),
+            Err(ConfigError::InvalidLine(2))
+        );
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn checkpoints_round_trip() {
+        let checkpoint = Checkpoint {
+            generation: 42,
+            params: Parameters {
+                crossover: CrossoverKind::Discrete,
+                mutation_rate: 0.125,
+                render_every: Some(10),
+                checkpoint_every: None,
+                ..Parameters::default()
+            },
+            population: vec![GOOD_WEIGHTS.clone(), vec![-0.3; NB_WEIGHTS]],
+        };
+        let path = std::env::temp_dir().join("genetic-snake-rs-checkpoint-test.txt");
+        save_checkpoint(&path, &checkpoint).unwrap();
+        assert_eq!(load_checkpoint(&path), Ok(checkpoint));
+
+        fs::write(&path, "# genetic-snake-rs weights: 3\n[1, 2, 3]\n").unwrap();
+        assert_eq!(load_checkpoint(&path), Err(CheckpointError::NotACheckpoint));
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn genomes_round_trip_through_json() {
+        let path = std::env::temp_dir().join("genetic-snake-rs-genome-test.json");
+        save_weights(&path, &GOOD_WEIGHTS).unwrap();
+        assert_eq!(load_weights(&path).unwrap(), *GOOD_WEIGHTS);
+
+        fs::write(&path, "[0.5, -0.25]").unwrap();
+        let error = load_weights(&path).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        fs::write(&path, "not json").unwrap();
+        assert!(load_weights(&path).is_err());
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn mirrored_matches_cancel_the_side_advantage() {
+        // Against itself, the genome wins on one side what it loses on the
+        // other side, or it's a draw on both sides
+        let params = Parameters {
+            nb_matches: 4,
+            mirrored_matches: true,
+            ..Parameters::default()
+        };
+        let fitness_calc = WinRatioFitnessCalc::new(&params);
+        assert_eq!(fitness_calc.fitness_of(&GOOD_WEIGHTS), 4);
+    }
+
+    #[test]
+    fn self_play_opponents_come_from_the_previous_generation() {
+        let params = Parameters {
+            nb_matches: 4,
+            mirrored_matches: true,
+            fitness: FitnessKind::SelfPlay,
+            ..Parameters::default()
+        };
+        let fitness_calc = SelfPlayFitnessCalc::new(&params);
+        let genome = vec![0.5; NB_WEIGHTS];
+        let weak_genome = vec![-0.5; NB_WEIGHTS];
+
+        // The opponents are shared by the clones, the fittest first
+        fitness_calc
+            .clone()
+            .update_opponents(&[weak_genome.clone(), genome.clone()], &[0, 10]);
+        assert_eq!(
+            *fitness_calc.opponents.read().unwrap(),
+            vec![genome.clone(), weak_genome]
+        );
+
+        // Against itself, like `mirrored_matches_cancel_the_side_advantage`
+        fitness_calc.update_opponents(std::slice::from_ref(&genome), &[10]);
+        assert_eq!(fitness_calc.fitness_of(&genome), 4);
+    }
+
+    #[test]
+    fn survival_fitness_counts_the_steps_survived() {
+        let params = Parameters {
+            nb_matches: 3,
+            fitness: FitnessKind::Survival,
+            ..Parameters::default()
+        };
+        let fitness_calc = SurvivalFitnessCalc::new(&params);
+        assert_eq!(
+            fitness_calc.highest_possible_fitness(),
+            params.max_fitness()
+        );
+
+        // Going where there is the least room is short-lived
+        let suicidal = vec![-1.; NB_WEIGHTS];
+        let fitness = fitness_calc.fitness_of(&GOOD_WEIGHTS);
+        assert!(fitness > fitness_calc.fitness_of(&suicidal));
+        assert!(fitness <= params.max_fitness());
+    }
+
+    #[test]
+    fn genomes_always_get_the_same_fitness() {
+        let params = Parameters {
+            nb_matches: 6,
+            ..Parameters::default()
+        };
+        let fitness_calc = WinRatioFitnessCalc::new(&params);
+        let genome = vec![0.3; NB_WEIGHTS];
+        assert_eq!(
+            fitness_calc.fitness_of(&genome),
+            fitness_calc.fitness_of(&genome)
+        );
+        assert_ne!(match_seed(&genome, 0), match_seed(&genome, 1));
+        assert_ne!(match_seed(&genome, 0), match_seed(&GOOD_WEIGHTS, 0));
+    }
+
+    #[test]
+    fn cached_genomes_are_evaluated_once() {
+        let params = Parameters {
+            nb_matches: 2,
+            cache_fitness: true,
+            ..Parameters::default()
+        };
+        let fitness_calc = WinRatioFitnessCalc::new(&params);
+        let clone = fitness_calc.clone();
+
+        let fitness = fitness_calc.fitness_of(&GOOD_WEIGHTS);
+        let mut nearly_the_same = GOOD_WEIGHTS.clone();
+        nearly_the_same[0] += 1e-9;
+        assert_eq!(clone.fitness_of(&nearly_the_same), fitness);
+
+        let cache = fitness_calc.cache().unwrap();
+        assert_eq!(cache.len(), 1);
+        assert_eq!(cache.hit_rate(), 0.5);
+        assert!(WinRatioFitnessCalc::new(&Parameters::default())
+            .cache()
+            .is_none());
+    }
+}
@@ -0,0 +1,43 @@
+#![allow(unused_imports)]
+
+//! Learning to play Snake with Genetic Algorithms.
+//!
+//! The game engine and the bots live in this library, so that they can be
+//! used by the binary and by the benchmarks.
+
+#[macro_use]
+extern crate lazy_static;
+
+use console::Style;
+use dialoguer::theme::ColorfulTheme;
+
+pub mod astar_bot;
+pub mod elo;
+pub mod floodfill_bot;
+pub mod game_engine;
+pub mod gauntlet;
+pub mod heuristic_bot;
+pub mod interactive_bot;
+pub mod interrupt;
+pub mod learning;
+pub mod mcts_bot;
+pub mod minimax_bot;
+pub mod random_bot;
+#[cfg(feature = "render")]
+pub mod render;
+pub mod simulation;
+pub mod spectator;
+pub mod tournament;
+
+lazy_static! {
+    /// Global dialog theme
+    pub static ref DIALOG_THEME: ColorfulTheme = {
+        ColorfulTheme {
+            values_style: Style::new().yellow().dim(),
+            indicator_style: Style::new().yellow().bold(),
+            yes_style: Style::new().yellow().dim(),
+            no_style: Style::new().yellow().dim(),
+            ..ColorfulTheme::default()
+        }
+    };
+}
@@ -4,40 +4,51 @@
 extern crate lazy_static;
 
 use std::{
-    thread,
+    env, fmt,
+    path::PathBuf,
+    process, thread,
     time::{Duration, Instant},
 };
 
-use console::Style;
-use dialoguer::{theme::ColorfulTheme, Confirmation, Input, Select};
+use colored::Colorize;
+use dialoguer::{Confirmation, Input, Select};
+use rand::prelude::*;
 
-mod bench_tests;
-mod game_engine;
-mod heuristic_bot;
-mod interactive_bot;
-mod learning;
-mod random_bot;
+use genetic_snake_rs::astar_bot::AStarBot;
+use genetic_snake_rs::floodfill_bot::FloodFillBot;
+use genetic_snake_rs::game_engine::{
+    BotFactory, Game, GameBoard, GameResultWinner, SnakeBot, SnakeId,
+};
+use genetic_snake_rs::heuristic_bot::{HeuristicBot, Weights, GOOD_WEIGHTS, NB_WEIGHTS};
+use genetic_snake_rs::interactive_bot::InteractiveBot;
+use genetic_snake_rs::interrupt;
+use genetic_snake_rs::learning::{
+    learn_headless, learning, load_checkpoint, test_saved_weights, Checkpoint, Parameters,
+};
+use genetic_snake_rs::mcts_bot::MctsBot;
+use genetic_snake_rs::minimax_bot::MinimaxBot;
+use genetic_snake_rs::random_bot::RandomBot;
+use genetic_snake_rs::simulation::{build_game, run_match, run_speed_test, MatchConfig};
+use genetic_snake_rs::spectator::spectate;
+use genetic_snake_rs::tournament::{run_elimination, run_round_robin, run_swiss};
+use genetic_snake_rs::DIALOG_THEME;
 
-use crate::game_engine::{Game, GameBoard, SnakeBot, SnakeId, BOARD_HEIGHT};
-use crate::heuristic_bot::{HeuristicBot, Weights, NB_WEIGHTS};
-use crate::interactive_bot::InteractiveBot;
-use crate::learning::learning;
-use crate::random_bot::RandomBot;
+fn main() {
+    interrupt::install_handler();
 
-lazy_static! {
-    /// Global dialog theme
-    pub static ref DIALOG_THEME: ColorfulTheme = {
-        ColorfulTheme {
-            values_style: Style::new().yellow().dim(),
-            indicator_style: Style::new().yellow().bold(),
-            yes_style: Style::new().yellow().dim(),
-            no_style: Style::new().yellow().dim(),
-            ..ColorfulTheme::default()
+    // Without arguments, the menu is shown
+    let args: Vec<String> = env::args().skip(1).collect();
+    if !args.is_empty() {
+        match parse_learning_args(&args) {
+            Ok(options) => run_headless_learning(options),
+            Err(e) => {
+                eprintln!("{}\n\n{}", e, USAGE);
+                process::exit(2);
+            }
         }
-    };
-}
+        return;
+    }
 
-fn main() {
     loop {
         // Ask what to do
         let main_choice = Select::with_theme(&*DIALOG_THEME)
@@ -46,10 +57,12 @@ fn main() {
             .item("start the genetic algorithm")
             .item("play against the best bot")
             .item("see a match between bots")
+            .item("run a tournament between bots")
+            .item("test a saved genome")
             .item("speed test!")
             .item("quit")
             .interact()
-            .unwrap_or(4);
+            .unwrap_or(6);
 
         match main_choice {
             0 => {
@@ -57,122 +70,485 @@ fn main() {
                 break;
             }
             1 => human_vs_good_bot(),
-            2 => start_match(prompt_and_create_bots()),
-            3 => speed_test(),
+            2 => {
+                let (bots, with_human) = prompt_and_create_bots();
+                start_match(bots, with_human)
+            }
+            3 => start_tournament(),
+            4 => test_saved_weights(),
+            5 => speed_test(),
             _ => break,
         }
         println!();
     }
 }
 
+const USAGE: &str = "\
+Usage: genetic-snake-rs [--mode learn [OPTIONS]]
+
+Without arguments, an interactive menu is shown.
+
+Options of the learning mode:
+    --config <FILE>       read the parameters of the genetic algorithm from
+                          FILE, e.g. ga_config.toml, before the options
+    --pop <SIZE>          population size
+    --target <FITNESS>    target fitness, 90% of the maximum by default
+    --generations <N>     maximum number of generations
+    --out <DIR>           directory of the stats and of the best weights
+    --crossover <KIND>    discrete or multi-point (default)
+    --mutator <KIND>      random or breeder (default)
+    --fitness <KIND>      evaluate the genomes against the human-tuned bot,
+                          static (default), against the best genomes of the
+                          previous generation, self-play, or by the steps
+                          survived alone, survival
+    --matches <N>         number of matches to evaluate a genome
+    --scores <W,D,L>      points for a win, a draw and a loss, e.g. 2,1,0
+    --mirrored <BOOL>     play each match on both sides, true or false
+    --cache-fitness <BOOL>
+                          evaluate each genome only once, true or false
+    --headless-render <K> render a game of the best genome every K
+                          generations in <DIR>/frames/, needs the
+                          `render` feature
+    --checkpoint-every <N>
+                          save the population in <DIR>/checkpoint.txt every
+                          N generations, 0 to never save it (default 50)
+    --resume <FILE>       continue the learning of a checkpoint, with its
+                          options except --out and --target";
+
+/// The options of the non-interactive learning, e.g.
+/// `--mode learn --pop 400 --target 36 --generations 2000 --out results/`.
+struct LearningOptions {
+    params: Parameters,
+    target_fitness: usize,
+    out_dir: PathBuf,
+    checkpoint: Option<Checkpoint>,
+}
+
+fn parse_learning_args(args: &[String]) -> Result<LearningOptions, String> {
+    let mut options = LearningOptions {
+        params: Parameters::default(),
+        target_fitness: 0,
+        out_dir: PathBuf::from("."),
+        checkpoint: None,
+    };
+    let mut mode = None;
+    let mut target_fitness = None;
+
+    // The options override the config, whatever their order
+    if let Some(i) = args.iter().position(|arg| arg == "--config") {
+        let path = args.get(i + 1).ok_or("Missing value after --config.")?;
+        options.params = Parameters::from_file(&PathBuf::from(path))
+            .map_err(|e| format!("Unable to read {}: {}", path, e))?;
+    }
+
+    let mut args = args.iter();
+    while let Some(flag) = args.next() {
+        let value = args
+            .next()
+            .ok_or_else(|| format!("Missing value after {}.", flag))?;
+        let invalid = || format!("Invalid value for {}: {}.", flag, value);
+        match flag.as_str() {
+            "--mode" => mode = Some(value.clone()),
+            "--config" => {}
+            "--pop" => options.params.population_size = value.parse().map_err(|_| invalid())?,
+            "--target" => target_fitness = Some(value.parse().map_err(|_| invalid())?),
+            "--generations" => {
+                options.params.generation_limit = value.parse().map_err(|_| invalid())?
+            }
+            "--out" => options.out_dir = PathBuf::from(value),
+            "--crossover" => options.params.crossover = value.parse()?,
+            "--mutator" => options.params.mutator = value.parse()?,
+            "--fitness" => options.params.fitness = value.parse()?,
+            "--matches" => options.params.nb_matches = value.parse().map_err(|_| invalid())?,
+            "--mirrored" => {
+                options.params.mirrored_matches = value.parse().map_err(|_| invalid())?
+            }
+            "--cache-fitness" => {
+                options.params.cache_fitness = value.parse().map_err(|_| invalid())?
+            }
+            "--headless-render" if cfg!(feature = "render") => match value.parse() {
+                Ok(every) if every > 0 => options.params.render_every = Some(every),
+                _ => return Err(invalid()),
+            },
+            "--headless-render" => {
+                return Err("--headless-render needs the render feature.".to_string())
+            }
+            "--checkpoint-every" => match value.parse() {
+                Ok(0) => options.params.checkpoint_every = None,
+                Ok(every) => options.params.checkpoint_every = Some(every),
+                Err(_) => return Err(invalid()),
+            },
+            "--resume" => {
+                let checkpoint = load_checkpoint(&PathBuf::from(value))
+                    .map_err(|e| format!("Unable to resume from {}: {}", value, e))?;
+                options.checkpoint = Some(checkpoint);
+            }
+            "--scores" => {
+                let scores = value
+                    .split(',')
+                    .map(|score| score.trim().parse())
+                    .collect::<Result<Vec<usize>, _>>()
+                    .map_err(|_| invalid())?;
+                match scores.as_slice() {
+                    [win, draw, loss] => {
+                        options.params.win_score = *win;
+                        options.params.draw_score = *draw;
+                        options.params.loss_score = *loss;
+                    }
+                    _ => return Err(format!("Expected three scores, got: {}.", value)),
+                }
+            }
+            _ => return Err(format!("Unknown option: {}.", flag)),
+        }
+    }
+
+    if let Some(checkpoint) = &options.checkpoint {
+        options.params = checkpoint.params.clone();
+    }
+    options.target_fitness =
+        target_fitness.unwrap_or_else(|| options.params.default_target_fitness());
+    match mode.as_deref() {
+        Some("learn") => Ok(options),
+        Some(mode) => Err(format!("Unknown mode: {}.", mode)),
+        None => Err("The mode is missing.".to_string()),
+    }
+}
+
+fn run_headless_learning(options: LearningOptions) {
+    match learn_headless(
+        &options.params,
+        options.target_fitness,
+        &options.out_dir,
+        options.checkpoint,
+    ) {
+        Ok(Some(_)) => {}
+        Ok(None) => {
+            eprintln!("{}", "Learning failed.".red().bold());
+            process::exit(1);
+        }
+        Err(e) => {
+            eprintln!("Unable to write the results: {}", e);
+            process::exit(1);
+        }
+    }
+}
+
 lazy_static! {
     /// Weights learned with the GA, which got 38/40 or 40/40
+    /// Note: They were learned without `dist_to_nearest_wall` and
+    /// `min_corridor_width`, whose weights are zero, so they need to be
+    /// learned again to use them.
     pub static ref GA_WEIGHTS: Weights = {
         #[cfg_attr(rustfmt, rustfmt_skip)]
         // 38/40
         // let weights: [f64; NB_WEIGHTS] = [
-        //     0.97500, -0.64724, -0.24451, -0.30122, -0.25775,
-        //     0.97500, -0.62002, -0.64823, -0.23038, 0.06820,
-        //     1.00000, -0.64373, -0.08643, -0.33367, -0.38482,
+        //     0.97500, -0.64724, -0.24451, -0.30122, -0.25775, 0., 0.,
+        //     0.97500, -0.62002, -0.64823, -0.23038, 0.06820, 0., 0.,
+        //     1.00000, -0.64373, -0.08643, -0.33367, -0.38482, 0., 0.,
         // ];
         // 38/40
         // let weights: [f64; NB_WEIGHTS] = [
-        //     0.95000,   0.62497,  -0.04825,  -0.49258,  -0.17677,
-        //     0.97500,   0.42442,  -0.63253,  -0.16685,  -0.05459,
-        //     0.97500,  -0.57496,  -0.10656,  -0.34064,  -0.27314,
+        //     0.95000,   0.62497,  -0.04825,  -0.49258,  -0.17677, 0., 0.,
+        //     0.97500,   0.42442,  -0.63253,  -0.16685,  -0.05459, 0., 0.,
+        //     0.97500,  -0.57496,  -0.10656,  -0.34064,  -0.27314, 0., 0.,
         // ];
         // 40/40
         let weights: [f64; NB_WEIGHTS] = [
-            1.02867,  -0.62294,  -0.08552,  -0.36006,  -0.24858,
-            1.07254,  -0.13452,  -0.45125,  -0.31519,   0.01470,
-            1.03946,   0.38929,   0.01750,  -0.55665,  -0.18053,
+            1.02867,  -0.62294,  -0.08552,  -0.36006,  -0.24858,   0.00000,   0.00000,
+            1.07254,  -0.13452,  -0.45125,  -0.31519,   0.01470,   0.00000,   0.00000,
+            1.03946,   0.38929,   0.01750,  -0.55665,  -0.18053,   0.00000,   0.00000,
         ];
         weights.to_vec()
     };
 }
 
+/// The tally of the games between the human and the bot, during one visit of
+/// the "play against the best bot" menu.
+#[derive(Default)]
+struct Session {
+    human_wins: usize,
+    bot_wins: usize,
+    draws: usize,
+}
+
+impl Session {
+    fn nb_games(&self) -> usize {
+        self.human_wins + self.bot_wins + self.draws
+    }
+
+    /// Whether someone won a "best of `best_of`" (0 means endless).
+    fn is_decided(&self, best_of: usize) -> bool {
+        best_of > 0
+            && (self.nb_games() >= best_of
+                || self.human_wins > best_of / 2
+                || self.bot_wins > best_of / 2)
+    }
+}
+
+impl fmt::Display for Session {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "You {} - {} bot ({} draws)",
+            self.human_wins.to_string().green().bold(),
+            self.bot_wins.to_string().red().bold(),
+            self.draws
+        )
+    }
+}
+
 fn human_vs_good_bot() {
-    let results = Game::new()
-        .continue_simulation_if_known_winner(false)
-        .add_snake(0, Box::from(HeuristicBot::new(&GA_WEIGHTS)))
-        .add_snake(1, Box::from(InteractiveBot::default()))
-        .initialize()
-        .print()
-        .after_each_step(move |board: &GameBoard| board.print())
-        .run_to_end();
-    println!("{}", results);
+    let best_of: usize = Input::with_theme(&*DIALOG_THEME)
+        .with_prompt("Best of how many games? (0 to play until you stop)")
+        .default(0)
+        .interact()
+        .unwrap_or(0);
+
+    let mut session = Session::default();
+    loop {
+        println!("You play the {}.", "red snake".red());
+        let bots: Vec<Box<dyn SnakeBot>> = vec![
+            Box::from(HeuristicBot::new(&GA_WEIGHTS)),
+            Box::from(InteractiveBot),
+        ];
+        let results = run_match(
+            bots,
+            &MatchConfig {
+                print: true,
+                interruptible: true,
+                ..MatchConfig::default()
+            },
+        );
+        println!("{}", results);
+
+        // Interrupted games don't count
+        if !results.interrupted {
+            match results.winner {
+                Some(GameResultWinner::Winner(1)) => session.human_wins += 1,
+                Some(GameResultWinner::Winner(_)) => session.bot_wins += 1,
+                _ => session.draws += 1,
+            }
+        }
+        println!("Game {}: {}\n", session.nb_games(), session);
+
+        if session.is_decided(best_of) {
+            if session.human_wins > session.bot_wins {
+                println!("{}", "You won the session!".green().bold());
+            } else if session.human_wins < session.bot_wins {
+                println!("{}", "The bot won the session!".red().bold());
+            } else {
+                println!("{}", "The session ends in a draw.".yellow().bold());
+            }
+            break;
+        }
+
+        if !Confirmation::with_theme(&*DIALOG_THEME)
+            .with_text("Play again?")
+            .default(true)
+            .interact()
+            .unwrap_or(false)
+        {
+            break;
+        }
+    }
 }
 
 enum Bot {
     Random,
     Heuristic,
     Best,
+    Mcts(u32),
+    AStar,
+    FloodFill,
+    Minimax(u32),
     Interactive,
 }
 
-fn prompt_and_create_bots() -> Vec<Box<dyn SnakeBot>> {
+/// Above, the board gets too crowded.
+const MAX_PLAYERS: usize = 10;
+
+/// Returns the bots, and whether a human plays.
+fn prompt_and_create_bots() -> (Vec<Box<dyn SnakeBot>>, bool) {
     let nb_players = loop {
         let nb_players = Input::with_theme(&*DIALOG_THEME)
-            .with_prompt("How many players?")
+            .with_prompt(&format!("How many players? (at most {})", MAX_PLAYERS))
             .default(2)
             .interact()
             .unwrap_or(2);
 
-        if nb_players <= 5 {
+        if nb_players <= MAX_PLAYERS {
             break nb_players;
         }
     };
 
     let mut bots: Vec<Box<dyn SnakeBot>> = vec![];
+    let mut with_human = false;
     for id in 1..=nb_players {
-        let bot = prompt_which_bot(&format!("Which bot do you want for player {}?", id));
+        let bot = prompt_which_bot(&format!("Which bot do you want for player {}?", id), true);
         match bot {
             Bot::Random => bots.push(Box::new(RandomBot::new())),
             Bot::Heuristic => bots.push(Box::new(HeuristicBot::default())),
             Bot::Best => bots.push(Box::new(HeuristicBot::new(&GA_WEIGHTS))),
-            Bot::Interactive => bots.push(Box::new(InteractiveBot)),
+            Bot::Mcts(iterations) => bots.push(Box::new(MctsBot::new(iterations))),
+            Bot::AStar => bots.push(Box::new(AStarBot)),
+            Bot::FloodFill => bots.push(Box::new(FloodFillBot)),
+            Bot::Minimax(depth) => bots.push(Box::new(MinimaxBot::with_depth(depth))),
+            Bot::Interactive => {
+                bots.push(Box::new(InteractiveBot));
+                with_human = true;
+            }
         };
     }
-    bots
+    (bots, with_human)
 }
 
-fn prompt_which_bot(msg: &str) -> Bot {
-    match Select::with_theme(&*DIALOG_THEME)
+/// The human can only be picked with `allow_human`, since they need the
+/// keyboard of a watched game.
+fn prompt_which_bot(msg: &str, allow_human: bool) -> Bot {
+    let mut select = Select::with_theme(&*DIALOG_THEME);
+    select
         .with_prompt(msg)
         .default(0)
         .item("random bot")
         .item("human-tuned heuristic bot")
         .item("best bot found with genetic algorithm")
-        .item("human")
-        .interact()
-        .unwrap_or(0)
-    {
+        .item("Monte Carlo bot")
+        .item("A* food-seeking bot")
+        .item("flood-fill survival bot")
+        .item("minimax bot (for two players)");
+    if allow_human {
+        select.item("human");
+    }
+    match select.interact().unwrap_or(0) {
         0 => Bot::Random,
         1 => Bot::Heuristic,
         2 => Bot::Best,
-        3 => Bot::Interactive,
+        3 => Bot::Mcts(
+            Input::with_theme(&*DIALOG_THEME)
+                .with_prompt("How many rollouts per move? (more is stronger but slower)")
+                .default(MctsBot::DEFAULT_ITERATIONS)
+                .interact()
+                .unwrap_or(MctsBot::DEFAULT_ITERATIONS),
+        ),
+        4 => Bot::AStar,
+        5 => Bot::FloodFill,
+        6 => Bot::Minimax(
+            Input::with_theme(&*DIALOG_THEME)
+                .with_prompt("How many plies ahead? (more is stronger but slower)")
+                .default(MinimaxBot::DEFAULT_DEPTH)
+                .interact()
+                .unwrap_or(MinimaxBot::DEFAULT_DEPTH),
+        ),
+        7 => Bot::Interactive,
         _ => unreachable!(),
     }
 }
 
-/// TODO: Move all the simulation stuff in a separate module
-fn start_match(mut bots: Vec<Box<dyn SnakeBot>>) {
-    let mut game = Game::new();
+fn start_match(bots: Vec<Box<dyn SnakeBot>>, with_human: bool) {
+    let step_delay = Duration::from_millis(200);
+    let config = MatchConfig {
+        print: true,
+        step_delay: if with_human { Some(step_delay) } else { None },
+        interruptible: true,
+        ..MatchConfig::default()
+    };
+    let mut game = build_game(bots, &config);
+    let seed = game.seed();
+    game.with_timing().print();
+    // The human needs the keyboard, so the match can't be paused
+    let results = if with_human {
+        game.run_to_end()
+    } else {
+        spectate(&mut game, step_delay)
+    };
+    println!("{}\n(seed {})", results, seed);
+    for timing in game.timings().unwrap_or_default() {
+        println!("{}", timing);
+    }
+}
+
+fn create_bot_factory(bot: &Bot) -> BotFactory<'static> {
+    match bot {
+        Bot::Random => RandomBot::factory(),
+        Bot::Heuristic => HeuristicBot::factory(&GOOD_WEIGHTS),
+        Bot::Best => HeuristicBot::factory(&GA_WEIGHTS),
+        Bot::Mcts(iterations) => MctsBot::factory(*iterations),
+        Bot::AStar => AStarBot::factory(),
+        Bot::FloodFill => FloodFillBot::factory(),
+        Bot::Minimax(depth) => MinimaxBot::factory(*depth),
+        Bot::Interactive => unreachable!("The human can't play unwatched games."),
+    }
+}
+
+fn bot_name(bot: &Bot) -> &'static str {
+    match bot {
+        Bot::Random => "random bot",
+        Bot::Heuristic => "human-tuned heuristic bot",
+        Bot::Best => "best GA bot",
+        Bot::Mcts(_) => "Monte Carlo bot",
+        Bot::AStar => "A* bot",
+        Bot::FloodFill => "flood-fill bot",
+        Bot::Minimax(_) => "minimax bot",
+        Bot::Interactive => "human",
+    }
+}
+
+fn start_tournament() {
+    let nb_bots: usize = Input::with_theme(&*DIALOG_THEME)
+        .with_prompt("How many bots?")
+        .default(4)
+        .interact()
+        .unwrap_or(4);
 
-    for id in (0..bots.len()).rev() {
-        let idx = bots.len() - 1;
-        game.add_snake(id as SnakeId, bots.swap_remove(idx));
+    let mut bots = vec![];
+    for id in 1..=nb_bots {
+        let bot = prompt_which_bot(&format!("Which bot do you want for seed {}?", id), false);
+        bots.push((
+            format!("{} #{}", bot_name(&bot), id),
+            create_bot_factory(&bot),
+        ));
     }
 
-    let results = game
-        .continue_simulation_if_known_winner(false)
-        .initialize()
-        .print()
-        .after_each_step(move |board: &GameBoard| board.print())
-        .after_each_step(|_| thread::sleep(Duration::from_millis(200)))
-        .run_to_end();
+    let format = Select::with_theme(&*DIALOG_THEME)
+        .with_prompt("Which tournament format?")
+        .default(0)
+        .item("round-robin")
+        .item("single elimination")
+        .item("swiss")
+        .interact()
+        .unwrap_or(0);
 
-    println!("{}", results);
+    let games_per_pairing = Input::with_theme(&*DIALOG_THEME)
+        .with_prompt("How many games per pairing (in both ways)?")
+        .default(5)
+        .interact()
+        .unwrap_or(5);
+
+    let seed = Input::with_theme(&*DIALOG_THEME)
+        .with_prompt("Seed?")
+        .default(thread_rng().gen::<u32>() as u64)
+        .interact()
+        .unwrap_or(0);
+
+    let standings = match format {
+        0 => {
+            let results = run_round_robin(bots, games_per_pairing, seed);
+            println!("\n{}", results.matrix);
+            results.standings
+        }
+        1 => run_elimination(bots, games_per_pairing, seed),
+        2 => {
+            let default_nb_rounds = (nb_bots as f64).log2().ceil() as usize + 1;
+            let nb_rounds = Input::with_theme(&*DIALOG_THEME)
+                .with_prompt("How many rounds?")
+                .default(default_nb_rounds)
+                .interact()
+                .unwrap_or(default_nb_rounds);
+            run_swiss(bots, nb_rounds, games_per_pairing, seed)
+        }
+        _ => unreachable!(),
+    };
+    println!("\n{}", standings);
 }
 
 fn speed_test() {
@@ -188,18 +564,11 @@ fn speed_test() {
         .interact()
         .unwrap_or(2);
 
-    let which_bot = match Select::with_theme(&*DIALOG_THEME)
-        .with_prompt("Which bot?")
-        .default(0)
-        .item("random bot")
-        .item("human-tuned heuristic bot")
-        .interact()
-        .unwrap_or(0)
-    {
-        0 => Bot::Random,
-        1 => Bot::Heuristic,
-        _ => unreachable!(),
-    };
+    let mut bots = vec![];
+    for id in 1..=nb_bots {
+        let bot = prompt_which_bot(&format!("Which bot do you want for snake {}?", id), false);
+        bots.push((bot_name(&bot).to_string(), create_bot_factory(&bot)));
+    }
 
     let print = Input::with_theme(&*DIALOG_THEME)
         .with_prompt("Print?")
@@ -207,21 +576,10 @@ fn speed_test() {
         .interact()
         .unwrap_or(false);
 
-    let continue_if_winner = false;
-
-    use crate::bench_tests::test_simulation_speed;
-    match which_bot {
-        Bot::Random => {
-            test_simulation_speed::<RandomBot>(nb_simulations, nb_bots, continue_if_winner, print);
-        }
-        Bot::Heuristic => {
-            test_simulation_speed::<HeuristicBot>(
-                nb_simulations,
-                nb_bots,
-                continue_if_winner,
-                print,
-            );
-        }
-        _ => unreachable!(),
+    let config = MatchConfig {
+        print,
+        max_steps: Some(Game::MAX_UNWATCHED_STEPS),
+        ..MatchConfig::default()
     };
+    println!("{}", run_speed_test(&bots, nb_simulations, &config));
 }
@@ -0,0 +1,222 @@
+//! A bot using Monte Carlo rollouts to choose its moves.
+
+use rand::prelude::*;
+
+use crate::game_engine::*;
+
+/// Evaluates each possible action with random rollouts played on a copy of
+/// the board, and picks the action with the best survival rate. The rollouts
+/// are allocated between the actions with UCB1, so that the promising actions
+/// get most of the `iterations` budget.
+///
+/// The rollouts are approximations of the game: the snakes never shrink, and
+/// the opponents move randomly (their heads expand to a random free
+/// neighbor) before the bot, so they can block it. This makes the bot
+/// cautious.
+pub struct MctsBot {
+    /// The number of rollouts per move. More is stronger, but slower.
+    pub iterations: u32,
+    rng: StdRng,
+}
+
+impl MctsBot {
+    pub const DEFAULT_ITERATIONS: u32 = 500;
+    /// The number of steps simulated by each rollout.
+    pub const ROLLOUT_DEPTH: u32 = 20;
+    /// The UCB1 exploration constant.
+    const EXPLORATION: f64 = std::f64::consts::SQRT_2;
+
+    pub fn new(iterations: u32) -> Self {
+        MctsBot {
+            iterations,
+            rng: StdRng::from_entropy(),
+        }
+    }
+
+    /// A bot whose rollouts only depend on `seed`, see `RandomBot::with_seed()`.
+    pub fn with_seed(iterations: u32, seed: u64) -> Self {
+        MctsBot {
+            iterations,
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+
+    /// Creates a new `MctsBot` for each game.
+    pub fn factory(iterations: u32) -> BotFactory<'static> {
+        Box::new(move || Box::new(MctsBot::new(iterations)))
+    }
+
+    /// Plays one rollout starting with `first_action`, on a board containing
+    /// the current state. Returns the ratio of the rollout the bot survived,
+    /// in `[0, 1]`. Trapping all the opponents counts as surviving.
+    fn rollout(
+        &mut self,
+        board: &mut GameBoard,
+        myself: &SnakeState,
+        opponents: &mut Vec<(SnakeId, Coordinate)>,
+        first_action: &Action,
+    ) -> f64 {
+        let mut head = myself.get_head_coord(board.size());
+        let mut orientation = myself.current_orientation.clone();
+        let mut health = myself.health;
+        let had_opponents = !opponents.is_empty();
+
+        let mut action = first_action.clone();
+        for step in 0..Self::ROLLOUT_DEPTH {
+            // Move the opponents, the trapped ones are dead
+            let rng = &mut self.rng;
+            opponents.retain(|(_, coord)| {
+                Orientation::ALL
+                    .iter()
+                    .filter_map(|orientation| {
+                        next_coord_towards(coord, orientation, board.size(), board.wrap_mode())
+                    })
+                    .any(|next| board.is_coord_free_or_food(&next))
+            });
+            for (id, coord) in opponents.iter_mut() {
+                let free_neighbors: Vec<Coordinate> = Orientation::ALL
+                    .iter()
+                    .filter_map(|orientation| {
+                        next_coord_towards(coord, orientation, board.size(), board.wrap_mode())
+                    })
+                    .filter(|next| board.is_coord_free_or_food(next))
+                    .collect();
+                if let Some(next) = free_neighbors.choose(rng) {
+                    board.set_tile_at_coord_unchecked(coord, Cell::SnakeBody(*id));
+                    board.set_tile_at_coord_unchecked(next, Cell::SnakeHead(*id));
+                    *coord = next.clone();
+                }
+            }
+            if had_opponents && opponents.is_empty() {
+                return 1.;
+            }
+
+            // Move the bot
+            let next_orientation = next_orientation(&orientation, &action);
+            let next_head =
+                match next_coord_towards(&head, &next_orientation, board.size(), board.wrap_mode())
+                {
+                    Some(next_head) if board.is_coord_free_or_food(&next_head) => next_head,
+                    _ => return step as f64 / Self::ROLLOUT_DEPTH as f64,
+                };
+            if let (Some(health), Some(max_health)) = (&mut health, myself.max_health) {
+                if board.get_tile_at_coord(&next_head) == Cell::Food {
+                    *health = max_health;
+                } else {
+                    *health = health.saturating_sub(1);
+                    if *health == 0 {
+                        return step as f64 / Self::ROLLOUT_DEPTH as f64;
+                    }
+                }
+            }
+            board.set_tile_at_coord_unchecked(&head, Cell::SnakeBody(myself.id));
+            board.set_tile_at_coord_unchecked(&next_head, Cell::SnakeHead(myself.id));
+            head = next_head;
+            orientation = next_orientation;
+
+            // Then play randomly
+            action = board
+                .get_non_suicide_moves(&head, &orientation)
+                .choose(&mut self.rng)
+                .cloned()
+                .unwrap_or(Action::Front);
+        }
+        1.
+    }
+}
+
+impl Default for MctsBot {
+    fn default() -> Self {
+        Self::new(Self::DEFAULT_ITERATIONS)
+    }
+}
+
+impl SnakeBot for MctsBot {
+    fn get_next_action(&mut self, myself: &SnakeState, board: &GameBoard) -> Action {
+        // The rollouts need the head
+        if myself.try_get_head_pos().is_none() {
+            return Action::Front;
+        }
+
+        let actions = [Action::Left, Action::Front, Action::Right];
+        let mut visits = [0_u32; 3];
+        let mut rewards = [0_f64; 3];
+
+        let root = board.snapshot();
+        let root_opponents: Vec<(SnakeId, Coordinate)> = board
+            .head_positions()
+            .filter(|&(id, _)| id != myself.id)
+            .map(|(id, head)| (id, Coordinate::from_pos(head, board.size())))
+            .collect();
+
+        let mut sim_board = GameBoard::new(StdRng::seed_from_u64(self.rng.gen()));
+        let mut opponents = Vec::with_capacity(root_opponents.len());
+        for iteration in 0..self.iterations as usize {
+            // UCB1: try each action once, then balance the exploitation of the
+            // good actions and the exploration of the less visited ones
+            let i = if iteration < actions.len() {
+                iteration
+            } else {
+                let ln_total = (iteration as f64).ln();
+                let ucb = |i: usize| {
+                    rewards[i] / visits[i] as f64
+                        + Self::EXPLORATION * (ln_total / visits[i] as f64).sqrt()
+                };
+                (1..actions.len()).fold(0, |best, i| if ucb(i) > ucb(best) { i } else { best })
+            };
+
+            sim_board.restore(&root);
+            opponents.clone_from(&root_opponents);
+            rewards[i] += self.rollout(&mut sim_board, myself, &mut opponents, &actions[i]);
+            visits[i] += 1;
+        }
+
+        // Pick the best survival rate
+        let mean_reward = |i: usize| {
+            if visits[i] == 0 {
+                0.
+            } else {
+                rewards[i] / visits[i] as f64
+            }
+        };
+        let best = (1..actions.len()).fold(0, |best, i| {
+            if mean_reward(i) > mean_reward(best) {
+                i
+            } else {
+                best
+            }
+        });
+        actions[best].clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn avoids_dead_ends() {
+        // A snake at (10, 8) facing North, with a dead end on its left
+        let mut board = GameBoard::new(StdRng::seed_from_u64(0));
+        for x in 7..=9 {
+            board
+                .set_tile_at_coord(&Coordinate { x, y: 7 }, Cell::Obstacle)
+                .unwrap();
+            board
+                .set_tile_at_coord(&Coordinate { x, y: 9 }, Cell::Obstacle)
+                .unwrap();
+        }
+        board
+            .set_tile_at_coord(&Coordinate { x: 6, y: 8 }, Cell::Obstacle)
+            .unwrap();
+
+        let head = Coordinate { x: 10, y: 8 };
+        board.set_tile_at_coord(&head, Cell::SnakeHead(0)).unwrap();
+        let myself = SnakeState::with_head(head, Orientation::North, board.size());
+
+        let mut bot = MctsBot::with_seed(200, 0);
+        for _ in 0..10 {
+            assert_ne!(bot.get_next_action(&myself, &board), Action::Left);
+        }
+    }
+}
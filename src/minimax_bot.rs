@@ -0,0 +1,250 @@
+//! A bot searching the moves of both snakes of a two-player game.
+
+use crate::game_engine::*;
+use crate::heuristic_bot::{compute_stats_from, Stats, GOOD_WEIGHTS, MAX_DEPTH, NB_STATS};
+
+/// Searches `depth` plies ahead with alpha-beta pruning, a ply being the move
+/// of one snake: the bot moves first, then its opponent replies, and so on.
+/// The leaf boards are scored by `evaluate`, with the `Stats` of the bot
+/// head.
+///
+/// The search is an approximation of the game: the snakes never shrink, and
+/// a head-on collision is always lost. The opponent is the nearest enemy,
+/// the others don't move, so the bot is meant for two-player games.
+pub struct MinimaxBot {
+    pub depth: u32,
+    evaluate: fn(&Stats) -> f64,
+}
+
+/// The score of a lost board, before the bonus of the plies survived.
+const LOSS: f64 = -1000.;
+
+/// A board of the search, the bot having to play if `my_turn`.
+#[derive(Clone)]
+struct Node {
+    board: GameBoard,
+    head: Coordinate,
+    orientation: Orientation,
+    opponent: Option<(SnakeId, Coordinate)>,
+    my_turn: bool,
+    plies: u32,
+}
+
+impl MinimaxBot {
+    pub const DEFAULT_DEPTH: u32 = 4;
+
+    /// A bot searching `depth` plies ahead, see `with_evaluator()` for the
+    /// evaluation of the leaves.
+    pub fn with_depth(depth: u32) -> Self {
+        MinimaxBot {
+            depth,
+            evaluate: Self::default_evaluation,
+        }
+    }
+
+    /// Scores the leaf boards with `evaluate` instead of
+    /// `default_evaluation()`. The higher, the better for the bot.
+    pub fn with_evaluator(mut self, evaluate: fn(&Stats) -> f64) -> Self {
+        self.evaluate = evaluate;
+        self
+    }
+
+    /// The score of the front move of a `HeuristicBot` with `GOOD_WEIGHTS`.
+    pub fn default_evaluation(stats: &Stats) -> f64 {
+        stats
+            .as_array(false)
+            .iter()
+            .zip(&GOOD_WEIGHTS[NB_STATS..2 * NB_STATS])
+            .map(|(stat, weight)| stat * weight)
+            .sum()
+    }
+
+    /// Creates a new `MinimaxBot` for each game.
+    pub fn factory(depth: u32) -> BotFactory<'static> {
+        Box::new(move || Box::new(MinimaxBot::with_depth(depth)))
+    }
+
+    /// The moves of the bot, with the nodes they lead to.
+    fn my_moves(node: &Node, id: SnakeId) -> Vec<(Action, Node)> {
+        let board = &node.board;
+        [Action::Left, Action::Front, Action::Right]
+            .iter()
+            .filter_map(|action| {
+                let orientation = next_orientation(&node.orientation, action);
+                let next =
+                    next_coord_towards(&node.head, &orientation, board.size(), board.wrap_mode())
+                        .filter(|next| board.is_coord_free_or_food(next))?;
+                let mut child = node.clone();
+                child
+                    .board
+                    .set_tile_at_coord_unchecked(&node.head, Cell::SnakeBody(id));
+                child
+                    .board
+                    .set_tile_at_coord_unchecked(&next, Cell::SnakeHead(id));
+                child.head = next;
+                child.orientation = orientation;
+                child.my_turn = false;
+                child.plies += 1;
+                Some((action.clone(), child))
+            })
+            .collect()
+    }
+
+    fn search(
+        &self,
+        node: &Node,
+        id: SnakeId,
+        plies_left: u32,
+        mut alpha: f64,
+        mut beta: f64,
+    ) -> f64 {
+        if plies_left == 0 {
+            return self.evaluate_leaf(node, id);
+        }
+
+        if node.my_turn {
+            let mut best = LOSS + node.plies as f64;
+            for (_, child) in Self::my_moves(node, id) {
+                best = best.max(self.search(&child, id, plies_left - 1, alpha, beta));
+                alpha = alpha.max(best);
+                if alpha >= beta {
+                    break;
+                }
+            }
+            return best;
+        }
+
+        let (opponent_id, opponent_head) = match &node.opponent {
+            Some(opponent) => opponent.clone(),
+            None => {
+                let child = Node {
+                    my_turn: true,
+                    plies: node.plies + 1,
+                    ..node.clone()
+                };
+                return self.search(&child, id, plies_left - 1, alpha, beta);
+            }
+        };
+        let board = &node.board;
+        let mut best = None;
+        for orientation in &Orientation::ALL {
+            let next = match next_coord_towards(
+                &opponent_head,
+                orientation,
+                board.size(),
+                board.wrap_mode(),
+            ) {
+                Some(next) => next,
+                None => continue,
+            };
+            let value = if next == node.head {
+                LOSS + node.plies as f64
+            } else if board.is_coord_free_or_food(&next) {
+                let mut child = node.clone();
+                child
+                    .board
+                    .set_tile_at_coord_unchecked(&opponent_head, Cell::SnakeBody(opponent_id));
+                child
+                    .board
+                    .set_tile_at_coord_unchecked(&next, Cell::SnakeHead(opponent_id));
+                child.opponent = Some((opponent_id, next));
+                child.my_turn = true;
+                child.plies += 1;
+                self.search(&child, id, plies_left - 1, alpha, beta)
+            } else {
+                continue;
+            };
+            let min = best.map_or(value, |best: f64| best.min(value));
+            best = Some(min);
+            beta = beta.min(min);
+            if alpha >= beta {
+                break;
+            }
+        }
+        // A trapped opponent loses
+        best.unwrap_or(-LOSS)
+    }
+
+    fn evaluate_leaf(&self, node: &Node, id: SnakeId) -> f64 {
+        // The stats are computed from a free cell
+        let mut board = node.board.clone();
+        board.set_tile_at_coord_unchecked(&node.head, Cell::Empty);
+        (self.evaluate)(&compute_stats_from(
+            id,
+            &Some(node.head.clone()),
+            &board,
+            MAX_DEPTH,
+        ))
+    }
+}
+
+impl Default for MinimaxBot {
+    fn default() -> Self {
+        Self::with_depth(Self::DEFAULT_DEPTH)
+    }
+}
+
+impl SnakeBot for MinimaxBot {
+    fn get_next_action(&mut self, myself: &SnakeState, board: &GameBoard) -> Action {
+        let head = match myself.try_get_head_coord(board.size()) {
+            Some(head) => head,
+            None => return Action::Front,
+        };
+        let opponent = board
+            .head_positions()
+            .filter(|&(id, _)| id != myself.id)
+            .map(|(id, pos)| (id, Coordinate::from_pos(pos, board.size())))
+            .min_by_key(|(_, coord)| board.distance(coord, &head));
+        let root = Node {
+            board: board.clone(),
+            head,
+            orientation: myself.current_orientation.clone(),
+            opponent,
+            my_turn: true,
+            plies: 0,
+        };
+
+        let mut best = (Action::Front, f64::NEG_INFINITY);
+        for (action, child) in Self::my_moves(&root, myself.id) {
+            let value = self.search(
+                &child,
+                myself.id,
+                self.depth.saturating_sub(1),
+                best.1,
+                f64::INFINITY,
+            );
+            if value > best.1 {
+                best = (action, value);
+            }
+        }
+        best.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sees_the_opponent_closing_the_way() {
+        // The corridor leads to the largest area, but the snake 1 can close
+        // it right after the snake 0 enters it
+        let board = GameBoard::from_ascii(
+            "...........\n\
+             ...........\n\
+             ####.######\n\
+             ###1.#.....\n\
+             ####.#.....\n\
+             ####0......\n\
+             ###########",
+        )
+        .unwrap();
+        let myself =
+            SnakeState::with_head(Coordinate { x: 4, y: 5 }, Orientation::North, board.size());
+
+        let greedy = MinimaxBot::with_depth(1).get_next_action(&myself, &board);
+        assert_eq!(greedy, Action::Front);
+        let minimax = MinimaxBot::with_depth(2).get_next_action(&myself, &board);
+        assert_eq!(minimax, Action::Right);
+    }
+}
@@ -3,12 +3,27 @@ use rand::prelude::*;
 use crate::game_engine::*;
 
 pub struct RandomBot {
-    rng: ThreadRng,
+    rng: StdRng,
 }
 
 impl RandomBot {
     pub fn new() -> Self {
-        RandomBot { rng: thread_rng() }
+        RandomBot {
+            rng: StdRng::from_entropy(),
+        }
+    }
+
+    /// A bot playing the same moves in the same situations, to be able to
+    /// reproduce its games, see `Game::with_seed()`.
+    pub fn with_seed(seed: u64) -> Self {
+        RandomBot {
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+
+    /// Creates a new `RandomBot` for each game.
+    pub fn factory() -> BotFactory<'static> {
+        Box::new(|| Box::new(RandomBot::new()))
     }
 }
 
@@ -29,8 +44,11 @@ pub fn get_non_suicide_random_action(
     myself: &SnakeState,
     board: &GameBoard,
 ) -> Action {
-    let possible_actions =
-        board.get_non_suicide_moves(&myself.get_head_coord(), &myself.current_orientation);
+    let head = match myself.try_get_head_coord(board.size()) {
+        Some(head) => head,
+        None => return Action::Front,
+    };
+    let possible_actions = board.get_non_suicide_moves(&head, &myself.current_orientation);
 
     if possible_actions.is_empty() {
         Action::Front // We're doomed, so don't care ^^'
@@ -0,0 +1,192 @@
+//! Renders the games as PNG images, e.g. to make videos of the learning.
+//! Only built with the `render` feature.
+//!
+//! The images are small, so they are encoded without compression, which
+//! avoids depending on an image library.
+
+use std::{
+    cell::RefCell,
+    fs, io,
+    path::{Path, PathBuf},
+    rc::Rc,
+};
+
+use crate::game_engine::*;
+
+/// The side of a cell, in pixels.
+const CELL_SIZE: usize = 8;
+
+/// The colors of the snakes, by ID.
+const SNAKE_PALETTE: [[u8; 3]; 6] = [
+    [46, 160, 67],
+    [207, 34, 46],
+    [9, 105, 218],
+    [27, 161, 179],
+    [191, 135, 0],
+    [130, 80, 223],
+];
+
+fn cell_color(cell: Cell) -> [u8; 3] {
+    let snake_color = |id: SnakeId, shade: u8| {
+        let [r, g, b] = SNAKE_PALETTE[id as usize % SNAKE_PALETTE.len()];
+        let shade = |c: u8| (c as u16 * shade as u16 / 4) as u8;
+        [shade(r), shade(g), shade(b)]
+    };
+    match cell {
+        Cell::Empty => [255, 255, 255],
+        Cell::Food => [219, 39, 179],
+        Cell::Obstacle | Cell::Wall => [40, 40, 40],
+        Cell::Mud => [140, 100, 60],
+        Cell::SnakeHead(id) => snake_color(id, 4),
+        Cell::SnakeBody(id) => snake_color(id, 3),
+        Cell::SnakeTail(id) => snake_color(id, 2),
+    }
+}
+
+/// Encodes the board as an RGB PNG image.
+pub fn board_to_png(board: &GameBoard) -> Vec<u8> {
+    let width = board.width() as usize * CELL_SIZE;
+    let height = board.height() as usize * CELL_SIZE;
+
+    // Each row starts with its filter type, none here
+    let mut pixels = vec![0; height * (1 + 3 * width)];
+    for (coord, cell) in board.iter_cells() {
+        let color = cell_color(cell);
+        for dy in 0..CELL_SIZE {
+            let row = coord.y as usize * CELL_SIZE + dy;
+            for dx in 0..CELL_SIZE {
+                let column = coord.x as usize * CELL_SIZE + dx;
+                let i = row * (1 + 3 * width) + 1 + 3 * column;
+                pixels[i..i + 3].copy_from_slice(&color);
+            }
+        }
+    }
+
+    let mut header = Vec::with_capacity(13);
+    header.extend_from_slice(&(width as u32).to_be_bytes());
+    header.extend_from_slice(&(height as u32).to_be_bytes());
+    // 8 bits per channel, RGB, no interlacing
+    header.extend_from_slice(&[8, 2, 0, 0, 0]);
+
+    let mut png = b"\x89PNG\r\n\x1a\n".to_vec();
+    write_chunk(&mut png, b"IHDR", &header);
+    write_chunk(&mut png, b"IDAT", &zlib_stored(&pixels));
+    write_chunk(&mut png, b"IEND", &[]);
+    png
+}
+
+/// Plays `game`, which must be initialized, to the end and writes a frame
+/// after each step, as `dir/<prefix>_stepNNNN.png`. Returns the number of
+/// frames.
+pub fn render_game(game: &mut Game, dir: &Path, prefix: &str) -> io::Result<u32> {
+    fs::create_dir_all(dir)?;
+
+    // The callbacks outlive the game, so they own what they write to
+    let frames = Rc::new(RefCell::new((0, Ok(()))));
+    let frames_written = frames.clone();
+    let dir: PathBuf = dir.to_owned();
+    let prefix = prefix.to_string();
+    game.after_each_step(move |board| {
+        let (ref mut nb_frames, ref mut result) = *frames_written.borrow_mut();
+        if result.is_ok() {
+            *nb_frames += 1;
+            let path = dir.join(format!("{}_step{:04}.png", prefix, nb_frames));
+            *result = fs::write(path, board_to_png(board));
+        }
+    });
+    game.run_to_end();
+
+    let (nb_frames, ref result) = *frames.borrow();
+    match result {
+        Ok(()) => Ok(nb_frames),
+        Err(e) => Err(io::Error::new(e.kind(), e.to_string())),
+    }
+}
+
+fn write_chunk(png: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    png.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    let start = png.len();
+    png.extend_from_slice(kind);
+    png.extend_from_slice(data);
+    let crc = crc32(&png[start..]);
+    png.extend_from_slice(&crc.to_be_bytes());
+}
+
+/// Wraps `data` in a zlib stream made of uncompressed deflate blocks.
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    const MAX_BLOCK: usize = 0xFFFF;
+
+    let mut stream = vec![0x78, 0x01];
+    let mut blocks = data.chunks(MAX_BLOCK).peekable();
+    while let Some(block) = blocks.next() {
+        let is_last = blocks.peek().is_none();
+        stream.push(is_last as u8);
+        let len = block.len() as u16;
+        stream.extend_from_slice(&len.to_le_bytes());
+        stream.extend_from_slice(&(!len).to_le_bytes());
+        stream.extend_from_slice(block);
+    }
+    stream.extend_from_slice(&adler32(data).to_be_bytes());
+    stream
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0_u32;
+    for &byte in data {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            let mask = (crc & 1).wrapping_neg();
+            crc = (crc >> 1) ^ (0xEDB8_8320 & mask);
+        }
+    }
+    !crc
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    let (mut a, mut b) = (1_u32, 0_u32);
+    for &byte in data {
+        a = (a + byte as u32) % 65521;
+        b = (b + a) % 65521;
+    }
+    (b << 16) | a
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::heuristic_bot::HeuristicBot;
+
+    #[test]
+    fn checksums_match_the_reference_values() {
+        assert_eq!(crc32(b"IEND"), 0xAE42_6082);
+        assert_eq!(adler32(b"Wikipedia"), 0x11E6_0398);
+    }
+
+    #[test]
+    fn boards_are_encoded_as_png() {
+        let board = GameBoard::from_ascii("o#").unwrap();
+        let png = board_to_png(&board);
+
+        assert!(png.starts_with(b"\x89PNG\r\n\x1a\n"));
+        assert_eq!(&png[12..16], b"IHDR");
+        let width = u32::from_be_bytes([png[16], png[17], png[18], png[19]]);
+        assert_eq!(width as usize, board.width() as usize * CELL_SIZE);
+        assert!(png.ends_with(&[0, 0, 0, 0, b'I', b'E', b'N', b'D', 0xAE, 0x42, 0x60, 0x82]));
+    }
+
+    #[test]
+    fn games_are_rendered_step_by_step() {
+        let dir = std::env::temp_dir().join("genetic-snake-rs-render-test");
+        let mut game = Game::with_seed(0);
+        game.continue_simulation_if_known_winner(false)
+            .max_steps(Game::MAX_UNWATCHED_STEPS)
+            .add_snake(0, Box::new(HeuristicBot::default()))
+            .add_snake(1, Box::new(HeuristicBot::default()))
+            .initialize();
+
+        let nb_frames = render_game(&mut game, &dir, "test").unwrap();
+        assert!(nb_frames > 1);
+        assert!(dir.join(format!("test_step{:04}.png", nb_frames)).exists());
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,369 @@
+//! Setting up and running games: single matches, series of games to
+//! evaluate bots, and speed tests. For reliable performance numbers, use the
+//! criterion benchmarks with `cargo bench`.
+
+use std::{
+    cell::RefCell,
+    fmt,
+    rc::Rc,
+    thread,
+    time::{Duration, Instant},
+};
+
+use indicatif::{ProgressBar, ProgressStyle};
+
+use crate::game_engine::*;
+
+/// How to set up and show a game, see `build_game()`.
+#[derive(Debug, Clone, Default)]
+pub struct MatchConfig {
+    /// The seed of the game, `None` for a random one.
+    pub seed: Option<u64>,
+    /// Whether to continue the game with the remaining snake once the winner
+    /// is known.
+    pub continue_if_winner: bool,
+    /// Whether to print the board at each step.
+    pub print: bool,
+    /// A pause after each step, to be able to follow the game.
+    pub step_delay: Option<Duration>,
+    /// Whether Ctrl+C stops the game instead of the program.
+    pub interruptible: bool,
+    pub board_size: BoardSize,
+    /// The game is decided after this number of steps, see
+    /// `Game::max_steps()`. `None` for no limit.
+    pub max_steps: Option<u32>,
+}
+
+/// Creates an initialized game between the bots, the `i`-th bot controlling
+/// the snake `i`.
+pub fn build_game<'a>(bots: Vec<Box<dyn SnakeBot + 'a>>, config: &MatchConfig) -> Game<'a> {
+    let mut game = match config.seed {
+        Some(seed) => Game::with_seed(seed),
+        None => Game::default(),
+    };
+    let BoardSize { width, height } = config.board_size;
+    game.with_board_size(width, height)
+        .continue_simulation_if_known_winner(config.continue_if_winner)
+        .interruptible(config.interruptible);
+    if let Some(max_steps) = config.max_steps {
+        game.max_steps(max_steps);
+    }
+    for (id, bot) in bots.into_iter().enumerate() {
+        game.add_snake(id as SnakeId, bot);
+    }
+    if config.print {
+        game.after_each_step(|board: &GameBoard| board.print());
+    }
+    if let Some(delay) = config.step_delay {
+        game.after_each_step(move |_| thread::sleep(delay));
+    }
+    game.initialize();
+    game
+}
+
+/// Plays one game between the bots, see `build_game()`.
+pub fn run_match<'a>(bots: Vec<Box<dyn SnakeBot + 'a>>, config: &MatchConfig) -> GameResults {
+    let mut game = build_game(bots, config);
+    if config.print {
+        game.print();
+    }
+    game.run_to_end()
+}
+
+/// The outcome of `run_speed_test()`.
+#[derive(Debug, Clone)]
+pub struct SpeedStats {
+    /// The names of the bots.
+    pub configuration: Vec<String>,
+    pub nb_simulations: usize,
+    pub total_steps: u128,
+    pub duration: Duration,
+}
+
+impl fmt::Display for SpeedStats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let duration = self.duration.as_secs_f64() * 1000.;
+        write!(
+            f,
+            "Simulation with {} bots ({}) ended:\n\
+             \t- {:12} simulations\n\
+             \t- {:12} total steps\n\
+             \t- {:12.3} total time ms\n\
+             \t- {:12.3} steps/simulation\n\
+             \t- {:12.3} simulations/sec\n\
+             \t- {:12.3} steps/sec",
+            self.configuration.len(),
+            self.configuration.join(" vs "),
+            self.nb_simulations,
+            self.total_steps,
+            duration,
+            self.total_steps as f64 / self.nb_simulations as f64,
+            self.nb_simulations as f64 / (duration / 1000.),
+            self.total_steps as f64 / (duration / 1000.)
+        )
+    }
+}
+
+/// Measures the speed of `nb_simulations` games between the given bots, one
+/// snake each. A progress bar is shown, unless the games are printed.
+pub fn run_speed_test(
+    bots: &[(String, BotFactory)],
+    nb_simulations: usize,
+    config: &MatchConfig,
+) -> SpeedStats {
+    // The progress bar, only if the games aren't printed
+    const PROGRESS_UPDATE_PERIOD: usize = 200;
+    let progress_bar = if config.print {
+        ProgressBar::hidden()
+    } else {
+        ProgressBar::new(nb_simulations as u64)
+    };
+    progress_bar.set_style(
+        ProgressStyle::default_bar()
+            .template(
+                "{spinner:.green} [{elapsed_precise}] [{wide_bar:0.cyan/blue}] {pos}/{len} \
+                 (ETA {eta})",
+            )
+            .progress_chars("#>-"),
+    );
+
+    let start_time = Instant::now();
+    let mut total_steps: u128 = 0;
+    for simulation in 0..nb_simulations {
+        if simulation % PROGRESS_UPDATE_PERIOD == 0 {
+            progress_bar.set_position(simulation as u64);
+        }
+
+        let bots = bots.iter().map(|(_, create_bot)| create_bot()).collect();
+        let results = run_match(bots, config);
+        if config.print {
+            println!("{}", results);
+        }
+
+        // Keep track of the total number of steps
+        total_steps += results.steps as u128;
+    }
+    progress_bar.finish_and_clear();
+
+    SpeedStats {
+        configuration: bots.iter().map(|(name, _)| name.clone()).collect(),
+        nb_simulations,
+        total_steps,
+        duration: start_time.elapsed(),
+    }
+}
+
+/// The aggregated results of a series of games, see `run_series()`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SeriesStats {
+    pub nb_games: usize,
+    /// The ratio of games won by each snake, by ID.
+    pub win_rates: Vec<f64>,
+    /// The ratio of games without winner.
+    pub draw_rate: f64,
+    pub average_steps: f64,
+    /// The average length of each snake at the end of the games, by ID.
+    pub average_lengths: Vec<f64>,
+}
+
+impl fmt::Display for SeriesStats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "{} games, {:.1} steps on average, {:.1}% of draws",
+            self.nb_games,
+            self.average_steps,
+            100. * self.draw_rate
+        )?;
+        for (id, (win_rate, length)) in self.win_rates.iter().zip(&self.average_lengths).enumerate()
+        {
+            writeln!(
+                f,
+                "  Snake {}: {:5.1}% of wins, length {:.1} on average",
+                id,
+                100. * win_rate,
+                length
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Plays `nb_games` games between the bots, the `i`-th factory creating the
+/// bot of the snake `i`. The games are seeded with `seed_base`,
+/// `seed_base + 1`, etc., so the series is reproducible if the bots are.
+pub fn run_series(bot_factories: &[BotFactory], nb_games: usize, seed_base: u64) -> SeriesStats {
+    run_series_with(bot_factories, nb_games, seed_base, |_| {})
+}
+
+/// Same as `run_series()`, but `set_up` is called on each initialized game,
+/// e.g. to register callbacks.
+fn run_series_with<F>(
+    bot_factories: &[BotFactory],
+    nb_games: usize,
+    seed_base: u64,
+    set_up: F,
+) -> SeriesStats
+where
+    F: Fn(&mut Game),
+{
+    let nb_snakes = bot_factories.len();
+    let mut wins = vec![0; nb_snakes];
+    let mut draws = 0;
+    let mut total_steps = 0;
+    let mut total_lengths = vec![0; nb_snakes];
+
+    for game_idx in 0..nb_games {
+        let config = MatchConfig {
+            seed: Some(seed_base.wrapping_add(game_idx as u64)),
+            max_steps: Some(Game::MAX_UNWATCHED_STEPS),
+            ..MatchConfig::default()
+        };
+        let bots = bot_factories
+            .iter()
+            .map(|create_bot| create_bot())
+            .collect();
+        let mut game = build_game(bots, &config);
+        set_up(&mut game);
+        let results = game.run_to_end();
+
+        match results.winner {
+            Some(GameResultWinner::Winner(id)) => wins[id as usize] += 1,
+            Some(GameResultWinner::Draw) => draws += 1,
+            Some(GameResultWinner::SoloGameOver) | None => {}
+        }
+        total_steps += results.steps as usize;
+        for snake in &results.snakes {
+            total_lengths[snake.id as usize] += snake.length;
+        }
+    }
+
+    let average = |total: usize| {
+        if nb_games == 0 {
+            0.
+        } else {
+            total as f64 / nb_games as f64
+        }
+    };
+    SeriesStats {
+        nb_games,
+        win_rates: wins.into_iter().map(average).collect(),
+        draw_rate: average(draws),
+        average_steps: average(total_steps),
+        average_lengths: total_lengths.into_iter().map(average).collect(),
+    }
+}
+
+/// How often each cell was occupied by a snake, over many games. This shows,
+/// for instance, whether a bot hugs the walls or avoids the center.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Heatmap {
+    pub snake_id: SnakeId,
+    size: BoardSize,
+    /// The number of steps during which each cell was occupied, by position.
+    visits: Vec<u32>,
+}
+
+impl Heatmap {
+    /// The characters of `render_ascii()`, from the least to the most visited
+    /// cells.
+    const INTENSITIES: &'static [char] = &[' ', '.', ':', '-', '=', '+', '*', '#', '%', '@'];
+
+    /// A heatmap for the games on boards of `size`.
+    pub fn new(snake_id: SnakeId, size: BoardSize) -> Self {
+        Heatmap {
+            snake_id,
+            size,
+            visits: vec![0; size.nb_cells()],
+        }
+    }
+
+    /// Counts the cells occupied by the snake on `board`.
+    pub fn record(&mut self, board: &GameBoard) {
+        for (coord, cell) in board.iter_cells() {
+            if cell.snake_id() == Some(self.snake_id) {
+                self.visits[coord.to_pos(self.size) as usize] += 1;
+            }
+        }
+    }
+
+    pub fn visits_at(&self, coord: &Coordinate) -> u32 {
+        self.visits[coord.to_pos(self.size) as usize]
+    }
+
+    /// The board with one character per cell, denser for the most visited
+    /// cells. The scale is linear, the maximum being the most visited cell.
+    pub fn render_ascii(&self) -> String {
+        let max_visits = self.visits.iter().cloned().max().unwrap_or(0).max(1);
+        let width = self.size.width as usize;
+        let border = format!("+{}+\n", "-".repeat(width));
+
+        let mut ascii = border.clone();
+        for row in self.visits.chunks(width) {
+            ascii.push('|');
+            for &visits in row {
+                let level = visits as usize * (Self::INTENSITIES.len() - 1) / max_visits as usize;
+                ascii.push(Self::INTENSITIES[level]);
+            }
+            ascii.push_str("|\n");
+        }
+        ascii.push_str(&border);
+        ascii
+    }
+}
+
+/// Runs a series of games, see `run_series()`, and records where the snake
+/// `snake_id` went at each step.
+pub fn run_heatmap(
+    bot_factories: &[BotFactory],
+    nb_games: usize,
+    seed_base: u64,
+    snake_id: SnakeId,
+) -> (SeriesStats, Heatmap) {
+    // The callbacks of the games must be 'static, hence the shared pointer
+    let heatmap = Rc::new(RefCell::new(Heatmap::new(snake_id, BoardSize::default())));
+    let stats = run_series_with(bot_factories, nb_games, seed_base, |game| {
+        let heatmap = heatmap.clone();
+        game.after_each_step(move |board| heatmap.borrow_mut().record(board));
+    });
+    let heatmap = heatmap.borrow().clone();
+    (stats, heatmap)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::heuristic_bot::{HeuristicBot, GOOD_WEIGHTS};
+
+    fn heuristic_bots() -> Vec<BotFactory<'static>> {
+        (0..2)
+            .map(|_| HeuristicBot::factory(&GOOD_WEIGHTS))
+            .collect()
+    }
+
+    #[test]
+    fn series_are_reproducible() {
+        let stats = run_series(&heuristic_bots(), 10, 7);
+        assert_eq!(stats.nb_games, 10);
+        assert_eq!(stats, run_series(&heuristic_bots(), 10, 7));
+
+        let total_rate: f64 = stats.win_rates.iter().sum::<f64>() + stats.draw_rate;
+        assert!((total_rate - 1.).abs() < 1e-9);
+        assert!(stats.average_lengths.iter().all(|length| *length >= 1.));
+    }
+
+    #[test]
+    fn heatmaps_show_the_most_visited_cells() {
+        let (stats, heatmap) = run_heatmap(&heuristic_bots(), 3, 7, 0);
+        assert_eq!(stats, run_series(&heuristic_bots(), 3, 7));
+        assert_eq!(heatmap, run_heatmap(&heuristic_bots(), 3, 7, 0).1);
+
+        let ascii = heatmap.render_ascii();
+        let lines: Vec<&str> = ascii.lines().collect();
+        assert_eq!(lines.len(), BoardSize::default().height as usize + 2);
+        assert!(lines
+            .iter()
+            .all(|line| line.chars().count() == BoardSize::default().width as usize + 2));
+        assert!(ascii.contains('@'));
+    }
+}
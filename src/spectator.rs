@@ -0,0 +1,120 @@
+//! Watching a game with keyboard controls: pausing, stepping and changing
+//! the speed.
+
+use std::{
+    cmp::{max, min},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, Receiver, RecvTimeoutError},
+        Arc,
+    },
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
+};
+
+use console::{Key, Style, Term};
+
+use crate::game_engine::*;
+use crate::interrupt::catch_interrupts;
+
+const MIN_STEP_DELAY: Duration = Duration::from_millis(25);
+const MAX_STEP_DELAY: Duration = Duration::from_millis(3200);
+/// How often Ctrl+C is checked while the game is paused.
+const PAUSED_POLL_DELAY: Duration = Duration::from_millis(100);
+
+/// Plays `game` to the end, waiting `step_delay` between the steps, while
+/// the keyboard controls it: space pauses and resumes the game, `n` plays
+/// one step while it's paused, `+` and `-` change the speed, and `q` or
+/// Escape stops it. The board isn't printed, see `Game::after_each_step()`.
+///
+/// The bots must not read the keyboard, e.g. `InteractiveBot`. If stdout
+/// isn't a terminal, the game simply runs with `step_delay`.
+pub fn spectate(game: &mut Game, step_delay: Duration) -> GameResults {
+    let term = Term::stdout();
+    if !term.is_term() {
+        game.after_each_step(move |_| thread::sleep(step_delay));
+        return game.run_to_end();
+    }
+
+    let hint = Style::new().yellow();
+    println!(
+        "{}",
+        hint.apply_to("[space] pause/resume, [n] next step, [+/-] speed, [q] stop")
+    );
+
+    let interrupts = catch_interrupts();
+    let done = Arc::new(AtomicBool::new(false));
+    let (keys, reader) = spawn_key_reader(done.clone());
+
+    let mut delay = step_delay;
+    let mut paused = false;
+    let mut stopped = false;
+    'game: while !game.is_game_over() {
+        let deadline = Instant::now() + delay;
+        loop {
+            if interrupts.take() {
+                stopped = true;
+                break 'game;
+            }
+            let timeout = if paused {
+                PAUSED_POLL_DELAY
+            } else {
+                deadline.saturating_duration_since(Instant::now())
+            };
+            match keys.recv_timeout(timeout) {
+                Ok(Key::Char(' ')) => {
+                    paused = !paused;
+                    let status = if paused { "Paused." } else { "Resumed." };
+                    println!("{}", hint.apply_to(status));
+                }
+                Ok(Key::Char('n')) if paused => break,
+                Ok(Key::Char('+')) => delay = max(delay / 2, MIN_STEP_DELAY),
+                Ok(Key::Char('-')) => delay = min(delay * 2, MAX_STEP_DELAY),
+                Ok(Key::Char('q')) | Ok(Key::Escape) => {
+                    stopped = true;
+                    break 'game;
+                }
+                Ok(_) => {}
+                Err(RecvTimeoutError::Timeout) if paused => {}
+                Err(RecvTimeoutError::Timeout) => break,
+                // The terminal can't be read anymore, so the game goes on
+                Err(RecvTimeoutError::Disconnected) => {
+                    paused = false;
+                    thread::sleep(deadline.saturating_duration_since(Instant::now()));
+                    break;
+                }
+            }
+        }
+        game.step();
+    }
+
+    let results = if stopped {
+        game.interrupt()
+    } else {
+        game.run_to_end()
+    };
+
+    // The reader is waiting for a key, which must not be lost by the next
+    // prompts
+    done.store(true, Ordering::SeqCst);
+    if !reader.is_finished() {
+        println!("{}", hint.apply_to("Press any key to continue."));
+    }
+    reader.join().ok();
+    results
+}
+
+/// Reads the keys in a thread, because reading a key blocks. The thread
+/// stops after the first key read once `done` is set.
+fn spawn_key_reader(done: Arc<AtomicBool>) -> (Receiver<Key>, JoinHandle<()>) {
+    let (sender, receiver) = mpsc::channel();
+    let reader = thread::spawn(move || {
+        let term = Term::stdout();
+        while let Ok(key) = term.read_key() {
+            if done.load(Ordering::SeqCst) || sender.send(key).is_err() {
+                break;
+            }
+        }
+    });
+    (receiver, reader)
+}
@@ -0,0 +1,429 @@
+//! Tournaments to rank several bots against each other.
+
+use std::{
+    cmp::{Ordering, Reverse},
+    fmt,
+};
+
+use rand::prelude::*;
+
+use crate::game_engine::*;
+
+/// The tally of one bot during a tournament.
+#[derive(Debug, Clone)]
+pub struct Standing {
+    pub name: String,
+    pub wins: usize,
+    pub draws: usize,
+    pub losses: usize,
+    /// The rounds of a Swiss tournament spent without opponent, which are
+    /// not games but score like wins.
+    pub byes: usize,
+}
+
+impl Standing {
+    fn new(name: String) -> Self {
+        Standing {
+            name,
+            wins: 0,
+            draws: 0,
+            losses: 0,
+            byes: 0,
+        }
+    }
+
+    /// Two points per win or bye and one per draw, like the fitness
+    /// function.
+    pub fn points(&self) -> usize {
+        2 * (self.wins + self.byes) + self.draws
+    }
+
+    pub fn nb_games(&self) -> usize {
+        self.wins + self.draws + self.losses
+    }
+}
+
+/// The ranking of a tournament, the best bot first.
+#[derive(Debug, Clone)]
+pub struct Standings(pub Vec<Standing>);
+
+impl Standings {
+    fn sorted(mut standings: Vec<Standing>) -> Self {
+        standings.sort_by(|a, b| {
+            b.points()
+                .cmp(&a.points())
+                .then_with(|| b.wins.cmp(&a.wins))
+        });
+        Standings(standings)
+    }
+}
+
+impl fmt::Display for Standings {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "{:>3}  {:<30} {:>5} {:>5} {:>5} {:>5} {:>5} {:>6}",
+            "#", "Bot", "Games", "W", "D", "L", "Byes", "Points"
+        )?;
+        for (rank, standing) in self.0.iter().enumerate() {
+            writeln!(
+                f,
+                "{:>3}  {:<30} {:>5} {:>5} {:>5} {:>5} {:>5} {:>6}",
+                rank + 1,
+                standing.name,
+                standing.nb_games(),
+                standing.wins,
+                standing.draws,
+                standing.losses,
+                standing.byes,
+                standing.points()
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// The head-to-head results of a round-robin, the bots being in the order
+/// they were given.
+#[derive(Debug, Clone)]
+pub struct ResultsMatrix {
+    pub names: Vec<String>,
+    /// `wins[i][j]` is the number of games the bot `i` won against the bot
+    /// `j`.
+    pub wins: Vec<Vec<usize>>,
+    /// The number of games played by each pair of bots.
+    pub games_per_pair: usize,
+}
+
+impl ResultsMatrix {
+    fn new(names: Vec<String>, games_per_pair: usize) -> Self {
+        let nb_bots = names.len();
+        ResultsMatrix {
+            names,
+            wins: vec![vec![0; nb_bots]; nb_bots],
+            games_per_pair,
+        }
+    }
+
+    /// The wins, draws and losses of the bot `i` against the bot `j`.
+    pub fn record(&self, i: usize, j: usize) -> (usize, usize, usize) {
+        let (wins, losses) = (self.wins[i][j], self.wins[j][i]);
+        (wins, self.games_per_pair - wins - losses, losses)
+    }
+}
+
+impl fmt::Display for ResultsMatrix {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // Each cell is the W-D-L of the row bot against the column bot
+        write!(f, "{:<30}", "W-D-L")?;
+        for j in 0..self.names.len() {
+            write!(f, " {:>9}", format!("#{}", j + 1))?;
+        }
+        writeln!(f)?;
+        for (i, name) in self.names.iter().enumerate() {
+            write!(f, "{:<30}", format!("#{} {}", i + 1, name))?;
+            for j in 0..self.names.len() {
+                if i == j {
+                    write!(f, " {:>9}", "-")?;
+                } else {
+                    let (wins, draws, losses) = self.record(i, j);
+                    write!(f, " {:>9}", format!("{}-{}-{}", wins, draws, losses))?;
+                }
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+/// The outcome of `run_round_robin()`.
+#[derive(Debug, Clone)]
+pub struct RoundRobin {
+    pub matrix: ResultsMatrix,
+    pub standings: Standings,
+}
+
+impl fmt::Display for RoundRobin {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "{}", self.matrix)?;
+        write!(f, "{}", self.standings)
+    }
+}
+
+/// Plays one seeded game between two bots, the first one being the snake 0.
+/// Returns the winner, if any: `Some(0)` for the first bot and `Some(1)` for
+/// the second one.
+fn play_game(first: &BotFactory, second: &BotFactory, seed: u64) -> Option<usize> {
+    let results = Game::with_seed(seed)
+        .continue_simulation_if_known_winner(false)
+        .max_steps(Game::MAX_UNWATCHED_STEPS)
+        .with_symmetric_starts()
+        .add_snake(0, first())
+        .add_snake(1, second())
+        .initialize()
+        .run_to_end();
+
+    match results.winner {
+        Some(GameResultWinner::Winner(id)) => Some(id as usize),
+        _ => None,
+    }
+}
+
+/// Plays `nb_games` games between two bots in both ways, to cancel any
+/// first-move advantage, and updates their standings. Returns the number of
+/// games won by each of them.
+fn play_pairing(
+    bots: &[(String, BotFactory)],
+    standings: &mut [Standing],
+    (i, j): (usize, usize),
+    nb_games: usize,
+    rng: &mut StdRng,
+) -> (usize, usize) {
+    let mut wins = (0, 0);
+    for _ in 0..nb_games {
+        // Same board for both ways
+        let game_seed = rng.gen();
+        for &(first, second) in &[(i, j), (j, i)] {
+            match play_game(&bots[first].1, &bots[second].1, game_seed) {
+                Some(winner) => {
+                    let (winner, loser) = if winner == 0 {
+                        (first, second)
+                    } else {
+                        (second, first)
+                    };
+                    standings[winner].wins += 1;
+                    standings[loser].losses += 1;
+                    if winner == i {
+                        wins.0 += 1;
+                    } else {
+                        wins.1 += 1;
+                    }
+                }
+                None => {
+                    standings[first].draws += 1;
+                    standings[second].draws += 1;
+                }
+            }
+        }
+    }
+    wins
+}
+
+/// Plays `games_per_pair` games between each pair of bots, in both ways to
+/// cancel any first-move advantage, and returns the head-to-head results
+/// with the resulting standings.
+///
+/// The bots are given as factories, since a bot is owned by the game it
+/// plays. The results only depend on `seed` (given that the bots are
+/// deterministic).
+pub fn run_round_robin(
+    bots: Vec<(String, BotFactory)>,
+    games_per_pair: usize,
+    seed: u64,
+) -> RoundRobin {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut standings: Vec<Standing> = bots
+        .iter()
+        .map(|(name, _)| Standing::new(name.clone()))
+        .collect();
+    let mut matrix = ResultsMatrix::new(
+        bots.iter().map(|(name, _)| name.clone()).collect(),
+        2 * games_per_pair,
+    );
+
+    for i in 0..bots.len() {
+        for j in (i + 1)..bots.len() {
+            let wins = play_pairing(&bots, &mut standings, (i, j), games_per_pair, &mut rng);
+            matrix.wins[i][j] = wins.0;
+            matrix.wins[j][i] = wins.1;
+        }
+    }
+
+    RoundRobin {
+        matrix,
+        standings: Standings::sorted(standings),
+    }
+}
+
+/// Runs a single-elimination bracket, where each match is made of
+/// `games_per_match` games in both ways. The bots are seeded in the given
+/// order: when the number of remaining bots is odd, the best seed gets a bye.
+///
+/// A tied match is decided by up to `MAX_TIEBREAK_GAMES` seeded tiebreak
+/// games, and then by a coin flip.
+///
+/// Returns the final ranking: the winner first, then the bots sorted by the
+/// round in which they were eliminated (ties are broken with the points).
+pub fn run_elimination(
+    bots: Vec<(String, BotFactory)>,
+    games_per_match: usize,
+    seed: u64,
+) -> Standings {
+    const MAX_TIEBREAK_GAMES: usize = 5;
+
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut standings: Vec<Standing> = bots
+        .iter()
+        .map(|(name, _)| Standing::new(name.clone()))
+        .collect();
+
+    // The bots eliminated in each round, the last round last
+    let mut eliminated: Vec<Vec<usize>> = vec![];
+    let mut remaining: Vec<usize> = (0..bots.len()).collect();
+    while remaining.len() > 1 {
+        let mut next_round = vec![];
+        let mut losers = vec![];
+
+        // Bye for the best seed
+        let mut players = &remaining[..];
+        if players.len() % 2 == 1 {
+            next_round.push(players[0]);
+            players = &players[1..];
+        }
+
+        // Pair the best remaining seed with the worst one
+        for k in 0..players.len() / 2 {
+            let (i, j) = (players[k], players[players.len() - 1 - k]);
+            let mut wins = play_pairing(&bots, &mut standings, (i, j), games_per_match, &mut rng);
+            for _ in 0..MAX_TIEBREAK_GAMES {
+                if wins.0 != wins.1 {
+                    break;
+                }
+                let tiebreak = play_pairing(&bots, &mut standings, (i, j), 1, &mut rng);
+                wins = (wins.0 + tiebreak.0, wins.1 + tiebreak.1);
+            }
+            let first_wins = match wins.0.cmp(&wins.1) {
+                Ordering::Greater => true,
+                Ordering::Less => false,
+                Ordering::Equal => rng.gen(),
+            };
+            let (winner, loser) = if first_wins { (i, j) } else { (j, i) };
+            next_round.push(winner);
+            losers.push(loser);
+        }
+
+        // Keep the seeding order for the next round
+        next_round.sort();
+        eliminated.push(losers);
+        remaining = next_round;
+    }
+
+    // Build the ranking
+    let mut ranking: Vec<usize> = remaining;
+    for mut losers in eliminated.into_iter().rev() {
+        losers.sort_by_key(|&i| Reverse(standings[i].points()));
+        ranking.extend(losers);
+    }
+    Standings(ranking.into_iter().map(|i| standings[i].clone()).collect())
+}
+
+/// Runs a Swiss-system tournament of `nb_rounds` rounds: at each round, the
+/// bots are paired with bots having similar scores, avoiding rematches when
+/// possible. Each pairing is made of `games_per_pairing` games in both ways.
+///
+/// When the number of bots is odd, the worst bot which didn't have a bye yet
+/// gets one, which counts as a win.
+pub fn run_swiss(
+    bots: Vec<(String, BotFactory)>,
+    nb_rounds: usize,
+    games_per_pairing: usize,
+    seed: u64,
+) -> Standings {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut standings: Vec<Standing> = bots
+        .iter()
+        .map(|(name, _)| Standing::new(name.clone()))
+        .collect();
+
+    let mut already_paired: Vec<(usize, usize)> = vec![];
+    let mut had_bye = vec![false; bots.len()];
+    for _ in 0..nb_rounds {
+        // Sort by score, equal scores in random order
+        let mut players: Vec<usize> = (0..bots.len()).collect();
+        players.shuffle(&mut rng);
+        players.sort_by_key(|&i| Reverse(standings[i].points()));
+
+        // Give a bye to the worst player without one
+        if players.len() % 2 == 1 {
+            let bye_idx = (0..players.len())
+                .rev()
+                .find(|&k| !had_bye[players[k]])
+                .unwrap_or(players.len() - 1);
+            let bye = players.remove(bye_idx);
+            had_bye[bye] = true;
+            standings[bye].byes += 1;
+        }
+
+        // Pair each player with the next one it didn't already play against
+        while !players.is_empty() {
+            let i = players.remove(0);
+            let opponent_idx = players
+                .iter()
+                .position(|&j| !already_paired.contains(&(i.min(j), i.max(j))))
+                .unwrap_or(0);
+            let j = players.remove(opponent_idx);
+            already_paired.push((i.min(j), i.max(j)));
+            play_pairing(&bots, &mut standings, (i, j), games_per_pairing, &mut rng);
+        }
+    }
+
+    Standings::sorted(standings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{floodfill_bot::FloodFillBot, random_bot::RandomBot};
+
+    #[test]
+    fn round_robin_matrix_matches_the_standings() {
+        let bots = vec![
+            ("random".to_string(), RandomBot::factory()),
+            ("flood-fill".to_string(), FloodFillBot::factory()),
+            ("random again".to_string(), RandomBot::factory()),
+        ];
+        let results = run_round_robin(bots, 2, 42);
+
+        let matrix = &results.matrix;
+        for standing in &results.standings.0 {
+            let i = matrix
+                .names
+                .iter()
+                .position(|name| *name == standing.name)
+                .unwrap();
+            let (mut wins, mut draws, mut losses) = (0, 0, 0);
+            for j in (0..matrix.names.len()).filter(|&j| j != i) {
+                let (w, d, l) = matrix.record(i, j);
+                assert_eq!(w + d + l, 4);
+                assert_eq!(matrix.record(j, i), (l, d, w));
+                wins += w;
+                draws += d;
+                losses += l;
+            }
+            assert_eq!(
+                (wins, draws, losses),
+                (standing.wins, standing.draws, standing.losses)
+            );
+        }
+    }
+
+    #[test]
+    fn swiss_byes_are_not_games() {
+        let bots = vec![
+            ("random".to_string(), RandomBot::factory()),
+            ("flood-fill".to_string(), FloodFillBot::factory()),
+            ("random again".to_string(), RandomBot::factory()),
+        ];
+        let standings = run_swiss(bots, 3, 1, 42);
+
+        // Each round, one bot gets a bye and the two others play two games
+        let nb_byes: usize = standings.0.iter().map(|s| s.byes).sum();
+        let nb_games: usize = standings.0.iter().map(Standing::nb_games).sum();
+        assert_eq!(nb_byes, 3);
+        assert_eq!(nb_games, 3 * 2 * 2);
+        for standing in &standings.0 {
+            assert_eq!(standing.byes, 1);
+            assert_eq!(standing.nb_games(), 4);
+        }
+    }
+}